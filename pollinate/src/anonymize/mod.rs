@@ -0,0 +1,300 @@
+//! # Pollinate Anonymize Module
+//!
+//! The `anonymize` module replaces selected values of an existing JSON document with freshly generated ones.
+//!
+//! ## Overview
+//!
+//! Values to replace are chosen by rules, each pairing a path with a schema used to generate the replacement.
+//! Paths are a subset of JSONPath: `$` for the root, `.name` or `['name']` for a key, `[0]` for an index,
+//! `[*]` or `.*` for every element and `..name` for a key at any depth, e.g. `$.users[*].email`.
+//! Rules can be read from a file mapping paths to schemas, or derived from a pollinate schema where every
+//! non object or array property is replaced. Everything not matched by a rule is left untouched, as are nulls
+//! and properties of a schema that have no type to generate replacements from.
+//!
+//! ```json
+//! {
+//!   "$.users[*].email": { "type": "string", "format": "email" },
+//!   "$..lastName": { "type": "string", "enum": ["Smith", "Jones"] }
+//! }
+//! ```
+//!
+//! ## Functions
+//!
+//! - `parse_path`: Parses a JSONPath into segments.
+//! - `read_rules`: Reads rules from a file mapping paths to schemas.
+//! - `rules_from_schema`: Creates a rule for every leaf property of a schema.
+//! - `anonymize`: Replaces every value matched by the rules.
+//! - `anonymize_with_schema`: Replaces every value described by a schema, for one record or an array of them.
+//!
+
+use serde_json::{Map, Value};
+use std::io;
+
+use crate::default_values::Values;
+use crate::json_utils::read_input;
+use crate::schema::parse_type;
+
+/// A single step of a JSONPath.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// Value of an object key
+    Key(String),
+    /// Element of an array
+    Index(usize),
+    /// Every element of an array or value of an object
+    Wildcard,
+    /// Value of an object key at any depth
+    Descendant(String),
+}
+
+/// Replaces the values at a path with values from a generator.
+#[derive(Clone)]
+pub struct Rule {
+    pub path: Vec<PathSegment>,
+    pub values: Box<dyn Values>,
+}
+
+impl Rule {
+    /// Fails with an `InvalidInput` error when the path can't be parsed.
+    pub fn new(path: &str, values: Box<dyn Values>) -> io::Result<Self> {
+        Ok(Rule {
+            path: parse_path(path)?,
+            values,
+        })
+    }
+}
+
+/// Parses a JSONPath into segments.
+/// Fails with an `InvalidInput` error for a path that doesn't start with `$`, an unclosed bracket or an index
+/// that isn't a number.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::anonymize::*;
+///
+/// let path = parse_path("$.users[*].emails[0]").unwrap();
+/// assert_eq!(
+///     path,
+///     vec![
+///         PathSegment::Key("users".to_string()),
+///         PathSegment::Wildcard,
+///         PathSegment::Key("emails".to_string()),
+///         PathSegment::Index(0),
+///     ]
+/// );
+/// assert!(parse_path("users[0]").is_err());
+/// ```
+pub fn parse_path(path: &str) -> io::Result<Vec<PathSegment>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| invalid(format!("Path {} must start with $", path)))?;
+    let mut segments = Vec::<PathSegment>::new();
+    let mut chars = rest.chars().peekable();
+    let read_name = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '.' && *c != '[') {
+            name.push(c);
+        }
+        name
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '.' if chars.next_if_eq(&'.').is_some() => {
+                segments.push(PathSegment::Descendant(read_name(&mut chars)));
+            }
+            '.' => match read_name(&mut chars).as_str() {
+                "*" => segments.push(PathSegment::Wildcard),
+                name => segments.push(PathSegment::Key(name.to_owned())),
+            },
+            '[' => {
+                let mut inner = String::new();
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        closed = true;
+                        break;
+                    }
+                    inner.push(c);
+                }
+                if !closed {
+                    return Err(invalid(format!("Unclosed [ in path {}", path)));
+                }
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|x| x.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|x| x.strip_suffix('"')));
+                segments.push(match quoted {
+                    Some(name) => PathSegment::Key(name.to_owned()),
+                    None if inner == "*" => PathSegment::Wildcard,
+                    None => {
+                        PathSegment::Index(inner.parse().map_err(|_| {
+                            invalid(format!("Invalid index [{}] in {}", inner, path))
+                        })?)
+                    }
+                });
+            }
+            _ => return Err(invalid(format!("Unexpected {} in path {}", c, path))),
+        }
+    }
+    Ok(segments)
+}
+
+/// Reads rules from a file containing an object that maps paths to schemas.
+/// Fails with an `InvalidInput` error for a rule whose path can't be parsed or whose schema has no type values
+/// can be generated for.
+pub fn read_rules(rules_path: &str) -> io::Result<Vec<Rule>> {
+    let rules_string = read_input(rules_path)?;
+    let parsed_rules: Map<String, Value> = serde_json::from_str(&rules_string)?;
+    parsed_rules
+        .iter()
+        .map(|(path, details)| {
+            let values = parse_type(details)?.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Rule {} has no type to generate values from", path),
+                )
+            })?;
+            Rule::new(path, values)
+        })
+        .collect()
+}
+
+/// Creates a rule for every property of a schema that is not an object or an array.
+/// Objects and arrays are walked so only the values within them are replaced.
+/// Properties without a type are left out, as they are when generating records.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::anonymize::*;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": {
+///         "users": {
+///             "type": "array",
+///             "maximum": 1,
+///             "items": {
+///                 "type": "object",
+///                 "properties": {"email": {"type": "string", "format": "email"}}
+///             }
+///         }
+///     }
+/// });
+/// let rules = rules_from_schema(&schema).unwrap();
+/// assert_eq!(rules.len(), 1);
+/// assert_eq!(rules[0].path, parse_path("$.users[*].email").unwrap());
+/// ```
+pub fn rules_from_schema(schema: &Value) -> io::Result<Vec<Rule>> {
+    let mut rules = Vec::<Rule>::new();
+    collect_rules(schema, Vec::new(), &mut rules)?;
+    Ok(rules)
+}
+
+fn collect_rules(details: &Value, path: Vec<PathSegment>, rules: &mut Vec<Rule>) -> io::Result<()> {
+    let is_leaf = details.get("x-sequence").is_some() || details.get("enum").is_some();
+    match details.get("type").and_then(|x| x.as_str()) {
+        Some("object") if !is_leaf && details.get("properties").is_some() => {
+            for (property, details) in details.get("properties").unwrap().as_object().unwrap() {
+                let mut path = path.clone();
+                path.push(PathSegment::Key(property.to_owned()));
+                collect_rules(details, path, rules)?;
+            }
+        }
+        Some("array") if !is_leaf => {
+            let mut path = path;
+            path.push(PathSegment::Wildcard);
+            collect_rules(details.get("items").unwrap_or(&Value::Null), path, rules)?;
+        }
+        _ => {
+            if let Some(values) = parse_type(details)? {
+                rules.push(Rule { path, values });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces every value matched by the path with a generated one.
+fn replace(value: &mut Value, path: &[PathSegment], values: &dyn Values) {
+    let Some((segment, rest)) = path.split_first() else {
+        if !value.is_null() {
+            *value = values.get_value();
+        }
+        return;
+    };
+    match (segment, value) {
+        (PathSegment::Key(key), Value::Object(map)) => {
+            if let Some(value) = map.get_mut(key) {
+                replace(value, rest, values);
+            }
+        }
+        (PathSegment::Index(index), Value::Array(array)) => {
+            if let Some(value) = array.get_mut(*index) {
+                replace(value, rest, values);
+            }
+        }
+        (PathSegment::Wildcard, Value::Array(array)) => {
+            for value in array.iter_mut() {
+                replace(value, rest, values);
+            }
+        }
+        (PathSegment::Wildcard, Value::Object(map)) => {
+            for value in map.values_mut() {
+                replace(value, rest, values);
+            }
+        }
+        (PathSegment::Descendant(key), value) => {
+            if let Some(matched) = value.get_mut(key.as_str()) {
+                replace(matched, rest, values);
+            }
+            let children: Vec<&mut Value> = match value {
+                Value::Object(map) => map.values_mut().collect(),
+                Value::Array(array) => array.iter_mut().collect(),
+                _ => Vec::new(),
+            };
+            for child in children {
+                replace(child, path, values);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Replaces every value matched by the rules, leaving the rest of the document untouched.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::anonymize::*;
+/// use pollinate::default_values::*;
+/// use serde_json::json;
+///
+/// let mut document = json!({"users": [{"name": "Adam", "id": 7}, {"name": "John", "id": 8}]});
+/// let rules = vec![Rule::new("$.users[*].name", Box::new(DiscreteValues::new(&[json!("X")]))).unwrap()];
+/// anonymize(&mut document, &rules);
+/// assert_eq!(document, json!({"users": [{"name": "X", "id": 7}, {"name": "X", "id": 8}]}));
+/// ```
+pub fn anonymize(document: &mut Value, rules: &[Rule]) {
+    for rule in rules {
+        replace(document, &rule.path, rule.values.as_ref());
+    }
+}
+
+/// Replaces every value described by a schema.
+/// When the document is an array and the schema an object, each element is anonymized.
+pub fn anonymize_with_schema(document: &mut Value, schema: &Value) -> io::Result<()> {
+    let rules = rules_from_schema(schema)?;
+    match document {
+        Value::Array(records) if schema.get("type") == Some(&Value::from("object")) => {
+            for record in records.iter_mut() {
+                anonymize(record, &rules);
+            }
+        }
+        document => anonymize(document, &rules),
+    }
+    Ok(())
+}
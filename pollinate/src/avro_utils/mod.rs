@@ -0,0 +1,716 @@
+//! # Pollinate Avro Module
+//!
+//! The `avro_utils` module reads Apache Avro schemas and writes generated records to Avro object container files.
+//!
+//! ## Overview
+//!
+//! An `.avsc` schema is converted to a pollinate schema so records can be generated from it like from any other
+//! schema. Records, enums, arrays, maps, unions, fixed and the logical types `decimal`, `uuid`, `date`,
+//! `time-millis`, `time-micros` and the `timestamp` family are supported. The original Avro schema is kept under
+//! `x-avro` so container files are written with it unchanged. For any other schema an Avro schema is derived,
+//! with nested objects as records and nullable types as unions with `null`.
+//!
+//! ## Functions
+//!
+//! - `schema_from_avro`: Converts an Avro schema to a pollinate schema.
+//! - `avro_schema_from_schema`: Derives an Avro schema from a pollinate schema.
+//! - `avro_value`: Converts a generated record to a value of an Avro schema.
+//!
+
+use apache_avro::types::Value as AvroValue;
+use apache_avro::{Decimal, Schema, Uuid, Writer};
+use rand::{thread_rng, Rng};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+
+use crate::json_utils::RecordWriter;
+
+/// Most items generated for Avro arrays and maps.
+const DEFAULT_MAX_ITEMS: u64 = 5;
+
+/// Number of records written to each block of a container file.
+const AVRO_BLOCK_SIZE: usize = 1000;
+
+/// Latest instant generated for timestamps, 2100-01-01 in seconds since the epoch.
+const MAX_TIMESTAMP_SECONDS: i64 = 4_102_444_800;
+
+/// Named types of an Avro schema, by full and short name.
+type Names = HashMap<String, Value>;
+
+/// Error for an Avro schema that can't be converted or written with.
+fn invalid_avro(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Returns a keyword every schema of its kind must have, such as the `items` of an array.
+fn keyword<'a>(map: &'a Map<String, Value>, key: &str, kind: &str) -> io::Result<&'a Value> {
+    map.get(key).ok_or_else(|| {
+        invalid_avro(format!(
+            "Avro {} has no {}, found {}",
+            kind,
+            key,
+            json!(map)
+        ))
+    })
+}
+
+/// Returns the name of a record, enum or fixed type.
+fn type_name<'a>(map: &'a Map<String, Value>, kind: &str) -> io::Result<&'a str> {
+    keyword(map, "name", kind)?.as_str().ok_or_else(|| {
+        invalid_avro(format!(
+            "Avro {} name must be a string, found {}",
+            kind,
+            json!(map)
+        ))
+    })
+}
+
+/// Returns the fields of a record.
+fn record_fields<'a>(map: &'a Map<String, Value>, name: &str) -> io::Result<&'a Vec<Value>> {
+    map.get("fields")
+        .and_then(|x| x.as_array())
+        .ok_or_else(|| invalid_avro(format!("Avro record {} has no list of fields", name)))
+}
+
+/// Returns the name and type of a field of a record.
+fn field_parts<'a>(field: &'a Value, record: &str) -> io::Result<(&'a str, &'a Value)> {
+    let name = field
+        .get("name")
+        .and_then(|x| x.as_str())
+        .ok_or_else(|| invalid_avro(format!("A field of Avro record {} has no name", record)))?;
+    let field_type = field.get("type").ok_or_else(|| {
+        invalid_avro(format!(
+            "Field {} of Avro record {} has no type",
+            name, record
+        ))
+    })?;
+    Ok((name, field_type))
+}
+
+/// Registers every record, enum and fixed type defined within a schema.
+fn collect_names(schema: &Value, namespace: Option<&str>, names: &mut Names) -> io::Result<()> {
+    match schema {
+        Value::Array(branches) => {
+            for branch in branches {
+                collect_names(branch, namespace, names)?;
+            }
+        }
+        Value::Object(map) => match map.get("type").and_then(|x| x.as_str()) {
+            Some(kind @ ("record" | "error" | "enum" | "fixed")) => {
+                let name = type_name(map, kind)?;
+                let (namespace, short) = match name.rsplit_once('.') {
+                    Some((namespace, short)) => (Some(namespace), short),
+                    None => (
+                        map.get("namespace").and_then(|x| x.as_str()).or(namespace),
+                        name,
+                    ),
+                };
+                if let Some(namespace) = namespace {
+                    names.insert(format!("{}.{}", namespace, short), schema.clone());
+                }
+                names.insert(short.to_owned(), schema.clone());
+                if matches!(kind, "record" | "error") {
+                    for field in record_fields(map, name)? {
+                        collect_names(field_parts(field, name)?.1, namespace, names)?;
+                    }
+                }
+            }
+            Some("array") => collect_names(keyword(map, "items", "array")?, namespace, names)?,
+            Some("map") => collect_names(keyword(map, "values", "map")?, namespace, names)?,
+            _ => collect_names(keyword(map, "type", "schema")?, namespace, names)?,
+        },
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Converts a primitive Avro type.
+fn primitive_schema(name: &str) -> Option<Value> {
+    Some(match name {
+        "null" => json!({"type": "null"}),
+        "boolean" => json!({"type": "boolean"}),
+        "int" => json!({"type": "integer", "minimum": i32::MIN, "maximum": i32::MAX}),
+        "long" => json!({"type": "integer", "minimum": i64::MIN, "maximum": i64::MAX}),
+        "float" | "double" => json!({"type": "number"}),
+        "string" | "bytes" => json!({"type": "string"}),
+        _ => return None,
+    })
+}
+
+/// Converts an Avro logical type, generating values in a realistic range.
+fn logical_schema(map: &Map<String, Value>, logical_type: &str) -> io::Result<Option<Value>> {
+    let range = |min: i64, max: i64| json!({"type": "integer", "minimum": min, "maximum": max});
+    Ok(Some(match logical_type {
+        "decimal" => {
+            let digits = |key: &str| {
+                map.get(key)
+                    .and_then(|x| x.as_i64())
+                    .and_then(|x| i32::try_from(x).ok())
+            };
+            let precision = digits("precision").ok_or_else(|| {
+                invalid_avro(format!(
+                    "Avro decimal has no whole precision, found {}",
+                    json!(map)
+                ))
+            })?;
+            let scale = match map.get("scale") {
+                None => 0,
+                Some(_) => digits("scale").ok_or_else(|| {
+                    invalid_avro(format!(
+                        "Avro decimal scale must be whole, found {}",
+                        json!(map)
+                    ))
+                })?,
+            };
+            let max = 10f64.powi(precision - scale) - 10f64.powi(-scale);
+            json!({"type": "number", "minimum": -max, "maximum": max})
+        }
+        "uuid" => json!({"type": "string", "format": "uuid"}),
+        "date" => range(0, MAX_TIMESTAMP_SECONDS / 86_400),
+        "time-millis" => range(0, 86_400_000 - 1),
+        "time-micros" => range(0, 86_400_000_000 - 1),
+        "timestamp-millis" | "local-timestamp-millis" => range(0, MAX_TIMESTAMP_SECONDS * 1_000),
+        "timestamp-micros" | "local-timestamp-micros" => {
+            range(0, MAX_TIMESTAMP_SECONDS * 1_000_000)
+        }
+        "timestamp-nanos" | "local-timestamp-nanos" => {
+            range(0, MAX_TIMESTAMP_SECONDS * 1_000_000_000)
+        }
+        _ => return Ok(None),
+    }))
+}
+
+/// Converts a union, using a nullable type when it is a single type and `null`.
+fn union_schema(
+    branches: &[Value],
+    names: &Names,
+    expanding: &mut Vec<String>,
+) -> io::Result<Value> {
+    let other: Vec<&Value> = branches
+        .iter()
+        .filter(|x| x.as_str() != Some("null"))
+        .collect();
+    let nullable = other.len() < branches.len();
+    if let [branch] = other[..] {
+        let mut converted = convert_schema(branch, names, expanding)?;
+        if !nullable {
+            return Ok(converted);
+        }
+        if let Some(Value::String(type_name)) = converted.get("type").cloned() {
+            converted["type"] = json!([type_name, "null"]);
+            return Ok(converted);
+        }
+        return Ok(json!({"oneOf": [converted, {"type": "null"}]}));
+    }
+    let branches = branches
+        .iter()
+        .map(|x| convert_schema(x, names, expanding))
+        .collect::<io::Result<Vec<Value>>>()?;
+    Ok(json!({"oneOf": branches}))
+}
+
+fn convert_schema(schema: &Value, names: &Names, expanding: &mut Vec<String>) -> io::Result<Value> {
+    match schema {
+        Value::String(name) => match primitive_schema(name) {
+            Some(converted) => Ok(converted),
+            None => {
+                let definition = names
+                    .get(name)
+                    .ok_or_else(|| invalid_avro(format!("Unknown Avro type {}", name)))?;
+                convert_schema(definition, names, expanding)
+            }
+        },
+        Value::Array(branches) => union_schema(branches, names, expanding),
+        Value::Object(map) => {
+            if let Some(logical_type) = map.get("logicalType").and_then(|x| x.as_str()) {
+                if let Some(converted) = logical_schema(map, logical_type)? {
+                    return Ok(converted);
+                }
+            }
+            let kind = keyword(map, "type", "schema")?;
+            Ok(match kind.as_str() {
+                Some("record" | "error") => {
+                    let name = type_name(map, "record")?.to_owned();
+                    if expanding.contains(&name) {
+                        return Err(invalid_avro(format!(
+                            "Recursive Avro type {} is not supported",
+                            name
+                        )));
+                    }
+                    let mut properties = Map::new();
+                    for field in record_fields(map, &name)? {
+                        let (field_name, field_type) = field_parts(field, &name)?;
+                        expanding.push(name.clone());
+                        let converted = convert_schema(field_type, names, expanding);
+                        expanding.pop();
+                        properties.insert(field_name.to_owned(), converted?);
+                    }
+                    json!({"type": "object", "properties": properties})
+                }
+                Some("enum") => json!({"type": "string", "enum": keyword(map, "symbols", "enum")?}),
+                Some("array") => json!({
+                    "type": "array",
+                    "minimum": 0,
+                    "maximum": DEFAULT_MAX_ITEMS,
+                    "items": convert_schema(keyword(map, "items", "array")?, names, expanding)?
+                }),
+                Some("map") => json!({
+                    "type": "object",
+                    "maxProperties": DEFAULT_MAX_ITEMS,
+                    "additionalProperties": convert_schema(keyword(map, "values", "map")?, names, expanding)?
+                }),
+                Some("fixed") => {
+                    let size = keyword(map, "size", "fixed")?;
+                    json!({"type": "string", "minLength": size, "maxLength": size})
+                }
+                _ => convert_schema(kind, names, expanding)?,
+            })
+        }
+        _ => Err(invalid_avro(format!("Invalid Avro schema {}", schema))),
+    }
+}
+
+/// Converts an Avro schema to a pollinate schema.
+/// The top level type must be a record, its name becomes the `title`
+/// and the Avro schema itself is kept under `x-avro`.
+/// Fails with an `InvalidInput` error naming the type or field that can't be converted, such as an unknown type,
+/// a record that contains itself or a decimal without a precision.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::avro_utils::schema_from_avro;
+/// use serde_json::json;
+///
+/// let avro = json!({
+///     "type": "record",
+///     "name": "User",
+///     "fields": [
+///         {"name": "id", "type": {"type": "string", "logicalType": "uuid"}},
+///         {"name": "nickname", "type": ["null", "string"]},
+///         {"name": "role", "type": {"type": "enum", "name": "Role", "symbols": ["ADMIN", "GUEST"]}}
+///     ]
+/// });
+/// let schema = schema_from_avro(&avro).unwrap();
+/// assert_eq!(schema["title"], json!("User"));
+/// assert_eq!(schema["properties"]["id"], json!({"type": "string", "format": "uuid"}));
+/// assert_eq!(schema["properties"]["nickname"], json!({"type": ["string", "null"]}));
+/// assert_eq!(schema["properties"]["role"], json!({"type": "string", "enum": ["ADMIN", "GUEST"]}));
+///
+/// assert!(schema_from_avro(&json!({"type": "record", "name": "User", "fields": [{"name": "id", "type": "Id"}]})).is_err());
+/// ```
+pub fn schema_from_avro(avro: &Value) -> io::Result<Value> {
+    let name = match avro {
+        Value::Object(map) if matches!(map.get("type"), Some(x) if x == "record" || x == "error") => {
+            type_name(map, "record")?
+        }
+        _ => {
+            return Err(invalid_avro(format!(
+                "Avro schemas must have a record at the top level, found {}",
+                avro
+            )))
+        }
+    };
+    let mut names = Names::new();
+    collect_names(avro, None, &mut names)?;
+    let mut schema = convert_schema(avro, &names, &mut Vec::new())?;
+    schema["title"] = json!(name);
+    schema["x-avro"] = avro.clone();
+    Ok(schema)
+}
+
+/// Replaces every character not allowed in an Avro name with an underscore.
+fn avro_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match name.chars().next() {
+        Some(c) if c.is_ascii_digit() => format!("_{}", name),
+        Some(_) => name,
+        None => "Record".to_owned(),
+    }
+}
+
+/// Error for a property of a pollinate schema that has no Avro counterpart.
+fn unsupported(details: &Value) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("No Avro type can be derived for {}", details),
+    )
+}
+
+fn derive_schema(details: &Value, name: &str) -> io::Result<Value> {
+    if let Some(sequence) = details.get("x-sequence") {
+        return Ok(match sequence.get("format") {
+            Some(_) => json!("string"),
+            None => json!("long"),
+        });
+    }
+    if let Some(options) = details.get("oneOf") {
+        let options = options.as_array().ok_or_else(|| unsupported(details))?;
+        let branches = options
+            .iter()
+            .enumerate()
+            .map(|(i, x)| derive_schema(x, &format!("{}_{}", name, i)))
+            .collect::<io::Result<Vec<Value>>>()?;
+        return Ok(json!(branches));
+    }
+    Ok(
+        match details.get("type").ok_or_else(|| unsupported(details))? {
+            Value::Array(types) => {
+                let mut details = details.clone();
+                match types.iter().find(|x| x.as_str() != Some("null")) {
+                    Some(other) => details["type"] = other.clone(),
+                    None => return Ok(json!("null")),
+                }
+                json!(["null", derive_schema(&details, name)?])
+            }
+            x => {
+                match x.as_str() {
+                    Some("string") => json!("string"),
+                    Some("integer") => json!("long"),
+                    Some("number") => json!("double"),
+                    Some("boolean") => json!("boolean"),
+                    Some("null") => json!("null"),
+                    Some("array") => {
+                        let items = details.get("items").ok_or_else(|| unsupported(details))?;
+                        json!({"type": "array", "items": derive_schema(items, name)?})
+                    }
+                    Some("object") => match details.get("properties").and_then(|x| x.as_object()) {
+                        Some(properties) => {
+                            let mut fields = Vec::new();
+                            for (property, details) in properties {
+                                let name = format!("{}_{}", name, avro_name(property));
+                                fields.push(json!({"name": property, "type": derive_schema(details, &name)?}));
+                            }
+                            json!({"type": "record", "name": name, "fields": fields})
+                        }
+                        None => {
+                            let values = details
+                                .get("additionalProperties")
+                                .ok_or_else(|| unsupported(details))?;
+                            json!({"type": "map", "values": derive_schema(values, name)?})
+                        }
+                    },
+                    _ => return Err(unsupported(details)),
+                }
+            }
+        },
+    )
+}
+
+/// Derives an Avro schema from a pollinate schema.
+/// A schema converted from Avro gives back the original, otherwise the record is named after the `title`.
+/// Fails with an `InvalidInput` error for a property without a type to derive an Avro type from.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::avro_utils::avro_schema_from_schema;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "title": "Person",
+///     "type": "object",
+///     "properties": {
+///         "age": {"type": ["integer", "null"], "minimum": 0, "maximum": 99},
+///         "partner": {"type": "object", "properties": {"name": {"type": "string"}}}
+///     }
+/// });
+/// assert_eq!(
+///     avro_schema_from_schema(&schema).unwrap(),
+///     json!({
+///         "type": "record",
+///         "name": "Person",
+///         "fields": [
+///             {"name": "age", "type": ["null", "long"]},
+///             {"name": "partner", "type": {
+///                 "type": "record",
+///                 "name": "Person_partner",
+///                 "fields": [{"name": "name", "type": "string"}]
+///             }}
+///         ]
+///     })
+/// );
+/// ```
+pub fn avro_schema_from_schema(schema: &Value) -> io::Result<Value> {
+    if let Some(avro) = schema.get("x-avro") {
+        return Ok(avro.clone());
+    }
+    let name = avro_name(schema.get("title").and_then(|x| x.as_str()).unwrap_or(""));
+    derive_schema(schema, &name)
+}
+
+/// Checks whether a value can be written as a branch of a union.
+fn matches_schema(value: &Value, schema: &Value, names: &Names) -> bool {
+    match schema {
+        Value::String(name) => match name.as_str() {
+            "null" => value.is_null(),
+            "boolean" => value.is_boolean(),
+            "int" => value.as_i64().is_some_and(|x| i32::try_from(x).is_ok()),
+            "long" => value.is_i64(),
+            "float" | "double" => value.is_number(),
+            "string" | "bytes" => value.is_string(),
+            name => names
+                .get(name)
+                .is_some_and(|x| matches_schema(value, x, names)),
+        },
+        Value::Object(map) => {
+            if map.get("logicalType") == Some(&json!("decimal")) {
+                return value.is_number();
+            }
+            match map.get("type").unwrap().as_str() {
+                Some("record" | "error" | "map") => value.is_object(),
+                Some("enum") => map
+                    .get("symbols")
+                    .unwrap()
+                    .as_array()
+                    .unwrap()
+                    .contains(value),
+                Some("array") => value.is_array(),
+                Some("fixed") => value.is_string(),
+                _ => matches_schema(value, map.get("type").unwrap(), names),
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Encodes a number as the big-endian two's complement unscaled value of a decimal.
+fn decimal_bytes(value: f64, scale: i32) -> Vec<u8> {
+    let unscaled = (value * 10f64.powi(scale)).round() as i128;
+    let mut bytes = unscaled.to_be_bytes().to_vec();
+    while bytes.len() > 1
+        && ((bytes[0] == 0 && bytes[1] & 0x80 == 0) || (bytes[0] == 0xff && bytes[1] & 0x80 != 0))
+    {
+        bytes.remove(0);
+    }
+    bytes
+}
+
+fn convert_value(value: &Value, schema: &Value, names: &Names) -> io::Result<AvroValue> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} does not match the Avro schema {}", value, schema),
+        )
+    };
+    let as_i64 = || value.as_i64().ok_or_else(invalid);
+    let as_i32 = || i32::try_from(as_i64()?).map_err(|_| invalid());
+    let as_str = || value.as_str().ok_or_else(invalid);
+    Ok(match schema {
+        Value::String(name) => match name.as_str() {
+            "null" if value.is_null() => AvroValue::Null,
+            "boolean" => AvroValue::Boolean(value.as_bool().ok_or_else(invalid)?),
+            "int" => AvroValue::Int(as_i32()?),
+            "long" => AvroValue::Long(as_i64()?),
+            "float" => AvroValue::Float(value.as_f64().ok_or_else(invalid)? as f32),
+            "double" => AvroValue::Double(value.as_f64().ok_or_else(invalid)?),
+            "string" => AvroValue::String(as_str()?.to_owned()),
+            "bytes" => AvroValue::Bytes(as_str()?.as_bytes().to_vec()),
+            name => convert_value(value, names.get(name).ok_or_else(invalid)?, names)?,
+        },
+        Value::Array(branches) => {
+            let (index, branch) = branches
+                .iter()
+                .enumerate()
+                .find(|(_, branch)| matches_schema(value, branch, names))
+                .ok_or_else(invalid)?;
+            AvroValue::Union(index as u32, Box::new(convert_value(value, branch, names)?))
+        }
+        Value::Object(map) => {
+            let type_name = map.get("type").unwrap().as_str();
+            match (type_name, map.get("logicalType").and_then(|x| x.as_str())) {
+                (_, Some("decimal")) => {
+                    let scale = map.get("scale").map_or(0, |x| x.as_i64().unwrap()) as i32;
+                    let value = value.as_f64().ok_or_else(invalid)?;
+                    AvroValue::Decimal(Decimal::from(decimal_bytes(value, scale)))
+                }
+                (Some("string"), Some("uuid")) => {
+                    AvroValue::Uuid(Uuid::parse_str(as_str()?).map_err(|_| invalid())?)
+                }
+                (Some("int"), Some("date")) => AvroValue::Date(as_i32()?),
+                (Some("int"), Some("time-millis")) => AvroValue::TimeMillis(as_i32()?),
+                (Some("long"), Some("time-micros")) => AvroValue::TimeMicros(as_i64()?),
+                (Some("long"), Some("timestamp-millis")) => AvroValue::TimestampMillis(as_i64()?),
+                (Some("long"), Some("timestamp-micros")) => AvroValue::TimestampMicros(as_i64()?),
+                (Some("long"), Some("timestamp-nanos")) => AvroValue::TimestampNanos(as_i64()?),
+                (Some("long"), Some("local-timestamp-millis")) => {
+                    AvroValue::LocalTimestampMillis(as_i64()?)
+                }
+                (Some("long"), Some("local-timestamp-micros")) => {
+                    AvroValue::LocalTimestampMicros(as_i64()?)
+                }
+                (Some("long"), Some("local-timestamp-nanos")) => {
+                    AvroValue::LocalTimestampNanos(as_i64()?)
+                }
+                (Some("record" | "error"), _) => {
+                    let mut fields = Vec::new();
+                    for field in map.get("fields").unwrap().as_array().unwrap() {
+                        let name = field.get("name").unwrap().as_str().unwrap();
+                        let field_value = value.get(name).unwrap_or(&Value::Null);
+                        let field_schema = field.get("type").unwrap();
+                        fields.push((
+                            name.to_owned(),
+                            convert_value(field_value, field_schema, names)?,
+                        ));
+                    }
+                    AvroValue::Record(fields)
+                }
+                (Some("enum"), _) => {
+                    let symbols = map.get("symbols").unwrap().as_array().unwrap();
+                    let index = symbols
+                        .iter()
+                        .position(|x| x == value)
+                        .ok_or_else(invalid)?;
+                    AvroValue::Enum(index as u32, as_str()?.to_owned())
+                }
+                (Some("array"), _) => {
+                    let items = map.get("items").unwrap();
+                    let values = value.as_array().ok_or_else(invalid)?;
+                    AvroValue::Array(
+                        values
+                            .iter()
+                            .map(|x| convert_value(x, items, names))
+                            .collect::<io::Result<_>>()?,
+                    )
+                }
+                (Some("map"), _) => {
+                    let values = map.get("values").unwrap();
+                    let entries = value.as_object().ok_or_else(invalid)?;
+                    AvroValue::Map(
+                        entries
+                            .iter()
+                            .map(|(key, x)| Ok((key.clone(), convert_value(x, values, names)?)))
+                            .collect::<io::Result<_>>()?,
+                    )
+                }
+                (Some("fixed"), _) => {
+                    let size = map.get("size").unwrap().as_u64().unwrap() as usize;
+                    let bytes = as_str()?.as_bytes().to_vec();
+                    if bytes.len() != size {
+                        return Err(invalid());
+                    }
+                    AvroValue::Fixed(size, bytes)
+                }
+                _ => convert_value(value, map.get("type").unwrap(), names)?,
+            }
+        }
+        _ => return Err(invalid()),
+    })
+}
+
+/// Converts a generated record to a value of an Avro schema.
+///
+/// # Examples
+///
+/// ```
+/// use apache_avro::types::Value as AvroValue;
+/// use pollinate::avro_utils::avro_value;
+/// use serde_json::json;
+///
+/// let avro = json!({
+///     "type": "record",
+///     "name": "Price",
+///     "fields": [
+///         {"name": "amount", "type": {"type": "bytes", "logicalType": "decimal", "precision": 6, "scale": 2}},
+///         {"name": "note", "type": ["null", "string"]}
+///     ]
+/// });
+/// let value = avro_value(&json!({"amount": 12.5, "note": null}), &avro).unwrap();
+/// assert_eq!(
+///     value,
+///     AvroValue::Record(vec![
+///         ("amount".to_string(), AvroValue::Decimal(vec![0x04, 0xe2].into())),
+///         ("note".to_string(), AvroValue::Union(0, Box::new(AvroValue::Null))),
+///     ])
+/// );
+/// ```
+pub fn avro_value(value: &Value, avro: &Value) -> io::Result<AvroValue> {
+    let mut names = Names::new();
+    collect_names(avro, None, &mut names)?;
+    convert_value(value, avro, &names)
+}
+
+/// Writes records to an Avro object container file with the schema embedded in the header.
+/// Records are buffered and written in blocks of 1000.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::avro_utils::AvroWriter;
+/// use pollinate::json_utils::RecordWriter;
+/// use serde_json::json;
+///
+/// let avro = json!({"type": "record", "name": "Point", "fields": [{"name": "x", "type": "long"}]});
+/// let mut output = Vec::new();
+/// let mut writer = AvroWriter::new(&mut output, &avro).unwrap();
+/// writer.write_record(&json!({"x": 1})).unwrap();
+/// writer.finish().unwrap();
+///
+/// let reader = apache_avro::Reader::new(output.as_slice()).unwrap();
+/// let records: Vec<_> = reader.map(|x| x.unwrap()).collect();
+/// assert_eq!(records.len(), 1);
+/// ```
+pub struct AvroWriter<W: Write> {
+    writer: W,
+    schema: Schema,
+    avro: Value,
+    names: Names,
+    marker: [u8; 16],
+    header_written: bool,
+    pending: Vec<AvroValue>,
+}
+
+impl<W: Write> AvroWriter<W> {
+    pub fn new(writer: W, avro: &Value) -> io::Result<Self> {
+        let schema = Schema::parse(avro).map_err(io::Error::other)?;
+        let mut names = Names::new();
+        collect_names(avro, None, &mut names)?;
+        Ok(AvroWriter {
+            writer,
+            schema,
+            avro: avro.clone(),
+            names,
+            marker: thread_rng().gen(),
+            header_written: false,
+            pending: Vec::with_capacity(AVRO_BLOCK_SIZE),
+        })
+    }
+
+    /// Writes the header if needed and the pending records as one block.
+    fn write_block(&mut self) -> io::Result<()> {
+        let mut writer = Writer::builder()
+            .schema(&self.schema)
+            .writer(&mut self.writer)
+            .marker(self.marker)
+            .has_header(self.header_written)
+            .build()
+            .map_err(io::Error::other)?;
+        for value in self.pending.drain(..) {
+            writer.append_value(value).map_err(io::Error::other)?;
+        }
+        writer.flush().map_err(io::Error::other)?;
+        self.header_written = true;
+        Ok(())
+    }
+}
+
+impl<W: Write> RecordWriter for AvroWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        self.pending
+            .push(convert_value(record, &self.avro, &self.names)?);
+        if self.pending.len() >= AVRO_BLOCK_SIZE {
+            self.write_block()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.write_block()?;
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,257 @@
+//! # Pollinate Binary Module
+//!
+//! The `binary_utils` module writes generated records in the binary formats MessagePack, CBOR and BSON.
+//!
+//! ## Overview
+//!
+//! A single record is written as one plain document. Several records are written as a stream where each
+//! MessagePack or CBOR record is prefixed by its length as a 4 byte big-endian integer. BSON documents already
+//! start with their own length so BSON records are written back to back, the layout used by tools such as `mongodump`.
+//!
+//! ## Functions
+//!
+//! - `encode_binary`: Encodes a single value in one of the binary formats.
+//! - `encode_bson`: Encodes an object as a BSON document.
+//! - `read_length_delimited`: Splits a length-delimited stream back into its records.
+//! - `dump_binary`: Dumps a vector of JSON values to a file as a binary record stream.
+//!
+
+use serde_json::{Map, Value};
+use std::io;
+use std::io::Write;
+
+use crate::json_utils::{dump_records, RecordWriter};
+
+/// Binary formats records can be encoded in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+    MessagePack,
+    Cbor,
+    Bson,
+}
+
+/// Encodes a single value in one of the binary formats.
+/// BSON can only encode objects.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::binary_utils::*;
+/// use serde_json::json;
+///
+/// let bytes = encode_binary(&json!({"a": 1}), BinaryFormat::MessagePack).unwrap();
+/// assert_eq!(bytes, vec![0x81, 0xa1, b'a', 0x01]);
+/// ```
+pub fn encode_binary(value: &Value, format: BinaryFormat) -> io::Result<Vec<u8>> {
+    match format {
+        BinaryFormat::MessagePack => rmp_serde::to_vec(value).map_err(io::Error::other),
+        BinaryFormat::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(value, &mut bytes).map_err(io::Error::other)?;
+            Ok(bytes)
+        }
+        BinaryFormat::Bson => match value {
+            Value::Object(map) => encode_bson(map),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only objects can be encoded as BSON documents",
+            )),
+        },
+    }
+}
+
+/// Encodes an object as a BSON document.
+/// Integers use the smallest of int32 and int64 that fits, integers too large for either become doubles.
+/// Keys are written as C strings, so a key containing a NUL byte can't be encoded.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::binary_utils::encode_bson;
+/// use serde_json::json;
+///
+/// let document = json!({"a": 1});
+/// let bytes = encode_bson(document.as_object().unwrap()).unwrap();
+/// assert_eq!(bytes, vec![12, 0, 0, 0, 0x10, b'a', 0, 1, 0, 0, 0, 0]);
+///
+/// let document = json!({"a\0b": 1});
+/// assert!(encode_bson(document.as_object().unwrap()).is_err());
+/// ```
+pub fn encode_bson(map: &Map<String, Value>) -> io::Result<Vec<u8>> {
+    encode_document(
+        map.iter()
+            .map(|(key, value)| (key.clone(), value))
+            .collect(),
+    )
+}
+
+fn encode_document(elements: Vec<(String, &Value)>) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0; 4];
+    for (key, value) in elements {
+        if key.contains('\0') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("BSON keys can't contain a NUL byte, found {:?}", key),
+            ));
+        }
+        let type_position = bytes.len();
+        bytes.push(0);
+        bytes.extend(key.as_bytes());
+        bytes.push(0);
+        bytes[type_position] = match value {
+            Value::Null => 0x0a,
+            Value::Bool(x) => {
+                bytes.push(*x as u8);
+                0x08
+            }
+            Value::Number(x) => match x.as_i64() {
+                Some(x) if i32::try_from(x).is_ok() => {
+                    bytes.extend((x as i32).to_le_bytes());
+                    0x10
+                }
+                Some(x) => {
+                    bytes.extend(x.to_le_bytes());
+                    0x12
+                }
+                None => {
+                    bytes.extend(x.as_f64().unwrap().to_le_bytes());
+                    0x01
+                }
+            },
+            Value::String(x) => {
+                bytes.extend((x.len() as i32 + 1).to_le_bytes());
+                bytes.extend(x.as_bytes());
+                bytes.push(0);
+                0x02
+            }
+            Value::Array(values) => {
+                let elements = values.iter().enumerate();
+                bytes.extend(encode_document(
+                    elements.map(|(i, x)| (i.to_string(), x)).collect(),
+                )?);
+                0x04
+            }
+            Value::Object(map) => {
+                bytes.extend(encode_bson(map)?);
+                0x03
+            }
+        };
+    }
+    bytes.push(0);
+    let length = bytes.len() as i32;
+    bytes[..4].copy_from_slice(&length.to_le_bytes());
+    Ok(bytes)
+}
+
+/// Splits a stream of records each prefixed by a 4 byte big-endian length.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::binary_utils::read_length_delimited;
+///
+/// let stream = [0, 0, 0, 1, 7, 0, 0, 0, 2, 8, 9];
+/// assert_eq!(read_length_delimited(&stream).unwrap(), vec![vec![7], vec![8, 9]]);
+/// ```
+pub fn read_length_delimited(stream: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+    let mut records = Vec::new();
+    let mut rest = stream;
+    while !rest.is_empty() {
+        let truncated = || io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated record");
+        let (length, body) = rest.split_first_chunk::<4>().ok_or_else(truncated)?;
+        let length = u32::from_be_bytes(*length) as usize;
+        if body.len() < length {
+            return Err(truncated());
+        }
+        records.push(body[..length].to_vec());
+        rest = &body[length..];
+    }
+    Ok(records)
+}
+
+/// Writes records in a binary format, either as one plain document or as a record stream.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::binary_utils::*;
+/// use pollinate::json_utils::RecordWriter;
+/// use serde_json::json;
+///
+/// let mut output = Vec::new();
+/// let mut writer = BinaryWriter::new(&mut output, BinaryFormat::Cbor);
+/// writer.write_record(&json!({"a": 1})).unwrap();
+/// writer.write_record(&json!({"a": 2})).unwrap();
+/// writer.finish().unwrap();
+/// let records = read_length_delimited(&output).unwrap();
+/// let first: serde_json::Value = ciborium::from_reader(records[0].as_slice()).unwrap();
+/// assert_eq!(first, json!({"a": 1}));
+/// ```
+pub struct BinaryWriter<W: Write> {
+    writer: W,
+    format: BinaryFormat,
+    single: bool,
+    written: u64,
+}
+
+impl<W: Write> BinaryWriter<W> {
+    pub fn new(writer: W, format: BinaryFormat) -> Self {
+        BinaryWriter {
+            writer,
+            format,
+            single: false,
+            written: 0,
+        }
+    }
+
+    pub fn single(writer: W, format: BinaryFormat) -> Self {
+        BinaryWriter {
+            writer,
+            format,
+            single: true,
+            written: 0,
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for BinaryWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        if self.single && self.written > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only one record can be written as a single document",
+            ));
+        }
+        let bytes = encode_binary(record, self.format)?;
+        if !self.single && self.format != BinaryFormat::Bson {
+            let length = u32::try_from(bytes.len()).map_err(io::Error::other)?;
+            self.writer.write_all(&length.to_be_bytes())?;
+        }
+        self.writer.write_all(&bytes)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Dumps a vector of JSON values to a file as a binary record stream.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::binary_utils::*;
+///
+/// let json_objects = vec![serde_json::json!({"key1": "value1"})];
+///
+/// let result = dump_binary(&json_objects, BinaryFormat::Bson, "output.bson");
+/// assert!(result.is_ok());
+/// # std::fs::remove_file("output.bson").unwrap();
+/// ```
+pub fn dump_binary(objects: &[Value], format: BinaryFormat, file: &str) -> io::Result<()> {
+    dump_records(objects, file, None, |writer| {
+        Ok(BinaryWriter::new(writer, format))
+    })
+}
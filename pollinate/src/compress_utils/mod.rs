@@ -0,0 +1,214 @@
+//! # Pollinate Compression Module
+//!
+//! The `compress_utils` module compresses output with gzip, zstd or bzip2 while records are written.
+//!
+//! ## Overview
+//!
+//! Records pass through the compressor as they stream out, so uncompressed output never exists on disk.
+//! The compressor is shared between the record writer and a `CompressedWriter` wrapping it: once the record
+//! writer has finished, the compressed stream is finished as well, which writes the format's trailer and
+//! reports any error doing so instead of losing it when the file is dropped.
+//!
+//! ## Functions
+//!
+//! - `Compression::from_path`: Infers the compression from an extension such as `.gz`, `.zst` or `.bz2`.
+//! - `strip_extension`: Removes a compression extension from a path.
+//!
+
+use flate2::write::GzEncoder;
+use serde_json::Value;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use crate::json_utils::RecordWriter;
+
+/// Compression applied to output files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// gzip, the extension is .gz
+    Gzip,
+    /// Zstandard, the extension is .zst
+    Zstd,
+    /// bzip2, the extension is .bz2
+    Bzip2,
+}
+
+impl Compression {
+    /// Every compression, in the order they are listed in help.
+    pub const ALL: &'static [Compression] =
+        &[Compression::Gzip, Compression::Zstd, Compression::Bzip2];
+
+    /// Returns the name the compression is parsed from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pollinate::compress_utils::Compression;
+    ///
+    /// assert_eq!("zstd".parse::<Compression>(), Ok(Compression::Zstd));
+    /// assert_eq!(Compression::Zstd.name(), "zstd");
+    /// assert!("zip".parse::<Compression>().is_err());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+        }
+    }
+
+    /// Infers the compression from the extension of a path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pollinate::compress_utils::Compression;
+    ///
+    /// assert_eq!(Compression::from_path("users.ndjson.gz"), Some(Compression::Gzip));
+    /// assert_eq!(Compression::from_path("users.ndjson"), None);
+    /// ```
+    pub fn from_path(file: &str) -> Option<Self> {
+        let extension = Path::new(file).extension()?.to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            "bz2" => Some(Compression::Bzip2),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Compression::ALL
+            .iter()
+            .find(|x| x.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Compression::ALL.iter().map(|x| x.name()).collect();
+                format!(
+                    "Unknown compression {}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Removes a compression extension from a path, leaving the extension of the format underneath.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::compress_utils::strip_extension;
+///
+/// assert_eq!(strip_extension("out/users.csv.zst"), "out/users.csv");
+/// assert_eq!(strip_extension("out/users.csv"), "out/users.csv");
+/// ```
+pub fn strip_extension(file: &str) -> &str {
+    match Compression::from_path(file) {
+        Some(_) => file.rsplit_once('.').unwrap().0,
+        None => file,
+    }
+}
+
+enum Stream {
+    Gzip(GzEncoder<Box<dyn Write + Send>>),
+    Zstd(zstd::Encoder<'static, Box<dyn Write + Send>>),
+    Bzip2(bzip2::write::BzEncoder<Box<dyn Write + Send>>),
+}
+
+/// A compressing stream that can be cloned, so it can be finished after the writer using it is done.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::compress_utils::{Compression, Encoder};
+/// use std::io::Write;
+///
+/// let mut encoder = Encoder::new(Box::new(std::io::sink()), Compression::Zstd).unwrap();
+/// encoder.write_all(b"{}").unwrap();
+/// encoder.finish().unwrap();
+/// assert!(encoder.write_all(b"{}").is_err());
+/// ```
+#[derive(Clone)]
+pub struct Encoder {
+    stream: Arc<Mutex<Option<Stream>>>,
+}
+
+impl Encoder {
+    pub fn new(writer: Box<dyn Write + Send>, compression: Compression) -> io::Result<Self> {
+        let stream = match compression {
+            Compression::Gzip => {
+                Stream::Gzip(GzEncoder::new(writer, flate2::Compression::default()))
+            }
+            Compression::Zstd => Stream::Zstd(zstd::Encoder::new(writer, 0)?),
+            Compression::Bzip2 => Stream::Bzip2(bzip2::write::BzEncoder::new(
+                writer,
+                bzip2::Compression::default(),
+            )),
+        };
+        Ok(Encoder {
+            stream: Arc::new(Mutex::new(Some(stream))),
+        })
+    }
+
+    /// Writes the end of the compressed stream and flushes it.
+    /// Anything written afterwards is an error.
+    pub fn finish(&self) -> io::Result<()> {
+        let mut writer = match self.stream.lock().unwrap().take() {
+            Some(Stream::Gzip(x)) => x.finish()?,
+            Some(Stream::Zstd(x)) => x.finish()?,
+            Some(Stream::Bzip2(x)) => x.finish()?,
+            None => return Ok(()),
+        };
+        writer.flush()
+    }
+
+    fn with_stream<T>(&self, f: impl FnOnce(&mut dyn Write) -> io::Result<T>) -> io::Result<T> {
+        match self.stream.lock().unwrap().as_mut() {
+            Some(Stream::Gzip(x)) => f(x),
+            Some(Stream::Zstd(x)) => f(x),
+            Some(Stream::Bzip2(x)) => f(x),
+            None => Err(io::Error::other("Compressed stream is already finished")),
+        }
+    }
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with_stream(|x| x.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.with_stream(|x| x.flush())
+    }
+}
+
+/// Wraps a record writer whose output is compressed, finishing the compressed stream after it.
+pub struct CompressedWriter {
+    writer: Box<dyn RecordWriter>,
+    encoder: Encoder,
+}
+
+impl CompressedWriter {
+    pub fn new(writer: Box<dyn RecordWriter>, encoder: Encoder) -> Self {
+        CompressedWriter { writer, encoder }
+    }
+}
+
+impl RecordWriter for CompressedWriter {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        self.writer.write_record(record)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.finish()?;
+        self.encoder.finish()
+    }
+}
@@ -0,0 +1,389 @@
+//! # Pollinate Coverage Module
+//!
+//! The `coverage` module plans records that cover the combinations of a schema's values instead of sampling
+//! them at random.
+//!
+//! ## Overview
+//!
+//! Every field with a small set of values is a factor: an `enum`, a boolean, or a number with a `minimum` and
+//! `maximum`, which contributes both bounds. Fields of nested objects are factors too, while arrays, `oneOf`,
+//! free strings and `x-unique` fields keep their random values. A nullable factor can also be null.
+//!
+//! - `exhaustive`: One record for every combination of the factors' values.
+//! - `pairwise`: Every pair of values of any two factors appears in at least one record, which needs far fewer
+//!   records. The records are built greedily one at a time, as AETG does.
+//!
+//! ## Functions
+//!
+//! - `factors`: Finds the factors of a schema.
+//!
+
+use rand::seq::SliceRandom;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io;
+use std::str::FromStr;
+
+use crate::default_values::generator_rng;
+use crate::json_utils::RecordWriter;
+
+/// Combinations of values covered by the planned records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coverage {
+    /// Every combination of values
+    Exhaustive,
+    /// Every pair of values of any two fields
+    Pairwise,
+}
+
+impl Coverage {
+    /// Every coverage, in the order they are listed in help.
+    pub const ALL: &'static [Coverage] = &[Coverage::Exhaustive, Coverage::Pairwise];
+
+    /// Returns the name the coverage is parsed from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pollinate::coverage::Coverage;
+    ///
+    /// assert_eq!("pairwise".parse::<Coverage>(), Ok(Coverage::Pairwise));
+    /// assert_eq!(Coverage::Pairwise.name(), "pairwise");
+    /// assert!("triple".parse::<Coverage>().is_err());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Coverage::Exhaustive => "exhaustive",
+            Coverage::Pairwise => "pairwise",
+        }
+    }
+}
+
+impl FromStr for Coverage {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Coverage::ALL
+            .iter()
+            .find(|x| x.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Coverage::ALL.iter().map(|x| x.name()).collect();
+                format!(
+                    "Unknown coverage {}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// A field and the values that should be covered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Factor {
+    /// JSON Pointer to the field in a record
+    pub pointer: String,
+    pub levels: Vec<Value>,
+}
+
+/// Values of a field that should be covered, or none when it is left random.
+fn levels(details: &Value) -> Vec<Value> {
+    if details.get("x-unique") == Some(&Value::Bool(true)) || details.get("x-sequence").is_some() {
+        return Vec::new();
+    }
+    let types: Vec<&str> = match details.get("type") {
+        Some(Value::Array(types)) => types.iter().filter_map(|x| x.as_str()).collect(),
+        Some(Value::String(x)) => vec![x.as_str()],
+        _ => Vec::new(),
+    };
+    let mut levels = match (details.get("enum"), types.iter().find(|x| **x != "null")) {
+        (Some(Value::Array(options)), _) => options.clone(),
+        (_, Some(&"boolean")) => vec![json!(true), json!(false)],
+        (_, Some(&"integer" | &"number")) => match (details.get("minimum"), details.get("maximum"))
+        {
+            (Some(min), Some(max)) => vec![min.clone(), max.clone()],
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    if !levels.is_empty() && types.contains(&"null") {
+        levels.push(Value::Null);
+    }
+    // repeated values would only add records covering nothing new
+    let mut seen = HashSet::new();
+    levels.retain(|x| seen.insert(x.to_string()));
+    levels
+}
+
+fn collect(properties: &Value, pointer: &str, factors: &mut Vec<Factor>) {
+    let Some(properties) = properties.as_object() else {
+        return;
+    };
+    for (key, details) in properties.iter() {
+        let pointer = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+        if details.get("type") == Some(&json!("object")) && details.get("properties").is_some() {
+            collect(&details["properties"], &pointer, factors);
+            continue;
+        }
+        let levels = levels(details);
+        if levels.len() > 1 {
+            factors.push(Factor { pointer, levels });
+        }
+    }
+}
+
+/// Finds the fields of a schema with values to cover, including fields of nested objects.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::coverage::factors;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {
+///     "plan": {"type": "string", "enum": ["free", "pro"]},
+///     "name": {"type": "string"},
+///     "owner": {"type": "object", "properties": {"age": {"type": ["integer", "null"], "minimum": 18, "maximum": 99}}}
+/// }});
+/// let factors = factors(&schema);
+/// assert_eq!(factors.len(), 2);
+/// assert_eq!(factors[0].pointer, "/owner/age");
+/// assert_eq!(factors[0].levels, vec![json!(18), json!(99), json!(null)]);
+/// ```
+pub fn factors(schema: &Value) -> Vec<Factor> {
+    let mut factors = Vec::new();
+    collect(&schema["properties"], "", &mut factors);
+    factors
+}
+
+/// The field a JSON Pointer refers to, adding it and any object on the way that is missing.
+fn field_mut<'a>(record: &'a mut Value, pointer: &str) -> &'a mut Value {
+    let mut value = record;
+    for token in pointer.split('/').skip(1) {
+        let key = token.replace("~1", "/").replace("~0", "~");
+        if !value.is_object() {
+            *value = json!({});
+        }
+        value = value
+            .as_object_mut()
+            .unwrap()
+            .entry(key)
+            .or_insert(Value::Null);
+    }
+    value
+}
+
+/// The nth combination of levels, counting with the last factor changing fastest.
+fn combination(sizes: &[usize], mut n: usize) -> Vec<usize> {
+    let mut row = vec![0; sizes.len()];
+    for (level, size) in row.iter_mut().zip(sizes).rev() {
+        *level = n % size;
+        n /= size;
+    }
+    row
+}
+
+/// Number of candidate rows built for every row kept by `pairwise`.
+const PAIRWISE_CANDIDATES: usize = 20;
+
+/// Rows covering every pair of levels of any two factors, built one row at a time.
+/// Each candidate row starts from a random pair not covered yet and adds the other factors in random order, each
+/// with the level covering the most new pairs. The candidate covering the most new pairs is kept.
+/// Needs at least two factors to have any pairs.
+fn pairwise(sizes: &[usize]) -> Vec<Vec<usize>> {
+    let mut rng = generator_rng();
+    let mut uncovered: HashSet<(usize, usize, usize, usize)> = HashSet::new();
+    for i in 0..sizes.len() {
+        for j in i + 1..sizes.len() {
+            for a in 0..sizes[i] {
+                uncovered.extend((0..sizes[j]).map(|b| (i, a, j, b)));
+            }
+        }
+    }
+    let covers = |row: &[Option<usize>], uncovered: &HashSet<_>, k: usize, level: usize| {
+        (0..sizes.len())
+            .filter(|i| *i != k)
+            .filter_map(|i| Some((i, row[i]?)))
+            .filter(|(i, a)| match *i < k {
+                true => uncovered.contains(&(*i, *a, k, level)),
+                false => uncovered.contains(&(k, level, *i, *a)),
+            })
+            .count()
+    };
+    let mut rows = Vec::new();
+    while !uncovered.is_empty() {
+        let mut best: Option<(usize, Vec<usize>)> = None;
+        let mut left: Vec<_> = uncovered.iter().copied().collect();
+        // sorted so that a seeded generator plans the same rows every run
+        left.sort_unstable();
+        for _ in 0..PAIRWISE_CANDIDATES {
+            let &(i, a, j, b) = left.choose(&mut rng).unwrap();
+            let mut row = vec![None; sizes.len()];
+            row[i] = Some(a);
+            row[j] = Some(b);
+            let mut order: Vec<usize> = (0..sizes.len()).filter(|x| *x != i && *x != j).collect();
+            order.shuffle(&mut rng);
+            for k in order {
+                let mut levels: Vec<usize> = (0..sizes[k]).collect();
+                levels.shuffle(&mut rng);
+                let level = levels
+                    .into_iter()
+                    .max_by_key(|level| covers(&row, &uncovered, k, *level))
+                    .unwrap();
+                row[k] = Some(level);
+            }
+            let row: Vec<usize> = row.into_iter().map(|x| x.unwrap()).collect();
+            let covered = (0..sizes.len())
+                .flat_map(|i| (i + 1..sizes.len()).map(move |j| (i, j)))
+                .filter(|(i, j)| uncovered.contains(&(*i, row[*i], *j, row[*j])))
+                .count();
+            if best.as_ref().is_none_or(|x| covered > x.0) {
+                best = Some((covered, row));
+            }
+        }
+        let (_, row) = best.unwrap();
+        for i in 0..sizes.len() {
+            for j in i + 1..sizes.len() {
+                uncovered.remove(&(i, row[i], j, row[j]));
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+/// The records needed to cover a schema, each given as the level of every factor.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::coverage::{Coverage, CoveragePlan};
+/// use serde_json::json;
+/// use std::collections::HashSet;
+///
+/// let schema = json!({"type": "object", "properties": {
+///     "os": {"type": "string", "enum": ["linux", "mac", "windows"]},
+///     "browser": {"type": "string", "enum": ["firefox", "chrome", "safari"]},
+///     "mobile": {"type": "boolean"},
+///     "dark": {"type": "boolean"}
+/// }});
+/// assert_eq!(CoveragePlan::new(&schema, Coverage::Exhaustive).unwrap().len(), 36);
+///
+/// let plan = CoveragePlan::new(&schema, Coverage::Exhaustive).unwrap();
+/// let mut record = json!({"os": "linux", "browser": "chrome", "mobile": true, "dark": true});
+/// plan.apply(35, &mut record);
+/// assert_eq!(record, json!({"os": "windows", "browser": "safari", "mobile": false, "dark": false}));
+///
+/// let plan = CoveragePlan::new(&schema, Coverage::Pairwise).unwrap();
+/// let mut pairs = HashSet::new();
+/// for n in 0..plan.len() {
+///     plan.apply(n, &mut record);
+///     pairs.insert((record["os"].clone(), record["browser"].clone()));
+/// }
+/// assert_eq!(pairs.len(), 9);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CoveragePlan {
+    factors: Vec<Factor>,
+    sizes: Vec<usize>,
+    /// Rows picked for pairwise coverage, exhaustive coverage works out every row from its index instead
+    rows: Option<Vec<Vec<usize>>>,
+}
+
+impl CoveragePlan {
+    /// Plans the records, failing when exhaustive coverage needs more records than can be generated.
+    /// Exhaustive records aren't stored, each is worked out from its index when applied.
+    pub fn new(schema: &Value, coverage: Coverage) -> io::Result<Self> {
+        let factors = factors(schema);
+        let sizes: Vec<usize> = factors.iter().map(|x| x.levels.len()).collect();
+        let rows = match coverage {
+            Coverage::Exhaustive => {
+                let needed = sizes.iter().try_fold(1u32, |x, y| x.checked_mul(*y as u32));
+                if needed.is_none() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Exhaustive coverage of {} fields needs more than {} records, try pairwise",
+                            factors.len(),
+                            u32::MAX
+                        ),
+                    ));
+                }
+                None
+            }
+            // with fewer than two factors every combination is needed anyway
+            Coverage::Pairwise if sizes.len() < 2 => None,
+            Coverage::Pairwise => Some(pairwise(&sizes)),
+        };
+        Ok(CoveragePlan {
+            factors,
+            sizes,
+            rows,
+        })
+    }
+
+    /// Number of records planned.
+    pub fn len(&self) -> usize {
+        match &self.rows {
+            Some(rows) => rows.len(),
+            None => self.sizes.iter().product(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn factors(&self) -> &[Factor] {
+        &self.factors
+    }
+
+    /// Sets the fields of a record to the levels planned for the nth record.
+    /// Fields the record lacks are added, along with the objects holding them, so no planned value is lost.
+    pub fn apply(&self, n: usize, record: &mut Value) {
+        let row = match &self.rows {
+            Some(rows) => rows[n].clone(),
+            None => combination(&self.sizes, n),
+        };
+        for (factor, level) in self.factors.iter().zip(&row) {
+            *field_mut(record, &factor.pointer) = factor.levels[*level].clone();
+        }
+    }
+}
+
+/// Sets the fields of each record to the next planned combination before passing it on to another writer.
+/// Fields that aren't factors keep their random values.
+pub struct CoverageWriter<'a> {
+    writer: Box<dyn RecordWriter + 'a>,
+    plan: CoveragePlan,
+    written: usize,
+}
+
+impl<'a> CoverageWriter<'a> {
+    pub fn new(writer: Box<dyn RecordWriter + 'a>, plan: CoveragePlan) -> Self {
+        CoverageWriter {
+            writer,
+            plan,
+            written: 0,
+        }
+    }
+}
+
+impl RecordWriter for CoverageWriter<'_> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        if self.written >= self.plan.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Only {} records were planned", self.plan.len()),
+            ));
+        }
+        let mut record = record.clone();
+        self.plan.apply(self.written, &mut record);
+        self.written += 1;
+        self.writer.write_record(&record)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.finish()
+    }
+}
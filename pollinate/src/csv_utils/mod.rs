@@ -0,0 +1,271 @@
+//! # Pollinate CSV Module
+//!
+//! The `csv_utils` module writes generated records as CSV or TSV.
+//!
+//! ## Overview
+//!
+//! Nested objects are flattened into dotted column names such as `partner.firstName`. Arrays are written
+//! as JSON, or joined with a delimiter when one is given. The header is derived from the schema so every file
+//! made from the same schema has the same columns in the same order. Fields are quoted as described in RFC 4180.
+//!
+//! ## Functions
+//!
+//! - `columns_from_schema`: Lists the flattened column names of a schema.
+//! - `column_paths_from_schema`: Lists the path of properties leading to each column of a schema.
+//! - `flatten_record`: Renders a record as one field per column.
+//! - `dump_csv`: Dumps a vector of JSON values to a CSV or TSV file.
+//!
+
+use serde_json::Value;
+use std::io;
+use std::io::Write;
+
+use crate::json_utils::{dump_records, RecordWriter};
+
+/// Lists the column names of a schema, with nested object properties joined by dots.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::csv_utils::columns_from_schema;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": {
+///         "firstName": {"type": "string", "enum": ["Adam"]},
+///         "partner": {"type": "object", "properties": {"firstName": {"type": "string", "enum": ["John"]}}}
+///     }
+/// });
+/// assert_eq!(columns_from_schema(&schema), vec!["firstName", "partner.firstName"]);
+/// ```
+pub fn columns_from_schema(schema: &Value) -> Vec<String> {
+    column_paths_from_schema(schema)
+        .iter()
+        .map(|path| path.join("."))
+        .collect()
+}
+
+/// Lists the properties leading to each column of a schema, in the order of `columns_from_schema`.
+/// Unlike the column names these keep properties whose names contain a dot apart from nested ones.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::csv_utils::column_paths_from_schema;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": {
+///         "v1.2": {"type": "string", "enum": ["Adam"]},
+///         "partner": {"type": "object", "properties": {"firstName": {"type": "string", "enum": ["John"]}}}
+///     }
+/// });
+/// assert_eq!(column_paths_from_schema(&schema), vec![vec!["partner", "firstName"], vec!["v1.2"]]);
+/// ```
+pub fn column_paths_from_schema(schema: &Value) -> Vec<Vec<String>> {
+    let mut paths = Vec::<Vec<String>>::new();
+    collect_columns(schema, &[], &mut paths);
+    paths
+}
+
+fn collect_columns(details: &Value, prefix: &[String], paths: &mut Vec<Vec<String>>) {
+    let properties = details.get("properties").and_then(|x| x.as_object());
+    for (property, details) in properties.into_iter().flatten() {
+        let mut path = prefix.to_vec();
+        path.push(property.to_owned());
+        let is_object = details.get("type").and_then(|x| x.as_str()) == Some("object")
+            && details.get("enum").is_none()
+            && details.get("properties").is_some();
+        if is_object {
+            collect_columns(details, &path, paths);
+        } else {
+            paths.push(path);
+        }
+    }
+}
+
+/// Renders a single value as a field, leaving out quoting.
+fn render_field(value: &Value, array_delimiter: Option<&str>) -> String {
+    match (value, array_delimiter) {
+        (Value::Null, _) => String::new(),
+        (Value::String(x), _) => x.clone(),
+        (Value::Array(values), Some(delimiter)) => values
+            .iter()
+            .map(|x| render_field(x, None))
+            .collect::<Vec<String>>()
+            .join(delimiter),
+        (value, _) => value.to_string(),
+    }
+}
+
+/// Renders a record as one field per column, with dots in the column names separating nested fields.
+/// Missing values and nulls are empty, arrays are JSON unless a delimiter is given.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::csv_utils::flatten_record;
+/// use serde_json::json;
+///
+/// let record = json!({"name": "Adam", "partner": {"age": 30}, "skills": ["Java", "Sales"]});
+/// let columns = vec!["name".to_string(), "partner.age".to_string(), "skills".to_string()];
+/// assert_eq!(flatten_record(&record, &columns, None), vec!["Adam", "30", r#"["Java","Sales"]"#]);
+/// assert_eq!(flatten_record(&record, &columns, Some("|")), vec!["Adam", "30", "Java|Sales"]);
+/// ```
+pub fn flatten_record(
+    record: &Value,
+    columns: &[String],
+    array_delimiter: Option<&str>,
+) -> Vec<String> {
+    render_fields(record, &split_columns(columns), array_delimiter)
+}
+
+/// Splits column names into the fields they refer to.
+fn split_columns(columns: &[String]) -> Vec<Vec<String>> {
+    columns
+        .iter()
+        .map(|column| column.split('.').map(String::from).collect())
+        .collect()
+}
+
+/// Renders the value at each path of a record as a field.
+fn render_fields(
+    record: &Value,
+    paths: &[Vec<String>],
+    array_delimiter: Option<&str>,
+) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| {
+            let value = path
+                .iter()
+                .try_fold(record, |value, key| value.get(key))
+                .unwrap_or(&Value::Null);
+            render_field(value, array_delimiter)
+        })
+        .collect()
+}
+
+/// Quotes a field if it contains the delimiter, a quote or a line break.
+fn quote_field(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Writes records as delimiter separated rows beneath a header.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::csv_utils::CsvWriter;
+/// use pollinate::json_utils::RecordWriter;
+/// use serde_json::json;
+///
+/// let mut output = Vec::new();
+/// let columns = vec!["name".to_string(), "bio".to_string()];
+/// let mut writer = CsvWriter::new(&mut output, columns, ',', None);
+/// writer.write_record(&json!({"name": "Adam", "bio": "Said \"hi\", left"})).unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "name,bio\r\nAdam,\"Said \"\"hi\"\", left\"\r\n");
+/// ```
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    columns: Vec<String>,
+    paths: Vec<Vec<String>>,
+    delimiter: char,
+    array_delimiter: Option<String>,
+    header_written: bool,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Creates a writer for the given columns, with dots in their names separating nested fields.
+    pub fn new(
+        writer: W,
+        columns: Vec<String>,
+        delimiter: char,
+        array_delimiter: Option<String>,
+    ) -> Self {
+        CsvWriter {
+            writer,
+            paths: split_columns(&columns),
+            columns,
+            delimiter,
+            array_delimiter,
+            header_written: false,
+        }
+    }
+
+    /// Creates a writer for the columns of a schema, including properties whose names contain a dot.
+    pub fn from_schema(
+        writer: W,
+        schema: &Value,
+        delimiter: char,
+        array_delimiter: Option<String>,
+    ) -> Self {
+        let paths = column_paths_from_schema(schema);
+        CsvWriter {
+            writer,
+            columns: paths.iter().map(|path| path.join(".")).collect(),
+            paths,
+            delimiter,
+            array_delimiter,
+            header_written: false,
+        }
+    }
+
+    fn write_row(&mut self, fields: &[String]) -> io::Result<()> {
+        let row: Vec<String> = fields
+            .iter()
+            .map(|x| quote_field(x, self.delimiter))
+            .collect();
+        write!(self.writer, "{}\r\n", row.join(&self.delimiter.to_string()))
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if !self.header_written {
+            self.header_written = true;
+            let columns = self.columns.clone();
+            self.write_row(&columns)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> RecordWriter for CsvWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        self.write_header()?;
+        let fields = render_fields(record, &self.paths, self.array_delimiter.as_deref());
+        self.write_row(&fields)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.write_header()?;
+        self.writer.flush()
+    }
+}
+
+/// Dumps a vector of JSON values to a CSV or TSV file with a header derived from the schema.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::csv_utils::*;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {"key": {"type": "string", "enum": ["value"]}}});
+/// let json_objects = vec![json!({"key": "value"})];
+///
+/// let result = dump_csv(&json_objects, &schema, ',', "output.csv");
+/// assert!(result.is_ok());
+/// # std::fs::remove_file("output.csv").unwrap();
+/// ```
+pub fn dump_csv(objects: &[Value], schema: &Value, delimiter: char, file: &str) -> io::Result<()> {
+    dump_records(objects, file, None, |writer| {
+        Ok(CsvWriter::from_schema(writer, schema, delimiter, None))
+    })
+}
@@ -0,0 +1,366 @@
+//! # Pollinate Dataset Module
+//!
+//! The `dataset` module generates several related tables from a single manifest file.
+//!
+//! ## Overview
+//!
+//! A manifest lists a number of schemas along with how many records to create for each and where to write them.
+//! Schemas can reference columns of other tables with the `x-foreign-key` keyword, for example `"customers.id"`.
+//! Tables are generated in dependency order so that every reference is sampled from records that already exist.
+//! Instead of a fixed `count` a table can be generated `per` record of a parent table, e.g. 1 to 5 orders per customer.
+//!
+//! ```json
+//! {
+//!   "tables": [
+//!     { "name": "customers", "schema": "customers.json", "output": "out/customers.json", "count": 10 },
+//!     {
+//!       "name": "orders", "schema": "orders.json", "output": "out/orders.json",
+//!       "per": { "table": "customers", "minimum": 1, "maximum": 5 }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! ## Functions
+//!
+//! - `read_manifest`: Reads a manifest file, resolving schema and output paths relative to it.
+//! - `dependency_order`: Sorts the tables of a manifest so parents come before the tables referencing them.
+//! - `generate_dataset`: Generates the records for every table in a manifest.
+//! - `dump_dataset`: Writes each generated table to its output path.
+//!
+
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+
+use crate::compress_utils::Compression;
+use crate::default_values::generator_rng;
+use crate::json_utils::{create_json_vec_from_schema, dump_json_array_compressed, JsonStyle};
+use crate::schema::{generate_template_from_value, read_schema};
+
+/// A set of tables to be generated together.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    pub tables: Vec<Table>,
+}
+
+/// A single schema within a manifest.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Table {
+    /// Name other tables use to reference this one in `x-foreign-key`
+    pub name: String,
+    /// Path to the table's schema
+    pub schema: String,
+    /// Path the generated records are written to
+    pub output: String,
+    /// Fixed number of records to create
+    pub count: Option<u32>,
+    /// Number of records to create for each record of a parent table
+    pub per: Option<Cardinality>,
+}
+
+/// Creates between `minimum` and `maximum` records, inclusive, for every record of `table`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Cardinality {
+    pub table: String,
+    #[serde(default)]
+    pub minimum: u32,
+    pub maximum: u32,
+}
+
+/// Reads a manifest from a file.
+/// Relative schema and output paths are resolved against the manifest's directory.
+/// Fails when two tables share a name or a table creates more than `maximum` records per parent as its `minimum`.
+pub fn read_manifest(manifest_path: &str) -> io::Result<Manifest> {
+    let manifest_string = std::fs::read_to_string(manifest_path)?;
+    let mut manifest: Manifest = serde_json::from_str(&manifest_string)?;
+    let base = Path::new(manifest_path).parent().unwrap_or(Path::new(""));
+    let mut names = HashSet::new();
+    for table in manifest.tables.iter_mut() {
+        if !names.insert(table.name.clone()) {
+            return Err(invalid_manifest(format!(
+                "Table {} is listed more than once",
+                table.name
+            )));
+        }
+        if let Some(per) = table.per.as_ref().filter(|x| x.minimum > x.maximum) {
+            return Err(invalid_manifest(format!(
+                "Table {} has a per minimum of {} above its maximum of {}",
+                table.name, per.minimum, per.maximum
+            )));
+        }
+        table.schema = base.join(&table.schema).to_string_lossy().into_owned();
+        table.output = base.join(&table.output).to_string_lossy().into_owned();
+    }
+    Ok(manifest)
+}
+
+/// Error for a manifest, or a schema it lists, that tables can't be generated from.
+fn invalid_manifest(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Collects every table referenced by `x-foreign-key` within a schema.
+fn referenced_tables(schema: &Value, tables: &mut HashSet<String>) -> io::Result<()> {
+    match schema {
+        Value::Object(map) => {
+            if let Some(reference) = map.get("x-foreign-key") {
+                let (table, _) = split_reference(reference)?;
+                tables.insert(table.to_owned());
+            }
+            for value in map.values() {
+                referenced_tables(value, tables)?;
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                referenced_tables(value, tables)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Splits a `"table.column"` reference into its table and column.
+fn split_reference(reference: &Value) -> io::Result<(&str, &str)> {
+    reference
+        .as_str()
+        .and_then(|x| x.split_once('.'))
+        .ok_or_else(|| {
+            invalid_manifest(format!(
+                "Foreign key {} must be of the form table.column",
+                reference
+            ))
+        })
+}
+
+/// Returns the value at a dotted path such as `address.zip` within a record.
+fn get_column<'a>(record: &'a Value, column: &str) -> &'a Value {
+    column
+        .split('.')
+        .fold(record, |value, key| value.get(key).unwrap_or(&Value::Null))
+}
+
+/// Sorts the tables of a manifest so every table comes after the tables it references.
+/// Fails with an `InvalidInput` error if a table references an unknown table or the references form a cycle.
+pub fn dependency_order(manifest: &Manifest) -> io::Result<Vec<Table>> {
+    let mut dependencies = HashMap::<String, HashSet<String>>::new();
+    for table in manifest.tables.iter() {
+        let mut parents = HashSet::new();
+        referenced_tables(&read_schema(&table.schema)?, &mut parents)?;
+        if let Some(per) = &table.per {
+            parents.insert(per.table.clone());
+        }
+        if let Some(parent) = parents
+            .iter()
+            .find(|parent| !manifest.tables.iter().any(|x| &&x.name == parent))
+        {
+            return Err(invalid_manifest(format!(
+                "Table {} references unknown table {}",
+                table.name, parent
+            )));
+        }
+        dependencies.insert(table.name.clone(), parents);
+    }
+
+    let mut ordered = Vec::<Table>::with_capacity(manifest.tables.len());
+    let mut done = HashSet::<String>::new();
+    while ordered.len() < manifest.tables.len() {
+        let ready = manifest
+            .tables
+            .iter()
+            .find(|x| !done.contains(&x.name) && dependencies[&x.name].is_subset(&done))
+            .ok_or_else(|| {
+                invalid_manifest("Foreign keys between tables form a cycle".to_owned())
+            })?;
+        done.insert(ready.name.clone());
+        ordered.push(ready.clone());
+    }
+    Ok(ordered)
+}
+
+/// Returns the JSON Schema type of referenced values, nullable when some of them are null.
+/// Integers and other numbers together are numbers.
+fn type_of_values(values: &[Value]) -> Option<Value> {
+    let mut types: Vec<&str> = Vec::new();
+    for value in values {
+        let name = match value {
+            Value::Null => continue,
+            Value::Bool(_) => "boolean",
+            Value::Number(x) if x.is_i64() || x.is_u64() => "integer",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        };
+        if !types.contains(&name) {
+            types.push(name);
+        }
+    }
+    if types.len() == 2 && types.contains(&"integer") && types.contains(&"number") {
+        types = vec!["number"];
+    }
+    let nullable = values.iter().any(Value::is_null);
+    match types[..] {
+        [] => Some(json!("null")),
+        [name] if nullable => Some(json!([name, "null"])),
+        [name] => Some(json!(name)),
+        _ => None,
+    }
+}
+
+/// Replaces every `x-foreign-key` in a schema with an enum of the referenced values.
+/// A property without a `type` gets the type of the values it references.
+fn resolve_foreign_keys(
+    schema: &mut Value,
+    generated: &HashMap<String, Vec<Value>>,
+) -> io::Result<()> {
+    match schema {
+        Value::Object(map) => {
+            if let Some(reference) = map.remove("x-foreign-key") {
+                let (table, column) = split_reference(&reference)?;
+                let possible: Vec<Value> = generated[table]
+                    .iter()
+                    .map(|record| get_column(record, column).clone())
+                    .collect();
+                if possible.is_empty() {
+                    return Err(invalid_manifest(format!(
+                        "Table {} has no records to reference",
+                        table
+                    )));
+                }
+                if !map.contains_key("type") {
+                    let type_name = type_of_values(&possible).ok_or_else(|| {
+                        invalid_manifest(format!(
+                            "Column {} has values of several types, give the foreign key to it a type",
+                            reference
+                        ))
+                    })?;
+                    map.insert("type".to_owned(), type_name);
+                }
+                map.insert("enum".to_owned(), json!(possible));
+            }
+            for value in map.values_mut() {
+                resolve_foreign_keys(value, generated)?;
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                resolve_foreign_keys(value, generated)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Finds the top level property referencing `parent` and the column it references.
+fn parent_key<'a>(
+    properties: &'a Map<String, Value>,
+    parent: &str,
+) -> io::Result<(&'a String, String)> {
+    for (property, details) in properties.iter() {
+        if let Some(reference) = details.get("x-foreign-key") {
+            let (table, column) = split_reference(reference)?;
+            if table == parent {
+                return Ok((property, column.to_owned()));
+            }
+        }
+    }
+    Err(invalid_manifest(format!(
+        "No property has a foreign key to {}",
+        parent
+    )))
+}
+
+/// Generates the records for every table in a manifest, keyed by table name.
+/// Fails with an `InvalidInput` error for tables that can't be generated, such as one without a `count` or `per`.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::dataset::*;
+///
+/// let manifest = read_manifest("test_data/dataset/manifest.json").unwrap();
+/// let tables = generate_dataset(&manifest).unwrap();
+/// let customer_ids: Vec<_> = tables["customers"].iter().map(|x| x["id"].clone()).collect();
+/// for order in tables["orders"].iter() {
+///     assert!(customer_ids.contains(&order["customerId"]));
+/// }
+/// ```
+pub fn generate_dataset(manifest: &Manifest) -> io::Result<HashMap<String, Vec<Value>>> {
+    let mut generated = HashMap::<String, Vec<Value>>::new();
+    for table in dependency_order(manifest)? {
+        let mut schema = read_schema(&table.schema)?;
+        let properties = schema
+            .get("properties")
+            .and_then(|x| x.as_object())
+            .ok_or_else(|| {
+                invalid_manifest(format!("Schema of table {} has no properties", table.name))
+            })?;
+        // foreign key of each record when generating per parent
+        let mut keys = Vec::<Value>::new();
+        let mut key_property = None;
+        if let Some(per) = &table.per {
+            let (property, column) = parent_key(properties, &per.table)?;
+            let mut rng = generator_rng();
+            for parent in generated[&per.table].iter() {
+                let count = rng.gen_range(per.minimum..=per.maximum);
+                for _ in 0..count {
+                    keys.push(get_column(parent, &column).clone());
+                }
+            }
+            key_property = Some(property.clone());
+        }
+        let count = match (&table.per, table.count) {
+            (Some(_), _) => u32::try_from(keys.len()).map_err(|_| {
+                invalid_manifest(format!(
+                    "Table {} would have {} records, more than {}",
+                    table.name,
+                    keys.len(),
+                    u32::MAX
+                ))
+            })?,
+            (None, Some(count)) => count,
+            (None, None) => {
+                return Err(invalid_manifest(format!(
+                    "Table {} needs either a count or per",
+                    table.name
+                )))
+            }
+        };
+
+        resolve_foreign_keys(&mut schema, &generated)?;
+        let template = generate_template_from_value(&schema)?;
+        let mut records = create_json_vec_from_schema(&template, count)?;
+        if let Some(property) = key_property {
+            for (record, key) in records.iter_mut().zip(keys) {
+                record[&property] = key;
+            }
+        }
+        generated.insert(table.name.clone(), records);
+    }
+    Ok(generated)
+}
+
+/// Writes each generated table to the output path listed in the manifest in the given JSON style,
+/// compressed with the compression given or else the one named by the output's extension.
+pub fn dump_dataset(
+    manifest: &Manifest,
+    generated: &HashMap<String, Vec<Value>>,
+    style: &JsonStyle,
+    compression: Option<Compression>,
+) -> io::Result<()> {
+    for table in manifest.tables.iter() {
+        if let Some(parent) = Path::new(&table.output).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let records = &generated[&table.name];
+        dump_json_array_compressed(records, &table.output, style, compression)?;
+    }
+    Ok(())
+}
@@ -33,16 +33,131 @@
 //!
 //! // Generate a JSON array based on the array values
 //! let generated_array = array_values.get_value();
+//!
+//! // Define a sequence counting 1, 2, 3...
+//! let ids = SequenceValues::new(1, 1, None, SequenceScope::Parent);
+//! assert_eq!(ids.get_value(), json!(1));
+//! assert_eq!(ids.get_value(), json!(2));
 //! ```
 use crate::json_utils::create_json_from_schema;
 use dyn_clone::DynClone;
-use rand::{seq::SliceRandom, thread_rng, Rng};
-use serde_json::{json, Value};
-use std::collections::HashMap;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, RngCore, SeedableRng};
+use serde_json::{json, Map, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+/// Number of random draws `UniqueValues` makes before enumerating the domain.
+const MAX_UNIQUE_RETRIES: u32 = 100;
+
+/// Chance that `NullableValues` returns null instead of a value.
+const NULL_PROBABILITY: f64 = 0.1;
+
+/// Number of days between 1970-01-01 and the end of 2099 that dates are generated on.
+const MAX_DAYS: i64 = 47_482;
+
+/// Length of the random keys generated for maps.
+const MAP_KEY_LENGTH: u32 = 8;
+
+/// Characters used for randomly generated strings.
+const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Characters outside ASCII used for edge case strings, including a combining accent and a zero width space.
+const UNICODE: &[char] = &[
+    'é', 'ß', 'Ω', 'Ж', 'ع', '漢', '字', '🙂', '🚀', '\u{301}', '\u{200b}',
+];
+
+/// Characters used for whitespace heavy edge case strings.
+const WHITESPACE: &[char] = &[' ', ' ', '\t', '\n', '\r', '\u{a0}', '\u{3000}', 'a', 'b'];
+
+thread_local! {
+    /// Random number generator behind `generator_rng`, one for each thread.
+    static GENERATOR_RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Handle to the random number generator every generator of this thread draws from.
+/// Unlike `rand::thread_rng` it can be seeded, so the same seed generates the same records.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::*;
+///
+/// let values = RangedValues::new(0, 1_000_000);
+/// seed_generator_rng(7);
+/// let first = values.get_value();
+/// seed_generator_rng(7);
+/// assert_eq!(values.get_value(), first);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GeneratorRng;
+
+impl RngCore for GeneratorRng {
+    fn next_u32(&mut self) -> u32 {
+        GENERATOR_RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        GENERATOR_RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        GENERATOR_RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        GENERATOR_RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+/// Returns the random number generator of this thread used by every generator.
+pub fn generator_rng() -> GeneratorRng {
+    GeneratorRng
+}
+
+/// Restarts the random number generator of this thread from a seed.
+pub fn seed_generator_rng(seed: u64) {
+    GENERATOR_RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Runs a function with `rng` as the random number generator of this thread, keeping its new state
+/// afterwards so several seeded sources of values can take turns on one thread.
+pub fn with_generator_rng<T>(rng: &mut StdRng, f: impl FnOnce() -> T) -> T {
+    GENERATOR_RNG.with(|current| std::mem::swap(&mut *current.borrow_mut(), rng));
+    let result = f();
+    GENERATOR_RNG.with(|current| std::mem::swap(&mut *current.borrow_mut(), rng));
+    result
+}
 
 ///Returns a random variable from the possible values
 pub trait Values: DynClone {
     fn get_value(&self) -> Value;
+
+    /// Restarts any per-parent state, such as sequence counters.
+    /// Called by `ArrayValues` before each new array is generated.
+    fn reset(&self) {}
+
+    /// Number of distinct values this generator can produce, if it is finite and known.
+    fn domain_size(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the nth value of the domain in a deterministic order.
+    /// Only meaningful for `n < domain_size()`.
+    fn nth_value(&self, _n: u64) -> Option<Value> {
+        None
+    }
+
+    /// Maximum number of records that can be generated before a unique
+    /// field or a sequence in this generator runs out of values.
+    fn unique_capacity(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns a random boundary value, such as a bound of a range or the longest allowed string.
+    /// Generators without boundaries return `None`.
+    fn edge_value(&self) -> Option<Value> {
+        None
+    }
 }
 
 dyn_clone::clone_trait_object!(Values);
@@ -76,12 +191,34 @@ impl DiscreteValues {
 impl Values for DiscreteValues {
     // need to get range of random numbers
     fn get_value(&self) -> Value {
-        let mut rng = thread_rng();
+        let mut rng = generator_rng();
         self.possible.choose(&mut rng).unwrap().clone()
     }
+
+    fn domain_size(&self) -> Option<u64> {
+        let distinct: HashSet<String> = self.possible.iter().map(|x| x.to_string()).collect();
+        Some(distinct.len() as u64)
+    }
+
+    fn nth_value(&self, n: u64) -> Option<Value> {
+        let mut distinct = HashSet::new();
+        self.possible
+            .iter()
+            .filter(|x| distinct.insert(x.to_string()))
+            .nth(n as usize)
+            .cloned()
+    }
+
+    fn edge_value(&self) -> Option<Value> {
+        let mut rng = generator_rng();
+        [self.possible.first(), self.possible.last()]
+            .choose(&mut rng)
+            .unwrap()
+            .cloned()
+    }
 }
 
-/// Creates a new instance of RangedValues with the given start and end values.
+/// Creates a new instance of RangedValues between start and end inclusive.
 /// Range must start and end with a valid i64 integer
 ///
 /// # Examples
@@ -109,12 +246,345 @@ impl<T: Into<i64> + Copy> RangedValues<T> {
 impl<T: Into<i64> + Copy> Values for RangedValues<T> {
     // need to get range of random numbers
     fn get_value(&self) -> Value {
-        let mut rng = thread_rng();
+        let mut rng = generator_rng();
         let start_range = Into::<i64>::into(self.start);
         let end_range = Into::<i64>::into(self.end);
-        let val = rng.gen_range(start_range..end_range);
+        let val = rng.gen_range(start_range..=end_range);
         json!(val)
     }
+
+    fn domain_size(&self) -> Option<u64> {
+        let size = Into::<i64>::into(self.end) as i128 - Into::<i64>::into(self.start) as i128 + 1;
+        Some(size.clamp(0, u64::MAX as i128) as u64)
+    }
+
+    fn nth_value(&self, n: u64) -> Option<Value> {
+        let val = Into::<i64>::into(self.start) as i128 + n as i128;
+        (val <= Into::<i64>::into(self.end) as i128).then(|| json!(val as i64))
+    }
+
+    /// Either bound, a value just inside one, or zero when it is in range.
+    fn edge_value(&self) -> Option<Value> {
+        let mut rng = generator_rng();
+        let start = Into::<i64>::into(self.start);
+        let end = Into::<i64>::into(self.end);
+        let mut edges = vec![start, end, start.saturating_add(1), end.saturating_sub(1)];
+        edges.retain(|x| (start..=end).contains(x));
+        if (start..=end).contains(&0) {
+            edges.push(0);
+        }
+        edges.choose(&mut rng).map(|x| json!(x))
+    }
+}
+
+/// Creates a new instance of FloatValues between start and end inclusive.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::*;
+///
+/// let values = FloatValues::new(0.5, 1.5);
+/// let value = values.get_value().as_f64().unwrap();
+/// assert!((0.5..=1.5).contains(&value));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FloatValues {
+    start: f64,
+    end: f64,
+}
+
+impl FloatValues {
+    pub fn new(start: f64, end: f64) -> Self {
+        FloatValues { start, end }
+    }
+}
+
+impl Values for FloatValues {
+    fn get_value(&self) -> Value {
+        let mut rng = generator_rng();
+        json!(rng.gen_range(self.start..=self.end))
+    }
+
+    /// Either bound, the closest number inside one, or zero when it is in range.
+    fn edge_value(&self) -> Option<Value> {
+        let mut rng = generator_rng();
+        let mut edges = vec![
+            self.start,
+            self.end,
+            self.start.next_up(),
+            self.end.next_down(),
+        ];
+        edges.retain(|x| (self.start..=self.end).contains(x));
+        if (self.start..=self.end).contains(&0.0) {
+            edges.push(0.0);
+        }
+        edges.choose(&mut rng).map(|x| json!(x))
+    }
+}
+
+/// Well known string formats that can be generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringFormat {
+    /// `local@example.com`
+    Email,
+    /// RFC 3339 timestamp such as `2021-03-04T05:06:07Z`
+    DateTime,
+    /// Full date such as `2021-03-04`
+    Date,
+    /// Random version 4 UUID
+    Uuid,
+}
+
+impl StringFormat {
+    /// Returns the format matching a JSON Schema `format` name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "email" => Some(StringFormat::Email),
+            "date-time" => Some(StringFormat::DateTime),
+            "date" => Some(StringFormat::Date),
+            "uuid" => Some(StringFormat::Uuid),
+            _ => None,
+        }
+    }
+
+    /// Returns the JSON Schema `format` name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StringFormat::Email => "email",
+            StringFormat::DateTime => "date-time",
+            StringFormat::Date => "date",
+            StringFormat::Uuid => "uuid",
+        }
+    }
+}
+
+/// Creates random strings with a length between min and max inclusive,
+/// or strings of a well known format.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::*;
+///
+/// let values = StringValues::new(2, 4, None);
+/// let value = values.get_value();
+/// let len = value.as_str().unwrap().len();
+/// assert!(len >= 2 && len <= 4);
+///
+/// let emails = StringValues::new(0, 0, Some(StringFormat::Email));
+/// assert!(emails.get_value().as_str().unwrap().ends_with("@example.com"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct StringValues {
+    min: u32,
+    max: u32,
+    format: Option<StringFormat>,
+}
+
+impl StringValues {
+    pub fn new(min: u32, max: u32, format: Option<StringFormat>) -> Self {
+        StringValues { min, max, format }
+    }
+}
+
+impl Values for StringValues {
+    fn get_value(&self) -> Value {
+        let mut rng = generator_rng();
+        let val = match self.format {
+            Some(StringFormat::Email) => format!("{}@example.com", random_string(5, 10)),
+            Some(StringFormat::DateTime) => {
+                let seconds = rng.gen_range(0..4_102_444_800i64);
+                let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+                let time = seconds.rem_euclid(86_400);
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+                    year,
+                    month,
+                    day,
+                    time / 3600,
+                    time % 3600 / 60,
+                    time % 60
+                )
+            }
+            Some(StringFormat::Date) => format_date(rng.gen_range(0..MAX_DAYS)),
+            Some(StringFormat::Uuid) => format_uuid(rng.gen()),
+            None => random_string(self.min, self.max),
+        };
+        json!(val)
+    }
+
+    /// Counts the alphanumeric strings of every allowed length, or the days a date can fall on.
+    fn domain_size(&self) -> Option<u64> {
+        match self.format {
+            Some(StringFormat::Date) => Some(MAX_DAYS as u64),
+            Some(_) => None,
+            None => Some(
+                (self.min..=self.max)
+                    .map(|len| (ALPHANUMERIC.len() as u64).saturating_pow(len))
+                    .fold(0, u64::saturating_add),
+            ),
+        }
+    }
+
+    fn nth_value(&self, n: u64) -> Option<Value> {
+        match self.format {
+            Some(StringFormat::Date) => (n < MAX_DAYS as u64).then(|| json!(format_date(n as i64))),
+            Some(_) => None,
+            None => {
+                let mut n = n;
+                for len in self.min..=self.max {
+                    let count = (ALPHANUMERIC.len() as u64).saturating_pow(len);
+                    if n < count {
+                        return Some(json!(nth_string(n, len)));
+                    }
+                    n -= count;
+                }
+                None
+            }
+        }
+    }
+
+    /// The shortest or longest string, one full of unicode or whitespace, or the earliest and latest dates.
+    fn edge_value(&self) -> Option<Value> {
+        let mut rng = generator_rng();
+        let val = match self.format {
+            Some(StringFormat::Email) => ["a@example.com", "a.b-c+d@example.com"]
+                .choose(&mut rng)?
+                .to_string(),
+            Some(StringFormat::DateTime) => [
+                "1970-01-01T00:00:00Z",
+                "2099-12-31T23:59:59Z",
+                "2024-02-29T12:00:00Z",
+            ]
+            .choose(&mut rng)?
+            .to_string(),
+            Some(StringFormat::Date) => ["1970-01-01", "2099-12-31", "2024-02-29"]
+                .choose(&mut rng)?
+                .to_string(),
+            Some(StringFormat::Uuid) => [
+                "00000000-0000-4000-8000-000000000000",
+                "ffffffff-ffff-4fff-bfff-ffffffffffff",
+            ]
+            .choose(&mut rng)?
+            .to_string(),
+            None => match rng.gen_range(0..4) {
+                0 => random_string(self.min, self.min),
+                1 => random_string(self.max, self.max),
+                2 => random_chars(UNICODE, self.min.max(1).min(self.max), self.max),
+                _ => random_chars(WHITESPACE, self.min.max(1).min(self.max), self.max),
+            },
+        };
+        Some(json!(val))
+    }
+}
+
+/// Returns a random string of the given characters with a length between min and max inclusive.
+fn random_chars(chars: &[char], min: u32, max: u32) -> String {
+    let mut rng = generator_rng();
+    let len = rng.gen_range(min..=max);
+    (0..len).map(|_| *chars.choose(&mut rng).unwrap()).collect()
+}
+
+/// Returns a random alphanumeric string with a length between min and max inclusive.
+fn random_string(min: u32, max: u32) -> String {
+    let mut rng = generator_rng();
+    let len = rng.gen_range(min..=max);
+    (0..len)
+        .map(|_| *ALPHANUMERIC.choose(&mut rng).unwrap() as char)
+        .collect()
+}
+
+/// Returns the nth alphanumeric string of a length, counting like a number in base 62.
+fn nth_string(n: u64, len: u32) -> String {
+    let base = ALPHANUMERIC.len() as u64;
+    let mut n = n;
+    let mut chars = vec![ALPHANUMERIC[0]; len as usize];
+    for x in chars.iter_mut().rev() {
+        *x = ALPHANUMERIC[(n % base) as usize];
+        n /= base;
+    }
+    String::from_utf8(chars).unwrap()
+}
+
+/// Formats random bytes as a version 4 UUID.
+pub(crate) fn format_uuid(bytes: [u8; 16]) -> String {
+    let hex: String = bytes.iter().map(|x| format!("{:02x}", x)).collect();
+    format!(
+        "{}-{}-4{}-{:x}{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[13..16],
+        8 + (bytes[8] & 0x3),
+        &hex[17..20],
+        &hex[20..32]
+    )
+}
+
+/// Formats days since 1970-01-01 as a full date such as `2021-03-04`.
+fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Wraps another generator and sometimes returns null instead.
+#[derive(Clone)]
+pub struct NullableValues {
+    types: Box<dyn Values>,
+}
+
+impl NullableValues {
+    pub fn new(types: Box<dyn Values>) -> Self {
+        NullableValues { types }
+    }
+}
+
+impl Values for NullableValues {
+    fn get_value(&self) -> Value {
+        let mut rng = generator_rng();
+        if rng.gen_bool(NULL_PROBABILITY) {
+            return Value::Null;
+        }
+        self.types.get_value()
+    }
+
+    fn reset(&self) {
+        self.types.reset();
+    }
+
+    /// Null and every value of the wrapped generator.
+    fn domain_size(&self) -> Option<u64> {
+        self.types.domain_size()?.checked_add(1)
+    }
+
+    fn nth_value(&self, n: u64) -> Option<Value> {
+        match n {
+            0 => Some(Value::Null),
+            n => self.types.nth_value(n - 1),
+        }
+    }
+
+    fn unique_capacity(&self) -> Option<u64> {
+        self.types.unique_capacity()
+    }
+
+    fn edge_value(&self) -> Option<Value> {
+        self.types.edge_value()
+    }
 }
 
 ///Schema for an JSON object made up of dynamic values
@@ -133,6 +603,19 @@ impl<'a> Values for ObjectValues<'a> {
     fn get_value(&self) -> Value {
         create_json_from_schema(&self.schema)
     }
+
+    fn reset(&self) {
+        for values in self.schema.values() {
+            values.reset();
+        }
+    }
+
+    fn unique_capacity(&self) -> Option<u64> {
+        self.schema
+            .values()
+            .filter_map(|values| values.unique_capacity())
+            .min()
+    }
 }
 
 /// Creates new instances of array values with a random number of
@@ -149,17 +632,449 @@ impl ArrayValues {
     pub fn new(min: u32, max: u32, types: Box<dyn Values>) -> Self {
         ArrayValues { min, max, types }
     }
+
+    fn array_of(&self, len: u32) -> Value {
+        self.types.reset();
+        let mut my_values = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let new_value = self.types.get_value();
+            my_values.push(new_value);
+        }
+        json!(my_values)
+    }
 }
 
 impl Values for ArrayValues {
     fn get_value(&self) -> Value {
-        let mut rng = thread_rng();
+        let mut rng = generator_rng();
         let val = rng.gen_range(self.min..=self.max);
-        let mut my_values = Vec::with_capacity(val as usize);
-        for _ in 0..=val {
-            let new_value = self.types.get_value();
-            my_values.push(new_value);
+        self.array_of(val)
+    }
+
+    fn reset(&self) {
+        self.types.reset();
+    }
+
+    /// Records that can be generated when every array is as long as allowed.
+    fn unique_capacity(&self) -> Option<u64> {
+        Some(self.types.unique_capacity()? / u64::from(self.max.max(1)))
+    }
+
+    /// The shortest or longest array.
+    fn edge_value(&self) -> Option<Value> {
+        let mut rng = generator_rng();
+        Some(self.array_of(*[self.min, self.max].choose(&mut rng)?))
+    }
+}
+
+/// Creates objects with a random number of entries between min and max inclusive.
+/// Keys are random alphanumeric strings and every value comes from the same generator (types).
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::*;
+/// use serde_json::*;
+///
+/// let values = MapValues::new(1, 3, Box::new(DiscreteValues::new(&[json!(7)])));
+/// let value = values.get_value();
+/// let map = value.as_object().unwrap();
+/// assert!(!map.is_empty() && map.len() <= 3);
+/// assert!(map.values().all(|x| *x == json!(7)));
+/// ```
+#[derive(Clone)]
+pub struct MapValues {
+    min: u32,
+    max: u32,
+    types: Box<dyn Values>,
+}
+
+impl MapValues {
+    pub fn new(min: u32, max: u32, types: Box<dyn Values>) -> Self {
+        MapValues { min, max, types }
+    }
+
+    fn map_of(&self, len: u32) -> Value {
+        self.types.reset();
+        let mut map = Map::new();
+        // keys are long enough that collisions are rare, retry the few that happen
+        while map.len() < len as usize {
+            map.insert(
+                random_string(MAP_KEY_LENGTH, MAP_KEY_LENGTH),
+                self.types.get_value(),
+            );
         }
-        json!(my_values)
+        Value::Object(map)
+    }
+}
+
+impl Values for MapValues {
+    fn get_value(&self) -> Value {
+        let mut rng = generator_rng();
+        let len = rng.gen_range(self.min..=self.max);
+        self.map_of(len)
+    }
+
+    fn reset(&self) {
+        self.types.reset();
+    }
+
+    /// Records that can be generated when every map has as many entries as allowed.
+    fn unique_capacity(&self) -> Option<u64> {
+        Some(self.types.unique_capacity()? / u64::from(self.max.max(1)))
+    }
+
+    /// The map with the fewest or most entries.
+    fn edge_value(&self) -> Option<Value> {
+        let mut rng = generator_rng();
+        Some(self.map_of(*[self.min, self.max].choose(&mut rng)?))
+    }
+}
+
+/// Picks one of several generators at random for every value.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::*;
+/// use serde_json::*;
+///
+/// let values = OneOfValues::new(vec![
+///     Box::new(DiscreteValues::new(&[json!("A")])),
+///     Box::new(DiscreteValues::new(&[json!(1)])),
+/// ]);
+/// let value = values.get_value();
+/// assert!(value == json!("A") || value == json!(1));
+/// ```
+#[derive(Clone)]
+pub struct OneOfValues {
+    options: Vec<Box<dyn Values>>,
+}
+
+impl OneOfValues {
+    pub fn new(options: Vec<Box<dyn Values>>) -> Self {
+        OneOfValues { options }
+    }
+}
+
+impl Values for OneOfValues {
+    fn get_value(&self) -> Value {
+        let mut rng = generator_rng();
+        self.options.choose(&mut rng).unwrap().get_value()
+    }
+
+    fn edge_value(&self) -> Option<Value> {
+        let mut rng = generator_rng();
+        let option = self.options.choose(&mut rng).unwrap();
+        Some(option.edge_value().unwrap_or_else(|| option.get_value()))
+    }
+
+    fn reset(&self) {
+        for options in self.options.iter() {
+            options.reset();
+        }
+    }
+
+    /// Records that can be generated when the option with the fewest values left is picked every time.
+    fn unique_capacity(&self) -> Option<u64> {
+        self.options
+            .iter()
+            .filter_map(|x| x.unique_capacity())
+            .min()
+    }
+}
+
+/// Determines when a sequence restarts its numbering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceScope {
+    /// Restart at `start` for every array the sequence is nested in.
+    Parent,
+    /// Never restart, keep counting across the whole run.
+    Global,
+}
+
+/// Creates a counter that yields `start`, `start + step`, `start + 2 * step`...
+/// State is kept between calls so consecutive records get consecutive values.
+/// When a format is given the number is rendered into it, `{}` for the plain
+/// number or `{:0N}` to zero pad it to N digits, and a string is returned.
+/// A sequence never repeats a value, so counting past the range of a 64 bit integer is
+/// reported by `unique_capacity`. A sequence that counts past it anyway starts over at `start`.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::*;
+/// use serde_json::*;
+///
+/// let values = SequenceValues::new(1, 1, Some("ORD-{:06}".to_string()), SequenceScope::Global);
+/// assert_eq!(values.get_value(), json!("ORD-000001"));
+/// assert_eq!(values.get_value(), json!("ORD-000002"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SequenceValues {
+    start: i64,
+    step: i64,
+    format: Option<String>,
+    scope: SequenceScope,
+    /// None once counting on would overflow.
+    next: Cell<Option<i64>>,
+}
+
+impl SequenceValues {
+    pub fn new(start: i64, step: i64, format: Option<String>, scope: SequenceScope) -> Self {
+        SequenceValues {
+            start,
+            step,
+            format,
+            scope,
+            next: Cell::new(Some(start)),
+        }
+    }
+}
+
+impl Values for SequenceValues {
+    fn get_value(&self) -> Value {
+        let current = self.next.get().unwrap_or(self.start);
+        self.next.set(current.checked_add(self.step));
+        match &self.format {
+            Some(format) => json!(format_sequence(format, current)),
+            None => json!(current),
+        }
+    }
+
+    fn reset(&self) {
+        if self.scope == SequenceScope::Parent {
+            self.next.set(Some(self.start));
+        }
+    }
+
+    /// Number of values left before the counter passes the range of a 64 bit integer.
+    fn unique_capacity(&self) -> Option<u64> {
+        let Some(next) = self.next.get() else {
+            return Some(0);
+        };
+        let limit = match self.step {
+            0 => return None,
+            x if x > 0 => i64::MAX as i128,
+            _ => i64::MIN as i128,
+        };
+        let remaining = (limit - next as i128) / self.step as i128 + 1;
+        Some(remaining.clamp(0, u64::MAX as i128) as u64)
+    }
+}
+
+/// A sequence format split around its placeholder.
+struct SequenceFormat<'a> {
+    before: &'a str,
+    /// Width the number is padded to, with zeros when the flag is set
+    width: Option<(usize, bool)>,
+    after: &'a str,
+}
+
+/// Splits a sequence format around its placeholder. A format without a placeholder has the number appended.
+fn split_sequence_format(format: &str) -> Result<SequenceFormat<'_>, String> {
+    let Some(open) = format.find('{') else {
+        return Ok(SequenceFormat {
+            before: format,
+            width: None,
+            after: "",
+        });
+    };
+    let Some(close) = format[open..].find('}').map(|x| open + x) else {
+        return Err(format!(
+            "Sequence format {:?} has an unclosed placeholder",
+            format
+        ));
+    };
+    let width = match &format[open + 1..close] {
+        "" => None,
+        spec => {
+            let width = spec
+                .strip_prefix(':')
+                .filter(|x| !x.is_empty() && x.bytes().all(|x| x.is_ascii_digit()))
+                .ok_or_else(|| {
+                    format!("Sequence format {:?} should use {{}} or {{:0N}}", format)
+                })?;
+            let zero_pad = width.starts_with('0');
+            let width = width
+                .parse::<usize>()
+                .map_err(|_| format!("Sequence format {:?} has too wide a width", format))?;
+            Some((width, zero_pad))
+        }
+    };
+    Ok(SequenceFormat {
+        before: &format[..open],
+        width,
+        after: &format[close + 1..],
+    })
+}
+
+/// Checks that a sequence format has at most one well formed `{}` or `{:0N}` placeholder.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::check_sequence_format;
+///
+/// assert!(check_sequence_format("ORD-{:06}").is_ok());
+/// assert!(check_sequence_format("A}{").is_err());
+/// assert!(check_sequence_format("ORD-{:x}").is_err());
+/// ```
+pub fn check_sequence_format(format: &str) -> Result<(), String> {
+    split_sequence_format(format).map(|_| ())
+}
+
+/// Replaces the first `{}` or `{:0N}` placeholder in format with the number.
+/// A malformed placeholder is left out and the number appended instead.
+fn format_sequence(format: &str, number: i64) -> String {
+    let Ok(SequenceFormat {
+        before,
+        width,
+        after,
+    }) = split_sequence_format(format)
+    else {
+        return format!("{}{}", format, number);
+    };
+    match width {
+        Some((width, true)) => format!("{}{:0width$}{}", before, number, after, width = width),
+        Some((width, false)) => format!("{}{:width$}{}", before, number, after, width = width),
+        None => format!("{}{}{}", before, number, after),
+    }
+}
+
+/// Wraps another generator and never returns the same value twice.
+/// Previously emitted values are remembered for the lifetime of the generator.
+/// Random draws are retried a number of times, after which the domain of the
+/// wrapped generator is enumerated in order to find an unused value.
+/// Check `unique_capacity` before generating: once every value is used, or when a generator
+/// whose values can't be counted, such as an object, keeps repeating itself, values repeat.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::*;
+/// use serde_json::*;
+///
+/// let values = UniqueValues::new(Box::new(RangedValues::new(0, 2)));
+/// let mut seen = vec![values.get_value(), values.get_value(), values.get_value()];
+/// seen.sort_by_key(|x| x.as_i64());
+/// assert_eq!(seen, vec![json!(0), json!(1), json!(2)]);
+/// ```
+#[derive(Clone)]
+pub struct UniqueValues {
+    types: Box<dyn Values>,
+    seen: RefCell<HashSet<String>>,
+    cursor: Cell<u64>,
+}
+
+impl UniqueValues {
+    pub fn new(types: Box<dyn Values>) -> Self {
+        UniqueValues {
+            types,
+            seen: RefCell::new(HashSet::new()),
+            cursor: Cell::new(0),
+        }
+    }
+}
+
+impl Values for UniqueValues {
+    fn get_value(&self) -> Value {
+        let mut seen = self.seen.borrow_mut();
+        let mut val = Value::Null;
+        for _ in 0..MAX_UNIQUE_RETRIES {
+            val = self.types.get_value();
+            if seen.insert(val.to_string()) {
+                return val;
+            }
+        }
+        // every value before the cursor has already been emitted
+        let size = self.types.domain_size().unwrap_or(0);
+        while self.cursor.get() < size {
+            let n = self.cursor.get();
+            self.cursor.set(n + 1);
+            let Some(next) = self.types.nth_value(n) else {
+                break;
+            };
+            if seen.insert(next.to_string()) {
+                return next;
+            }
+        }
+        val
+    }
+
+    fn reset(&self) {
+        self.types.reset();
+    }
+
+    fn domain_size(&self) -> Option<u64> {
+        self.types.domain_size()
+    }
+
+    fn nth_value(&self, n: u64) -> Option<Value> {
+        self.types.nth_value(n)
+    }
+
+    fn unique_capacity(&self) -> Option<u64> {
+        let remaining = self.domain_size()? - self.seen.borrow().len() as u64;
+        Some(remaining)
+    }
+}
+
+/// Wraps another generator and returns one of its boundary values instead of a random one
+/// for a share of the values given by `ratio`, between 0 and 1.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::*;
+/// use serde_json::*;
+///
+/// let values = EdgeCaseValues::new(Box::new(RangedValues::new(10, 20)), 1.0);
+/// for _ in 0..20 {
+///     let value = values.get_value().as_i64().unwrap();
+///     assert!([10, 11, 19, 20].contains(&value));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct EdgeCaseValues {
+    types: Box<dyn Values>,
+    ratio: f64,
+}
+
+impl EdgeCaseValues {
+    pub fn new(types: Box<dyn Values>, ratio: f64) -> Self {
+        EdgeCaseValues { types, ratio }
+    }
+}
+
+impl Values for EdgeCaseValues {
+    fn get_value(&self) -> Value {
+        let mut rng = generator_rng();
+        if rng.gen_bool(self.ratio) {
+            if let Some(val) = self.types.edge_value() {
+                return val;
+            }
+        }
+        self.types.get_value()
+    }
+
+    fn reset(&self) {
+        self.types.reset();
+    }
+
+    fn domain_size(&self) -> Option<u64> {
+        self.types.domain_size()
+    }
+
+    fn nth_value(&self, n: u64) -> Option<Value> {
+        self.types.nth_value(n)
+    }
+
+    fn unique_capacity(&self) -> Option<u64> {
+        self.types.unique_capacity()
+    }
+
+    fn edge_value(&self) -> Option<Value> {
+        self.types.edge_value()
     }
 }
@@ -0,0 +1,317 @@
+//! # Pollinate Infer Module
+//!
+//! The `infer` module creates a pollinate schema from example JSON documents.
+//!
+//! ## Overview
+//!
+//! Every sample is walked and observations are merged for each path: the types seen, whether null was seen,
+//! which object keys were always present, numeric ranges, array lengths and string values. From these a schema
+//! is produced that pollinate can generate similar records from. Strings with few distinct values become an `enum`,
+//! and strings that all match a known format such as `email` or `date-time` are given that `format`.
+//! A field that is only ever null is a nullable string, so the schema still generates values for it.
+//!
+//! ## Functions
+//!
+//! - `read_samples`: Reads JSON, a JSON array of records, or NDJSON from a file.
+//! - `detect_format`: Detects the well known format of a string.
+//! - `infer_schema`: Creates a schema describing all of the samples.
+//!
+
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+
+use crate::default_values::StringFormat;
+use crate::json_utils::read_input;
+
+/// Most distinct values a string may have to be inferred as an enum.
+const MAX_ENUM_VALUES: usize = 10;
+
+/// Schema for a field only ever seen as null, or array items never seen at all.
+fn unknown_schema() -> Value {
+    json!({"type": ["string", "null"]})
+}
+
+/// Everything seen at a single path of the samples.
+#[derive(Default)]
+struct Observation {
+    nulls: u64,
+    booleans: u64,
+    integers: u64,
+    numbers: u64,
+    strings: u64,
+    arrays: u64,
+    objects: u64,
+    int_range: Option<(i64, i64)>,
+    number_range: Option<(f64, f64)>,
+    length_range: Option<(usize, usize)>,
+    string_values: Vec<String>,
+    distinct_strings: HashSet<String>,
+    /// `None` until a string is seen, then the format shared by every string so far
+    format: Option<Option<StringFormat>>,
+    array_range: Option<(usize, usize)>,
+    items: Option<Box<Observation>>,
+    properties: BTreeMap<String, Observation>,
+    /// Number of objects each property was present in
+    presence: BTreeMap<String, u64>,
+}
+
+/// Widens an inclusive range to contain value.
+fn widen<T: PartialOrd + Copy>(range: Option<(T, T)>, value: T) -> Option<(T, T)> {
+    match range {
+        None => Some((value, value)),
+        Some((min, max)) => Some((
+            if value < min { value } else { min },
+            if value > max { value } else { max },
+        )),
+    }
+}
+
+impl Observation {
+    fn observe(&mut self, value: &Value) {
+        match value {
+            Value::Null => self.nulls += 1,
+            Value::Bool(_) => self.booleans += 1,
+            Value::Number(x) => {
+                if let Some(x) = x.as_i64() {
+                    self.integers += 1;
+                    self.int_range = widen(self.int_range, x);
+                } else {
+                    self.numbers += 1;
+                }
+                self.number_range = widen(self.number_range, x.as_f64().unwrap());
+            }
+            Value::String(x) => {
+                self.strings += 1;
+                self.length_range = widen(self.length_range, x.chars().count());
+                if self.distinct_strings.len() <= MAX_ENUM_VALUES
+                    && self.distinct_strings.insert(x.clone())
+                {
+                    self.string_values.push(x.clone());
+                }
+                let format = detect_format(x);
+                self.format = match self.format {
+                    None => Some(format),
+                    Some(current) if current == format => Some(current),
+                    Some(_) => Some(None),
+                };
+            }
+            Value::Array(values) => {
+                self.arrays += 1;
+                self.array_range = widen(self.array_range, values.len());
+                let items = self.items.get_or_insert_with(Default::default);
+                for value in values {
+                    items.observe(value);
+                }
+            }
+            Value::Object(map) => {
+                self.objects += 1;
+                for (key, value) in map {
+                    *self.presence.entry(key.clone()).or_default() += 1;
+                    self.properties
+                        .entry(key.clone())
+                        .or_default()
+                        .observe(value);
+                }
+            }
+        }
+    }
+
+    /// Picks the most common type, treating a mix of integers and decimals as numbers.
+    fn type_name(&self) -> Option<&'static str> {
+        let numbers = if self.numbers > 0 {
+            ("number", self.numbers + self.integers)
+        } else {
+            ("integer", self.integers)
+        };
+        [
+            ("boolean", self.booleans),
+            numbers,
+            ("string", self.strings),
+            ("array", self.arrays),
+            ("object", self.objects),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .max_by_key(|(_, count)| *count)
+        .map(|(name, _)| name)
+    }
+
+    fn to_schema(&self) -> Value {
+        let Some(type_name) = self.type_name() else {
+            return unknown_schema();
+        };
+        let mut schema = match type_name {
+            "integer" => {
+                let (min, max) = self.int_range.unwrap();
+                if min == max {
+                    json!({"type": "integer", "enum": [min]})
+                } else {
+                    json!({"type": "integer", "minimum": min, "maximum": max})
+                }
+            }
+            "number" => {
+                let (min, max) = self.number_range.unwrap();
+                json!({"type": "number", "minimum": min, "maximum": max})
+            }
+            "string" => self.string_schema(),
+            "array" => {
+                let (min, max) = self.array_range.unwrap();
+                let items = match &self.items {
+                    Some(items) => items.to_schema(),
+                    None => unknown_schema(),
+                };
+                json!({"type": "array", "minimum": min, "maximum": max, "items": items})
+            }
+            "object" => {
+                let mut properties = Map::new();
+                for (key, observation) in self.properties.iter() {
+                    properties.insert(key.clone(), observation.to_schema());
+                }
+                let required: Vec<&String> = self
+                    .presence
+                    .iter()
+                    .filter(|(_, count)| **count == self.objects)
+                    .map(|(key, _)| key)
+                    .collect();
+                json!({"type": "object", "properties": properties, "required": required})
+            }
+            _ => json!({"type": type_name}),
+        };
+        if self.nulls > 0 {
+            schema["type"] = json!([type_name, "null"]);
+        }
+        schema
+    }
+
+    fn string_schema(&self) -> Value {
+        let distinct = self.distinct_strings.len();
+        if distinct <= MAX_ENUM_VALUES && self.strings >= 2 * distinct as u64 {
+            return json!({"type": "string", "enum": self.string_values});
+        }
+        if let Some(Some(format)) = self.format {
+            return json!({"type": "string", "format": format.name()});
+        }
+        let (min, max) = self.length_range.unwrap();
+        json!({"type": "string", "minLength": min, "maxLength": max})
+    }
+}
+
+/// Reads example documents from a file, or from standard input when the path is `-`.
+/// The file may hold a single JSON document, several concatenated documents or NDJSON.
+/// A top level array is treated as a list of records.
+/// Fails when the file can't be read or holds anything but JSON.
+pub fn read_samples(sample_path: &str) -> io::Result<Vec<Value>> {
+    let sample_string = read_input(sample_path)?;
+    let mut samples = Vec::<Value>::new();
+    for document in serde_json::Deserializer::from_str(&sample_string).into_iter::<Value>() {
+        match document.map_err(io::Error::from)? {
+            Value::Array(records) => samples.extend(records),
+            record => samples.push(record),
+        }
+    }
+    Ok(samples)
+}
+
+/// Detects the well known format of a string.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::StringFormat;
+/// use pollinate::infer::detect_format;
+///
+/// assert_eq!(detect_format("adam@example.com"), Some(StringFormat::Email));
+/// assert_eq!(detect_format("2021-03-04T05:06:07Z"), Some(StringFormat::DateTime));
+/// assert_eq!(detect_format("2021-03-04"), Some(StringFormat::Date));
+/// assert_eq!(detect_format("Adam"), None);
+/// ```
+pub fn detect_format(value: &str) -> Option<StringFormat> {
+    let is_date = |x: &[u8]| {
+        x.len() == 10
+            && x.iter().enumerate().all(|(i, c)| match i {
+                4 | 7 => *c == b'-',
+                _ => c.is_ascii_digit(),
+            })
+    };
+    let bytes = value.as_bytes();
+    if is_date(bytes) {
+        return Some(StringFormat::Date);
+    }
+    if bytes.len() >= 20
+        && is_date(&bytes[..10])
+        && matches!(bytes[10], b'T' | b't' | b' ')
+        && bytes[13] == b':'
+        && bytes[16] == b':'
+        && [11, 12, 14, 15, 17, 18]
+            .iter()
+            .all(|i| bytes[*i].is_ascii_digit())
+    {
+        return Some(StringFormat::DateTime);
+    }
+    if bytes.len() == 36
+        && bytes.iter().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => *c == b'-',
+            _ => c.is_ascii_hexdigit(),
+        })
+    {
+        return Some(StringFormat::Uuid);
+    }
+    if let Some((local, domain)) = value.split_once('@') {
+        if !local.is_empty()
+            && !domain.starts_with('.')
+            && domain.contains('.')
+            && !domain.ends_with('.')
+            && !value.contains(char::is_whitespace)
+            && !domain.contains('@')
+        {
+            return Some(StringFormat::Email);
+        }
+    }
+    None
+}
+
+/// Creates a schema describing every one of the samples.
+/// Fails with an `InvalidInput` error when there are no samples or they aren't objects,
+/// as pollinate only generates objects.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::infer::infer_schema;
+/// use serde_json::json;
+///
+/// let samples = vec![
+///     json!({"name": "Adam", "age": 30, "email": "adam@example.com"}),
+///     json!({"name": "Adam", "age": 41, "email": null}),
+/// ];
+/// let schema = infer_schema(&samples).unwrap();
+/// assert_eq!(schema["properties"]["age"], json!({"type": "integer", "minimum": 30, "maximum": 41}));
+/// assert_eq!(schema["properties"]["email"]["type"], json!(["string", "null"]));
+/// assert_eq!(schema["required"], json!(["age", "email", "name"]));
+///
+/// assert!(infer_schema(&[]).is_err());
+/// ```
+pub fn infer_schema(samples: &[Value]) -> io::Result<Value> {
+    if samples.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "No samples to infer a schema from",
+        ));
+    }
+    let mut observation = Observation::default();
+    for sample in samples {
+        observation.observe(sample);
+    }
+    if observation.type_name() != Some("object") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Samples must be JSON objects to infer a schema from them",
+        ));
+    }
+    let mut schema = json!({"$schema": "https://json-schema.org/draft/2020-12/schema"});
+    if let Value::Object(inferred) = observation.to_schema() {
+        schema.as_object_mut().unwrap().extend(inferred);
+    }
+    Ok(schema)
+}
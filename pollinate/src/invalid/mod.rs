@@ -0,0 +1,417 @@
+//! # Pollinate Invalid Module
+//!
+//! The `invalid` module breaks generated records so they violate exactly one constraint of their schema, for
+//! testing how parsers and APIs handle almost valid data.
+//!
+//! ## Overview
+//!
+//! Every constraint a record could break is collected from the schema along with the value breaking it:
+//!
+//! - `type`: A value of another type, also used for `x-sequence` values. The record itself stays an object.
+//! - `oneOf`: A value matching none of the schemas.
+//! - `enum`: A value that isn't listed.
+//! - `minimum`, `maximum`: A number just outside the range, or an array one item too short or too long.
+//! - `minLength`, `maxLength`: A string one character too short or too long.
+//! - `format`: An almost valid email, date, date-time or uuid, such as `2023-02-30`.
+//! - `required`: A required property left out.
+//! - `additionalProperties`: A property the schema doesn't allow.
+//! - `minProperties`, `maxProperties`: An object one property short or over.
+//!
+//! One of them is picked at random, and kept only when the validator finds exactly one error in the broken
+//! record. The `Violation` describes that error and which constraint was broken.
+//!
+//! ## Functions
+//!
+//! - `break_record`: Breaks a single record.
+//! - `annotated_schema`: Schema of the records written with their violation.
+//!
+
+use rand::seq::SliceRandom;
+use serde_json::{json, Map, Value};
+use std::io;
+use std::io::Write;
+
+use crate::default_values::{generator_rng, StringFormat};
+use crate::json_utils::RecordWriter;
+use crate::schema::parse_type;
+use crate::validate::validate;
+
+/// Name of the property added to objects where `additionalProperties` is false.
+const UNEXPECTED_PROPERTY: &str = "unexpected";
+
+/// A constraint broken by a record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// JSONPath of the value reported by the validator, `$` for the record itself
+    pub path: String,
+    /// Keyword of the constraint, such as `maximum` or `required`
+    pub rule: String,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn to_json(&self) -> Value {
+        json!({"path": self.path, "rule": self.rule, "message": self.message})
+    }
+}
+
+/// A value that breaks a constraint when it replaces the value at a JSON Pointer.
+struct Candidate {
+    pointer: String,
+    rule: &'static str,
+    value: Value,
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Values of every type, to pick one that a schema doesn't allow.
+fn wrong_types() -> [Value; 6] {
+    [
+        json!("invalid"),
+        json!(7),
+        json!(0.5),
+        json!(true),
+        json!([]),
+        json!({}),
+    ]
+}
+
+/// A string close to the format that still isn't valid.
+fn wrong_format(format: StringFormat) -> Value {
+    match format {
+        StringFormat::Email => json!("user.example.com"),
+        StringFormat::DateTime => json!("2021-03-04T25:06:07Z"),
+        StringFormat::Date => json!("2023-02-30"),
+        StringFormat::Uuid => json!("e3820c82-c8c4-44bd-9db4-d48651ecb24g"),
+    }
+}
+
+/// A number past a bound, keeping integers as integers.
+fn past(bound: &Value, step: i64) -> Option<Value> {
+    match bound.as_i64() {
+        Some(x) => Some(json!(x.checked_add(step)?)),
+        None => Some(json!(bound.as_f64()? + step as f64)),
+    }
+}
+
+/// New values generated from a schema.
+fn generate(schema: Option<&Value>, count: usize) -> Option<Vec<Value>> {
+    let values = parse_type(schema?).ok().flatten()?;
+    Some((0..count).map(|_| values.get_value()).collect())
+}
+
+/// Collects the candidates for a value and everything in it.
+fn collect(value: &Value, schema: &Value, pointer: &str, candidates: &mut Vec<Candidate>) {
+    let mut add = |rule, value| {
+        candidates.push(Candidate {
+            pointer: pointer.to_string(),
+            rule,
+            value,
+        })
+    };
+    let allowed = match (
+        schema.get("oneOf"),
+        schema.get("x-sequence"),
+        schema.get("type"),
+    ) {
+        (Some(options), _, _) => Some(("oneOf", json!({ "oneOf": options }))),
+        (None, Some(sequence), _) if sequence.get("format").is_some() => {
+            Some(("type", json!({"type": "string"})))
+        }
+        (None, Some(_), _) => Some(("type", json!({"type": "integer"}))),
+        (None, None, Some(types)) => Some(("type", json!({ "type": types }))),
+        (None, None, None) => None,
+    };
+    // the record itself always stays an object
+    if let Some((rule, allowed)) = allowed.filter(|_| !pointer.is_empty()) {
+        let wrong: Vec<Value> = wrong_types()
+            .into_iter()
+            .filter(|x| !validate(x, &allowed).is_empty())
+            .collect();
+        if let Some(wrong) = wrong.choose(&mut generator_rng()) {
+            add(rule, wrong.clone());
+        }
+    }
+    if schema.get("x-sequence").is_some() || schema.get("oneOf").is_some() {
+        return;
+    }
+    if value.is_null() {
+        return;
+    }
+    if let Some(options) = schema.get("enum").and_then(|x| x.as_array()) {
+        // one more try than there are options always finds a value that isn't listed
+        let tries = 1..=options.len() as i64 + 1;
+        let wrong = match value {
+            Value::String(text) => tries
+                .map(|i| json!(format!("{}{}", text, "_".repeat(i as usize))))
+                .find(|x| !options.contains(x)),
+            Value::Number(_) => tries
+                .filter_map(|i| past(value, i))
+                .find(|x| !options.contains(x)),
+            _ => None,
+        };
+        if let Some(wrong) = wrong {
+            add("enum", wrong);
+        }
+        return;
+    }
+    match value {
+        Value::Number(_) => {
+            if let Some(wrong) = schema.get("minimum").and_then(|x| past(x, -1)) {
+                add("minimum", wrong);
+            }
+            if let Some(wrong) = schema.get("maximum").and_then(|x| past(x, 1)) {
+                add("maximum", wrong);
+            }
+        }
+        Value::String(_) => {
+            let length = |key| schema.get(key).and_then(|x: &Value| x.as_u64());
+            if let Some(min) = length("minLength").filter(|x| *x > 0) {
+                add("minLength", json!("x".repeat(min as usize - 1)));
+            }
+            if let Some(max) = length("maxLength") {
+                add("maxLength", json!("x".repeat(max as usize + 1)));
+            }
+            let format = schema.get("format").and_then(|x| x.as_str());
+            if let Some(format) = format.and_then(StringFormat::from_name) {
+                add("format", wrong_format(format));
+            }
+        }
+        Value::Array(items) => {
+            let items_schema = schema.get("items");
+            if let Some(min) = schema.get("minimum").and_then(|x| x.as_u64()) {
+                if min > 0 {
+                    let mut shorter = items.clone();
+                    shorter.truncate(min as usize - 1);
+                    add("minimum", Value::Array(shorter));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(|x| x.as_u64()) {
+                let missing = (max as usize + 1).saturating_sub(items.len());
+                if let Some(extra) = generate(items_schema, missing) {
+                    let mut longer = items.clone();
+                    longer.extend(extra);
+                    add("maximum", Value::Array(longer));
+                }
+            }
+            if let Some(items_schema) = items_schema {
+                for (i, item) in items.iter().enumerate() {
+                    collect(
+                        item,
+                        items_schema,
+                        &format!("{}/{}", pointer, i),
+                        candidates,
+                    );
+                }
+            }
+        }
+        Value::Object(map) => collect_object(map, schema, pointer, candidates),
+        _ => (),
+    }
+}
+
+fn collect_object(
+    map: &Map<String, Value>,
+    schema: &Value,
+    pointer: &str,
+    candidates: &mut Vec<Candidate>,
+) {
+    let mut add = |rule, map: Map<String, Value>| {
+        candidates.push(Candidate {
+            pointer: pointer.to_string(),
+            rule,
+            value: Value::Object(map),
+        })
+    };
+    let required = schema.get("required").and_then(|x| x.as_array());
+    for key in required.into_iter().flatten().filter_map(|x| x.as_str()) {
+        let mut without = map.clone();
+        if without.remove(key).is_some() {
+            add("required", without);
+        }
+    }
+    let additional = schema.get("additionalProperties");
+    if additional == Some(&Value::Bool(false)) && !map.contains_key(UNEXPECTED_PROPERTY) {
+        let mut with = map.clone();
+        with.insert(UNEXPECTED_PROPERTY.to_string(), json!(true));
+        add("additionalProperties", with);
+    }
+    let entries = additional.filter(|x| x.is_object());
+    if let Some(min) = schema.get("minProperties").and_then(|x| x.as_u64()) {
+        if min > 0 {
+            let fewer = map.clone().into_iter().take(min as usize - 1).collect();
+            add("minProperties", fewer);
+        }
+    }
+    if let Some(max) = schema.get("maxProperties").and_then(|x| x.as_u64()) {
+        let missing = (max as usize + 1).saturating_sub(map.len());
+        if let Some(extra) = generate(entries, missing) {
+            let mut more = map.clone();
+            let keys = (0..)
+                .map(|i| format!("key{}", i))
+                .filter(|x| !map.contains_key(x));
+            more.extend(keys.zip(extra));
+            add("maxProperties", more);
+        }
+    }
+    let properties = schema.get("properties").and_then(|x| x.as_object());
+    for (key, value) in map.iter() {
+        let details = properties.and_then(|x| x.get(key)).or(entries);
+        if let Some(details) = details {
+            let pointer = format!("{}/{}", pointer, escape_pointer(key));
+            collect(value, details, &pointer, candidates);
+        }
+    }
+}
+
+/// Breaks a valid record so it violates exactly one constraint of its schema, picked at random.
+/// Returns `None` when the schema has no constraint that can be broken on its own.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::invalid::break_record;
+/// use pollinate::validate::validate;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {"age": {"type": "integer", "minimum": 0, "maximum": 100}}});
+/// let (broken, violation) = break_record(&json!({"age": 30}), &schema).unwrap();
+/// assert_eq!(violation.path, "$.age");
+/// assert!(["type", "minimum", "maximum"].contains(&violation.rule.as_str()));
+/// assert_eq!(validate(&broken, &schema).len(), 1);
+/// ```
+pub fn break_record(record: &Value, schema: &Value) -> Option<(Value, Violation)> {
+    let mut candidates = Vec::new();
+    collect(record, schema, "", &mut candidates);
+    candidates.shuffle(&mut generator_rng());
+    for candidate in candidates {
+        let mut broken = record.clone();
+        *broken.pointer_mut(&candidate.pointer)? = candidate.value;
+        if let [error] = &validate(&broken, schema)[..] {
+            let violation = Violation {
+                path: error.path.clone(),
+                rule: candidate.rule.to_string(),
+                message: error.message.clone(),
+            };
+            return Some((broken, violation));
+        }
+    }
+    None
+}
+
+/// Schema of records written along with their violation, as `{"record": ..., "violation": ...}`.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::invalid::annotated_schema;
+/// use serde_json::json;
+///
+/// let schema = json!({"title": "Person", "type": "object", "properties": {}});
+/// let annotated = annotated_schema(&schema);
+/// assert_eq!(annotated["title"], "Person");
+/// assert_eq!(annotated["properties"]["record"], schema);
+/// ```
+pub fn annotated_schema(schema: &Value) -> Value {
+    let text = json!({"type": "string"});
+    let mut annotated = json!({
+        "type": "object",
+        "properties": {
+            "record": schema,
+            "violation": {
+                "type": "object",
+                "properties": {"path": text, "rule": text, "message": text}
+            }
+        }
+    });
+    if let Some(title) = schema.get("title") {
+        annotated["title"] = title.clone();
+    }
+    annotated
+}
+
+/// Breaks every record before passing it on to another writer.
+/// Each violation is either written along with its record, shaped by `annotated_schema`, or as a line of a
+/// separate NDJSON file holding the number of the record it belongs to.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::invalid::{annotated_schema, InvalidWriter};
+/// use pollinate::json_utils::{NdjsonWriter, RecordWriter};
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "required": ["id"], "properties": {"id": {"type": "string", "format": "uuid"}}});
+/// let mut output = Vec::new();
+/// let mut writer = InvalidWriter::new(Box::new(NdjsonWriter::new(&mut output)), &schema);
+/// writer.write_record(&json!({"id": "e3820c82-c8c4-44bd-9db4-d48651ecb240"})).unwrap();
+/// writer.finish().unwrap();
+/// drop(writer);
+/// let annotated: serde_json::Value = serde_json::from_slice(&output).unwrap();
+/// assert!(["type", "format", "required"].contains(&annotated["violation"]["rule"].as_str().unwrap()));
+/// ```
+pub struct InvalidWriter<'a> {
+    writer: Box<dyn RecordWriter + 'a>,
+    schema: Value,
+    violations: Option<Box<dyn Write + 'a>>,
+    written: u64,
+}
+
+impl<'a> InvalidWriter<'a> {
+    /// Writes each record with its violation, the writer should expect records of the `annotated_schema`.
+    pub fn new(writer: Box<dyn RecordWriter + 'a>, schema: &Value) -> Self {
+        InvalidWriter {
+            writer,
+            schema: schema.clone(),
+            violations: None,
+            written: 0,
+        }
+    }
+
+    /// Writes the broken records as they are, and their violations to a separate NDJSON output.
+    pub fn with_violations(
+        writer: Box<dyn RecordWriter + 'a>,
+        schema: &Value,
+        violations: Box<dyn Write + 'a>,
+    ) -> Self {
+        InvalidWriter {
+            writer,
+            schema: schema.clone(),
+            violations: Some(violations),
+            written: 0,
+        }
+    }
+}
+
+impl RecordWriter for InvalidWriter<'_> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        self.written += 1;
+        let Some((broken, violation)) = break_record(record, &self.schema) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The schema has no constraint that can be broken on its own",
+            ));
+        };
+        match &mut self.violations {
+            Some(violations) => {
+                let mut line = violation.to_json();
+                line["record"] = json!(self.written);
+                writeln!(violations, "{}", line)?;
+                self.writer.write_record(&broken)
+            }
+            None => {
+                let annotated = json!({"record": broken, "violation": violation.to_json()});
+                self.writer.write_record(&annotated)
+            }
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(violations) = &mut self.violations {
+            violations.flush()?;
+        }
+        self.writer.finish()
+    }
+}
@@ -4,8 +4,158 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::io;
+use std::io::{BufWriter, Write};
+use std::str::FromStr;
 
+use crate::avro_utils::{avro_schema_from_schema, AvroWriter};
+use crate::binary_utils::{BinaryFormat, BinaryWriter};
+use crate::compress_utils::{strip_extension, CompressedWriter, Compression, Encoder};
+use crate::csv_utils::CsvWriter;
 use crate::default_values::Values;
+use crate::parquet_utils::{ParquetWriter, DEFAULT_ROW_GROUP_SIZE};
+use crate::sql_utils::{
+    table_name_from_schema, tables_from_schema, SqlDialect, SqlNesting, SqlWriter, SqliteWriter,
+};
+use crate::template_utils::{TemplateWriter, TextTemplate};
+use crate::xml_utils::XmlWriter;
+
+/// How JSON output is laid out.
+/// Pretty output puts every value on its own line indented by `indent` spaces.
+/// Canonical output follows RFC 8785: keys sorted by their UTF-16 code units and numbers
+/// written the way ECMAScript does, so the same data always produces the same bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JsonStyle {
+    pub indent: Option<usize>,
+    pub canonical: bool,
+}
+
+impl JsonStyle {
+    pub fn pretty(indent: usize) -> Self {
+        JsonStyle {
+            indent: Some(indent),
+            canonical: false,
+        }
+    }
+
+    pub fn canonical() -> Self {
+        JsonStyle {
+            indent: None,
+            canonical: true,
+        }
+    }
+}
+
+/// Writes a number as ECMAScript would, the shortest digits that round trip and
+/// an exponent only below 1e-6 or from 1e21.
+fn canonical_number(x: f64) -> String {
+    if x == 0.0 {
+        return "0".to_owned();
+    }
+    let scientific = format!("{:e}", x.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+    let sign = if x < 0.0 { "-" } else { "" };
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let fraction = if k > 1 {
+            format!(".{}", &digits[1..])
+        } else {
+            String::new()
+        };
+        let exponent_sign = if n > 0 { "+" } else { "-" };
+        format!(
+            "{}{}e{}{}",
+            &digits[..1],
+            fraction,
+            exponent_sign,
+            (n - 1).abs()
+        )
+    };
+    format!("{}{}", sign, body)
+}
+
+fn write_indent(writer: &mut impl Write, style: &JsonStyle, depth: usize) -> io::Result<()> {
+    if let Some(indent) = style.indent {
+        write!(writer, "\n{}", " ".repeat(indent * depth))?;
+    }
+    Ok(())
+}
+
+fn write_styled(
+    writer: &mut impl Write,
+    value: &Value,
+    style: &JsonStyle,
+    depth: usize,
+) -> io::Result<()> {
+    match value {
+        Value::Number(x) if style.canonical && x.is_f64() => {
+            writer.write_all(canonical_number(x.as_f64().unwrap()).as_bytes())
+        }
+        Value::Array(values) if !values.is_empty() => {
+            writer.write_all(b"[")?;
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(b",")?;
+                }
+                write_indent(writer, style, depth + 1)?;
+                write_styled(writer, value, style, depth + 1)?;
+            }
+            write_indent(writer, style, depth)?;
+            writer.write_all(b"]")
+        }
+        Value::Object(map) if !map.is_empty() => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            if style.canonical {
+                keys.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16()));
+            }
+            writer.write_all(b"{")?;
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(b",")?;
+                }
+                write_indent(writer, style, depth + 1)?;
+                serde_json::to_writer(&mut *writer, key)?;
+                writer.write_all(if style.indent.is_some() { b": " } else { b":" })?;
+                write_styled(writer, &map[key], style, depth + 1)?;
+            }
+            write_indent(writer, style, depth)?;
+            writer.write_all(b"}")
+        }
+        value => Ok(serde_json::to_writer(writer, value)?),
+    }
+}
+
+/// Writes a JSON value in the given style.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+/// use serde_json::json;
+///
+/// let value = json!({"b": [1.5e21, 0.000001, 100.0], "a": {"c": -0.0}});
+///
+/// let mut output = Vec::new();
+/// write_json(&mut output, &value, &JsonStyle::canonical()).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), r#"{"a":{"c":0},"b":[1.5e+21,0.000001,100]}"#);
+///
+/// let mut output = Vec::new();
+/// write_json(&mut output, &json!({"a": [1, 2]}), &JsonStyle::pretty(2)).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "{\n  \"a\": [\n    1,\n    2\n  ]\n}");
+/// ```
+pub fn write_json(writer: &mut impl Write, value: &Value, style: &JsonStyle) -> io::Result<()> {
+    if *style == JsonStyle::default() {
+        return Ok(serde_json::to_writer(writer, value)?);
+    }
+    write_styled(writer, value, style, 0)
+}
 
 //add error handling?
 /// Dumps a single JSON Struct to a file.
@@ -33,8 +183,104 @@ use crate::default_values::Values;
 /// assert!(result.is_ok());
 /// ```
 pub fn dump_json(object: &impl Serialize, file: &str) -> io::Result<()> {
-    let json_string = serde_json::to_string(&object).unwrap();
-    fs::write(file, json_string)
+    dump_json_with_style(object, file, &JsonStyle::default())
+}
+
+/// Dumps a single JSON Struct to a file in the given style.
+pub fn dump_json_with_style(
+    object: &impl Serialize,
+    file: &str,
+    style: &JsonStyle,
+) -> io::Result<()> {
+    dump_value_with_style(serde_json::to_value(object)?, file, style)
+}
+
+/// Path that stands for standard input or standard output instead of a file.
+pub const STDIO_PATH: &str = "-";
+
+/// Buffered writer for an output file or standard output.
+pub type OutputWriter = BufWriter<Box<dyn Write + Send>>;
+
+/// Opens a file for buffered writing, or standard output when the path is `-`.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+/// use std::io::Write;
+///
+/// let mut writer = create_output("-").unwrap();
+/// assert!(writer.flush().is_ok());
+/// ```
+pub fn create_output(file: &str) -> io::Result<OutputWriter> {
+    Ok(BufWriter::new(open_output(file)?))
+}
+
+fn open_output(file: &str) -> io::Result<Box<dyn Write + Send>> {
+    Ok(match file {
+        STDIO_PATH => Box::new(io::stdout()),
+        _ => Box::new(fs::File::create(file)?),
+    })
+}
+
+/// Opens a file for buffered writing like `create_output`, compressing everything written when a compression is given.
+/// The encoder returned has to be finished after the writer is flushed to write the end of the compressed stream.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::compress_utils::Compression;
+/// use pollinate::json_utils::*;
+/// use std::io::Write;
+///
+/// let (mut writer, encoder) = create_compressed_output("out.txt.gz", Some(Compression::Gzip)).unwrap();
+/// writer.write_all(b"text").unwrap();
+/// writer.flush().unwrap();
+/// encoder.unwrap().finish().unwrap();
+/// # std::fs::remove_file("out.txt.gz").unwrap();
+/// ```
+pub fn create_compressed_output(
+    file: &str,
+    compression: Option<Compression>,
+) -> io::Result<(OutputWriter, Option<Encoder>)> {
+    match compression {
+        Some(compression) => {
+            let output = Box::new(BufWriter::new(open_output(file)?));
+            let encoder = Encoder::new(output, compression)?;
+            let writer: Box<dyn Write + Send> = Box::new(encoder.clone());
+            Ok((BufWriter::new(writer), Some(encoder)))
+        }
+        None => Ok((create_output(file)?, None)),
+    }
+}
+
+/// Writes every object to a file through the record writer built on its output,
+/// compressed with the compression given or else the one named by the file's extension.
+pub(crate) fn dump_records<W: RecordWriter>(
+    objects: &[Value],
+    file: &str,
+    compression: Option<Compression>,
+    writer: impl FnOnce(OutputWriter) -> io::Result<W>,
+) -> io::Result<()> {
+    let compression = compression.or_else(|| Compression::from_path(file));
+    let (output, encoder) = create_compressed_output(file, compression)?;
+    let mut writer = writer(output)?;
+    for object in objects {
+        writer.write_record(object)?;
+    }
+    writer.finish()?;
+    match encoder {
+        Some(encoder) => encoder.finish(),
+        None => Ok(()),
+    }
+}
+
+/// Reads a whole file, or standard input when the path is `-`.
+pub fn read_input(file: &str) -> io::Result<String> {
+    match file {
+        STDIO_PATH => io::read_to_string(io::stdin()),
+        _ => fs::read_to_string(file),
+    }
 }
 
 /// Dumps a vector of JSON values to a file.
@@ -54,9 +300,45 @@ pub fn dump_json(object: &impl Serialize, file: &str) -> io::Result<()> {
 /// let result = dump_json_array(&json_objects, "output.json");
 /// assert!(result.is_ok());
 /// ```
-pub fn dump_json_array(objects: &Vec<Value>, file: &str) -> io::Result<()> {
-    let json_string = serde_json::to_string(&objects).unwrap();
-    fs::write(file, json_string)
+pub fn dump_json_array(objects: &[Value], file: &str) -> io::Result<()> {
+    dump_json_array_with_style(objects, file, &JsonStyle::default())
+}
+
+/// Dumps a vector of JSON values to a file in the given style.
+pub fn dump_json_array_with_style(
+    objects: &[Value],
+    file: &str,
+    style: &JsonStyle,
+) -> io::Result<()> {
+    dump_json_array_compressed(objects, file, style, None)
+}
+
+/// Dumps a vector of JSON values to a file in the given style,
+/// compressed with the compression given or else the one named by the file's extension.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::compress_utils::Compression;
+/// use pollinate::json_utils::*;
+///
+/// let json_objects = vec![serde_json::json!({"key": "value"})];
+///
+/// let result = dump_json_array_compressed(&json_objects, "output.json.zst", &JsonStyle::default(), None);
+/// assert!(result.is_ok());
+/// let result = dump_json_array_compressed(&json_objects, "-", &JsonStyle::default(), Some(Compression::Gzip));
+/// assert!(result.is_ok());
+/// # std::fs::remove_file("output.json.zst").unwrap();
+/// ```
+pub fn dump_json_array_compressed(
+    objects: &[Value],
+    file: &str,
+    style: &JsonStyle,
+    compression: Option<Compression>,
+) -> io::Result<()> {
+    dump_records(objects, file, compression, |writer| {
+        Ok(JsonWriter::new(writer).with_style(*style))
+    })
 }
 
 /// Dumps a single JSON value to a file.
@@ -74,8 +356,55 @@ pub fn dump_json_array(objects: &Vec<Value>, file: &str) -> io::Result<()> {
 /// assert!(result.is_ok());
 /// ```
 pub fn dump_value(value: Value, file: &str) -> io::Result<()> {
-    let json_string = serde_json::to_string(&value).unwrap();
-    fs::write(file, json_string)
+    dump_value_with_style(value, file, &JsonStyle::default())
+}
+
+/// Dumps a single JSON value to a file in the given style.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+///
+/// let json_value = serde_json::json!({"key": "value"});
+///
+/// let result = dump_value_with_style(json_value, "pretty.json", &JsonStyle::pretty(4));
+/// assert!(result.is_ok());
+/// assert_eq!(std::fs::read_to_string("pretty.json").unwrap(), "{\n    \"key\": \"value\"\n}");
+/// # std::fs::remove_file("pretty.json").unwrap();
+/// ```
+pub fn dump_value_with_style(value: Value, file: &str, style: &JsonStyle) -> io::Result<()> {
+    dump_value_compressed(value, file, style, None)
+}
+
+/// Dumps a single JSON value to a file in the given style,
+/// compressed with the compression given or else the one named by the file's extension.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+///
+/// let json_value = serde_json::json!({"key": "value"});
+///
+/// let result = dump_value_compressed(json_value, "value.json.gz", &JsonStyle::default(), None);
+/// assert!(result.is_ok());
+/// # std::fs::remove_file("value.json.gz").unwrap();
+/// ```
+pub fn dump_value_compressed(
+    value: Value,
+    file: &str,
+    style: &JsonStyle,
+    compression: Option<Compression>,
+) -> io::Result<()> {
+    let compression = compression.or_else(|| Compression::from_path(file));
+    let (mut writer, encoder) = create_compressed_output(file, compression)?;
+    write_json(&mut writer, &value, style)?;
+    writer.flush()?;
+    match encoder {
+        Some(encoder) => encoder.finish(),
+        None => Ok(()),
+    }
 }
 
 /// Takes a schema represented as a hashmap and generates a JSON object.
@@ -104,7 +433,10 @@ pub fn dump_value(value: Value, file: &str) -> io::Result<()> {
 /// ```
 pub fn create_json_from_schema<'a>(schema: &HashMap<String, Box<dyn Values + 'a>>) -> Value {
     let mut val = json!({});
-    for key in schema.keys() {
+    // fields are generated in a fixed order so that a seeded generator gives the same records every run
+    let mut keys: Vec<&String> = schema.keys().collect();
+    keys.sort();
+    for key in keys {
         val[key] = schema.get(key).unwrap().get_value();
     }
     val
@@ -131,19 +463,680 @@ pub fn create_json_from_schema<'a>(schema: &HashMap<String, Box<dyn Values + 'a>
 /// let mut schema = HashMap::new();
 /// schema.insert("key".to_string(), Box::new(MyValue) as Box<dyn Values>);
 ///
-/// let result = create_json_vec_from_schema(&schema, 3);
+/// let result = create_json_vec_from_schema(&schema, 3).unwrap();
 /// assert_eq!(result, vec![json!({"key": "some_value"}); 3]);
 /// ```
+///
+/// Fails with an `InvalidInput` error before generating anything if a field marked unique,
+/// or a sequence, cannot produce `number_of_objects` distinct values.
 pub fn create_json_vec_from_schema<'a>(
     schema: &HashMap<String, Box<dyn Values + 'a>>,
     number_of_objects: u32,
-) -> Vec<Value> {
+) -> io::Result<Vec<Value>> {
+    check_unique_capacity(schema, number_of_objects)?;
     let capacity = usize::try_from(number_of_objects).unwrap();
     let mut json_objects = Vec::<Value>::with_capacity(capacity);
     for _ in 0..capacity {
         json_objects.push(create_json_from_schema(schema))
     }
-    json_objects
+    Ok(json_objects)
+}
+
+/// Fails with an `InvalidInput` error if any unique field or sequence in the schema,
+/// including those nested in objects and arrays, has fewer values left than requested.
+/// Call it before writing anything, `stream_json_from_schema` checks it again before generating.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::check_unique_capacity;
+/// use pollinate::schema::populate_schema;
+/// use serde_json::json;
+///
+/// let properties = json!({"size": {"type": "string", "enum": ["S", "M", "L"], "x-unique": true}});
+/// let schema = populate_schema(properties.as_object().unwrap()).unwrap();
+/// assert!(check_unique_capacity(&schema, 3).is_ok());
+/// assert!(check_unique_capacity(&schema, 4).is_err());
+/// ```
+pub fn check_unique_capacity<'a>(
+    schema: &HashMap<String, Box<dyn Values + 'a>>,
+    count: u32,
+) -> io::Result<()> {
+    for (key, values) in schema.iter() {
+        if let Some(capacity) = values.unique_capacity() {
+            if capacity < count as u64 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Field {} never repeats a value but only has {} possible values left, {} were requested",
+                        key, capacity, count
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Formats generated records can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A JSON array, or a single object when only one record is written
+    Json,
+    /// One JSON record per line
+    Ndjson,
+    /// Comma separated values with nested objects flattened into dotted columns
+    Csv,
+    /// Tab separated values with nested objects flattened into dotted columns
+    Tsv,
+    /// SQL script creating a table and inserting every record
+    Sql,
+    /// SQLite database file with child tables for nested objects
+    Sqlite,
+    /// A stream of YAML documents, one per record
+    Yaml,
+    /// A TOML array of tables, or a single table when only one record is written
+    Toml,
+    /// MessagePack, as a length-delimited stream unless only one record is written
+    Msgpack,
+    /// CBOR, as a length-delimited stream unless only one record is written
+    Cbor,
+    /// BSON documents written back to back
+    Bson,
+    /// An Avro object container file with the schema embedded
+    Avro,
+    /// A Parquet file with columns derived from the schema
+    Parquet,
+    /// XML with an element for every record, shaped by the schema's x-xml hints
+    Xml,
+}
+
+impl OutputFormat {
+    /// Every output format, in the order they are listed in help.
+    pub const ALL: &'static [OutputFormat] = &[
+        OutputFormat::Json,
+        OutputFormat::Ndjson,
+        OutputFormat::Csv,
+        OutputFormat::Tsv,
+        OutputFormat::Sql,
+        OutputFormat::Sqlite,
+        OutputFormat::Yaml,
+        OutputFormat::Toml,
+        OutputFormat::Msgpack,
+        OutputFormat::Cbor,
+        OutputFormat::Bson,
+        OutputFormat::Avro,
+        OutputFormat::Parquet,
+        OutputFormat::Xml,
+    ];
+
+    /// Returns the name the output format is parsed from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pollinate::json_utils::OutputFormat;
+    ///
+    /// assert_eq!("ndjson".parse::<OutputFormat>(), Ok(OutputFormat::Ndjson));
+    /// assert_eq!(OutputFormat::Ndjson.name(), "ndjson");
+    /// assert!("jsonl".parse::<OutputFormat>().is_err());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Sql => "sql",
+            OutputFormat::Sqlite => "sqlite",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Toml => "toml",
+            OutputFormat::Msgpack => "msgpack",
+            OutputFormat::Cbor => "cbor",
+            OutputFormat::Bson => "bson",
+            OutputFormat::Avro => "avro",
+            OutputFormat::Parquet => "parquet",
+            OutputFormat::Xml => "xml",
+        }
+    }
+
+    /// Infers the format from the extension of a file, e.g. `.ndjson` or `.jsonl`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pollinate::json_utils::OutputFormat;
+    ///
+    /// assert_eq!(OutputFormat::from_path("out.jsonl"), Some(OutputFormat::Ndjson));
+    /// assert_eq!(OutputFormat::from_path("out.json"), Some(OutputFormat::Json));
+    /// assert_eq!(OutputFormat::from_path("out"), None);
+    /// ```
+    pub fn from_path(file: &str) -> Option<Self> {
+        let extension = std::path::Path::new(strip_extension(file))
+            .extension()?
+            .to_str()?;
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Some(OutputFormat::Json),
+            "ndjson" | "jsonl" => Some(OutputFormat::Ndjson),
+            "csv" => Some(OutputFormat::Csv),
+            "tsv" => Some(OutputFormat::Tsv),
+            "sql" => Some(OutputFormat::Sql),
+            "db" | "sqlite" | "sqlite3" => Some(OutputFormat::Sqlite),
+            "yaml" | "yml" => Some(OutputFormat::Yaml),
+            "toml" => Some(OutputFormat::Toml),
+            "msgpack" | "mpk" => Some(OutputFormat::Msgpack),
+            "avro" => Some(OutputFormat::Avro),
+            "parquet" => Some(OutputFormat::Parquet),
+            "cbor" => Some(OutputFormat::Cbor),
+            "bson" => Some(OutputFormat::Bson),
+            "xml" => Some(OutputFormat::Xml),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        OutputFormat::ALL
+            .iter()
+            .find(|x| x.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = OutputFormat::ALL.iter().map(|x| x.name()).collect();
+                format!(
+                    "Unknown output format {}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Writes records one at a time so they never all need to be held in memory.
+pub trait RecordWriter {
+    /// Writes a single record.
+    fn write_record(&mut self, record: &Value) -> io::Result<()>;
+
+    /// Writes anything that comes after the last record and flushes the output.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// Writes records as a JSON array, `[` and `]` are written around the records as they stream out.
+/// In single mode exactly one record is written without the surrounding array.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+/// use serde_json::json;
+///
+/// let mut output = Vec::new();
+/// let mut writer = JsonWriter::new(&mut output);
+/// writer.write_record(&json!({"a": 1})).unwrap();
+/// writer.write_record(&json!({"a": 2})).unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), r#"[{"a":1},{"a":2}]"#);
+/// ```
+pub struct JsonWriter<W: Write> {
+    writer: W,
+    single: bool,
+    written: u64,
+    style: JsonStyle,
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        JsonWriter {
+            writer,
+            single: false,
+            written: 0,
+            style: JsonStyle::default(),
+        }
+    }
+
+    pub fn single(writer: W) -> Self {
+        JsonWriter {
+            writer,
+            single: true,
+            written: 0,
+            style: JsonStyle::default(),
+        }
+    }
+
+    /// Lays out the records in the given style, pretty arrays put each record on its own lines.
+    pub fn with_style(mut self, style: JsonStyle) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl<W: Write> RecordWriter for JsonWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        if self.single && self.written > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only one record can be written as a single JSON value",
+            ));
+        }
+        if self.single {
+            write_json(&mut self.writer, record, &self.style)?;
+        } else {
+            self.writer
+                .write_all(if self.written == 0 { b"[" } else { b"," })?;
+            write_indent(&mut self.writer, &self.style, 1)?;
+            write_styled(&mut self.writer, record, &self.style, 1)?;
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if !self.single {
+            if self.written == 0 {
+                self.writer.write_all(b"[")?;
+            } else {
+                write_indent(&mut self.writer, &self.style, 0)?;
+            }
+            self.writer.write_all(b"]")?;
+        }
+        if self.style.indent.is_some() {
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.flush()
+    }
+}
+
+/// Writes records as newline delimited JSON, one record per line.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+/// use serde_json::json;
+///
+/// let mut output = Vec::new();
+/// let mut writer = NdjsonWriter::new(&mut output);
+/// writer.write_record(&json!({"a": 1})).unwrap();
+/// writer.write_record(&json!({"a": 2})).unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "{\"a\":1}\n{\"a\":2}\n");
+/// ```
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+    canonical: bool,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        NdjsonWriter {
+            writer,
+            canonical: false,
+        }
+    }
+
+    /// Writes every line as canonical JSON, lines are never indented.
+    pub fn canonical(mut self) -> Self {
+        self.canonical = true;
+        self
+    }
+}
+
+impl<W: Write> RecordWriter for NdjsonWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        let style = JsonStyle {
+            indent: None,
+            canonical: self.canonical,
+        };
+        write_json(&mut self.writer, record, &style)?;
+        self.writer.write_all(b"\n")
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Writes records as a stream of YAML documents each starting with `---`.
+/// In single mode exactly one record is written as a plain document.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+/// use serde_json::json;
+///
+/// let mut output = Vec::new();
+/// let mut writer = YamlWriter::new(&mut output);
+/// writer.write_record(&json!({"a": 1})).unwrap();
+/// writer.write_record(&json!({"a": 2})).unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "---\na: 1\n---\na: 2\n");
+/// ```
+pub struct YamlWriter<W: Write> {
+    writer: W,
+    single: bool,
+    written: u64,
+}
+
+impl<W: Write> YamlWriter<W> {
+    pub fn new(writer: W) -> Self {
+        YamlWriter {
+            writer,
+            single: false,
+            written: 0,
+        }
+    }
+
+    pub fn single(writer: W) -> Self {
+        YamlWriter {
+            writer,
+            single: true,
+            written: 0,
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for YamlWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        if self.single && self.written > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only one record can be written as a single YAML document",
+            ));
+        }
+        if !self.single {
+            self.writer.write_all(b"---\n")?;
+        }
+        let yaml_string = serde_yaml::to_string(record).map_err(io::Error::other)?;
+        self.writer.write_all(yaml_string.as_bytes())?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Removes nulls from objects and arrays, as TOML has no way to represent them.
+fn without_nulls(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(_, x)| !x.is_null())
+                .map(|(key, x)| (key.clone(), without_nulls(x)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .filter(|x| !x.is_null())
+                .map(without_nulls)
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// Writes records as a TOML array of tables named `records`.
+/// In single mode exactly one record is written as the top level table.
+/// Nulls are left out since TOML cannot represent them.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+/// use serde_json::json;
+///
+/// let mut output = Vec::new();
+/// let mut writer = TomlWriter::new(&mut output);
+/// writer.write_record(&json!({"a": 1, "b": null})).unwrap();
+/// writer.write_record(&json!({"a": 2})).unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "[[records]]\na = 1\n\n[[records]]\na = 2\n");
+/// ```
+pub struct TomlWriter<W: Write> {
+    writer: W,
+    single: bool,
+    written: u64,
+}
+
+impl<W: Write> TomlWriter<W> {
+    pub fn new(writer: W) -> Self {
+        TomlWriter {
+            writer,
+            single: false,
+            written: 0,
+        }
+    }
+
+    pub fn single(writer: W) -> Self {
+        TomlWriter {
+            writer,
+            single: true,
+            written: 0,
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for TomlWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        if self.single && self.written > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only one record can be written as a single TOML table",
+            ));
+        }
+        let record = without_nulls(record);
+        let toml_string = if self.single {
+            toml::to_string(&record)
+        } else {
+            toml::to_string(&json!({ "records": [record] }))
+        }
+        .map_err(io::Error::other)?;
+        if self.written > 0 {
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.write_all(toml_string.as_bytes())?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Dumps a vector of JSON values to a file as a stream of YAML documents.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+///
+/// let json_objects = vec![serde_json::json!({"key1": "value1"})];
+///
+/// let result = dump_yaml(&json_objects, "output.yaml");
+/// assert!(result.is_ok());
+/// # std::fs::remove_file("output.yaml").unwrap();
+/// ```
+pub fn dump_yaml(objects: &[Value], file: &str) -> io::Result<()> {
+    dump_records(objects, file, None, |writer| Ok(YamlWriter::new(writer)))
+}
+
+/// Dumps a vector of JSON values to a file as a TOML array of tables.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::*;
+///
+/// let json_objects = vec![serde_json::json!({"key1": "value1"})];
+///
+/// let result = dump_toml(&json_objects, "output.toml");
+/// assert!(result.is_ok());
+/// # std::fs::remove_file("output.toml").unwrap();
+/// ```
+pub fn dump_toml(objects: &[Value], file: &str) -> io::Result<()> {
+    dump_records(objects, file, None, |writer| Ok(TomlWriter::new(writer)))
+}
+
+/// Settings shared by every record writer.
+#[derive(Debug, Clone)]
+pub struct WriterOptions {
+    pub format: OutputFormat,
+    /// Joins arrays in CSV and TSV fields with this delimiter instead of writing them as JSON
+    pub array_delimiter: Option<String>,
+    /// Database SQL output is written for
+    pub sql_dialect: SqlDialect,
+    /// Whether SQL output stores nested values in JSON columns or child tables
+    pub sql_nesting: SqlNesting,
+    /// Number of rows in each row group of Parquet output
+    pub row_group_size: usize,
+    /// Layout of JSON and NDJSON output
+    pub json_style: JsonStyle,
+    /// Compresses the output as it is written
+    pub compression: Option<Compression>,
+    /// Renders every record through this template instead of writing the format
+    pub template: Option<TextTemplate>,
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        WriterOptions {
+            format: OutputFormat::Json,
+            array_delimiter: None,
+            sql_dialect: SqlDialect::Postgres,
+            sql_nesting: SqlNesting::Json,
+            row_group_size: DEFAULT_ROW_GROUP_SIZE,
+            json_style: JsonStyle::default(),
+            compression: None,
+            template: None,
+        }
+    }
+}
+
+/// Creates a buffered writer for a file in the format given by the options.
+/// A file of `-` writes to standard output, which every format other than SQLite supports.
+/// With a compression set the output is compressed as the records are written.
+/// With a template set every record is rendered through it, whatever the format.
+/// The schema is used by formats that need to know every column upfront.
+/// Output holding a single record is written as one document rather than a list or stream.
+pub fn create_record_writer(
+    file: &str,
+    schema: &Value,
+    count: u32,
+    options: &WriterOptions,
+) -> io::Result<Box<dyn RecordWriter>> {
+    if options.format == OutputFormat::Sqlite {
+        if file == STDIO_PATH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "SQLite output can only be written to a file",
+            ));
+        }
+        if options.compression.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "SQLite output can't be compressed",
+            ));
+        }
+        let name = table_name_from_schema(schema);
+        let tables = tables_from_schema(schema, &name, SqlNesting::Tables);
+        return Ok(Box::new(SqliteWriter::open(file, tables)?));
+    }
+    let (writer, encoder) = create_compressed_output(file, options.compression)?;
+    let array_delimiter = options.array_delimiter.clone();
+    let record_writer: Box<dyn RecordWriter> = if let Some(template) = &options.template {
+        Box::new(TemplateWriter::new(writer, template.clone(), count))
+    } else {
+        match options.format {
+            OutputFormat::Json if count == 1 => {
+                Box::new(JsonWriter::single(writer).with_style(options.json_style))
+            }
+            OutputFormat::Json => Box::new(JsonWriter::new(writer).with_style(options.json_style)),
+            OutputFormat::Ndjson if options.json_style.canonical => {
+                Box::new(NdjsonWriter::new(writer).canonical())
+            }
+            OutputFormat::Ndjson => Box::new(NdjsonWriter::new(writer)),
+            OutputFormat::Csv => {
+                Box::new(CsvWriter::from_schema(writer, schema, ',', array_delimiter))
+            }
+            OutputFormat::Tsv => Box::new(CsvWriter::from_schema(
+                writer,
+                schema,
+                '\t',
+                array_delimiter,
+            )),
+            OutputFormat::Sql => {
+                let name = table_name_from_schema(schema);
+                let tables = tables_from_schema(schema, &name, options.sql_nesting);
+                Box::new(SqlWriter::new(writer, tables, options.sql_dialect))
+            }
+            OutputFormat::Yaml if count == 1 => Box::new(YamlWriter::single(writer)),
+            OutputFormat::Yaml => Box::new(YamlWriter::new(writer)),
+            OutputFormat::Toml if count == 1 => Box::new(TomlWriter::single(writer)),
+            OutputFormat::Toml => Box::new(TomlWriter::new(writer)),
+            OutputFormat::Xml if count == 1 => Box::new(XmlWriter::single(writer, schema)),
+            OutputFormat::Xml => Box::new(XmlWriter::new(writer, schema)),
+            OutputFormat::Parquet => {
+                Box::new(ParquetWriter::new(writer, schema, options.row_group_size)?)
+            }
+            OutputFormat::Avro => {
+                Box::new(AvroWriter::new(writer, &avro_schema_from_schema(schema)?)?)
+            }
+            OutputFormat::Msgpack | OutputFormat::Cbor | OutputFormat::Bson => {
+                let format = match options.format {
+                    OutputFormat::Msgpack => BinaryFormat::MessagePack,
+                    OutputFormat::Cbor => BinaryFormat::Cbor,
+                    _ => BinaryFormat::Bson,
+                };
+                if count == 1 {
+                    Box::new(BinaryWriter::single(writer, format))
+                } else {
+                    Box::new(BinaryWriter::new(writer, format))
+                }
+            }
+            OutputFormat::Sqlite => unreachable!(),
+        }
+    };
+    Ok(match encoder {
+        Some(encoder) => Box::new(CompressedWriter::new(record_writer, encoder)),
+        None => record_writer,
+    })
+}
+
+/// Generates records from a schema and writes each one as soon as it is created,
+/// so memory use stays constant regardless of the number of records.
+/// Fails before writing anything if a field marked unique cannot produce that many distinct values.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::*;
+/// use pollinate::json_utils::*;
+/// use serde_json::json;
+/// use std::collections::HashMap;
+///
+/// let mut schema = HashMap::new();
+/// schema.insert("id".to_string(), Box::new(SequenceValues::new(1, 1, None, SequenceScope::Parent)) as Box<dyn Values>);
+///
+/// let mut output = Vec::new();
+/// stream_json_from_schema(&schema, 3, &mut NdjsonWriter::new(&mut output)).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n");
+/// ```
+pub fn stream_json_from_schema<'a>(
+    schema: &HashMap<String, Box<dyn Values + 'a>>,
+    number_of_objects: u32,
+    writer: &mut dyn RecordWriter,
+) -> io::Result<()> {
+    check_unique_capacity(schema, number_of_objects)?;
+    for _ in 0..number_of_objects {
+        writer.write_record(&create_json_from_schema(schema))?;
+    }
+    writer.finish()
 }
 
 //pub fn generate_json(object: &impl Serialize) -> String {}
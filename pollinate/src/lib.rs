@@ -7,9 +7,38 @@
 //! - **Schema Parsing:** The crate supports parsing JSON schema details, including handling integer, string, array, and object types.
 //! - **Random Data Generation:** Using the parsed schema, `pollinate` can generate templates containing random values for each specified field.
 //! - **Extensibility:** Custom value types can be easily added by implementing the `Values` trait.
+//! - **Output Formats:** Records can be streamed out as JSON, NDJSON, CSV, TSV, YAML, TOML, MessagePack, CBOR, BSON, Avro, Parquet, XML, SQL scripts or directly into SQLite, optionally compressed with gzip, zstd or bzip2.
+//! - **Templates:** Records can be rendered through a Handlebars template into any textual format.
+//! - **Anonymization:** PII in existing JSON documents can be swapped out for generated values.
+//! - **Schema Inference:** A schema can be inferred from example JSON documents.
+//! - **Datasets:** Several related schemas can be generated together, with foreign keys between them.
+//! - **Validation:** Records can be checked against the schema they were generated from, or any JSON file against a schema.
+//! - **Edge Cases:** Generators can prefer boundary values, such as range bounds and empty or full arrays, for a share of values.
+//! - **Coverage:** Records can cover every combination, or every pair, of a schema's enum, boolean and boundary values.
+//! - **Invalid Records:** Records breaking exactly one constraint can be generated for negative testing.
+//! - **Iterators:** A `Template` generates records lazily through a seedable iterator, or a `futures::Stream` with the `async` feature.
+//! - **Typed Generation:** Records can be deserialized straight into Rust types, whose schema can be derived with `schemars`.
 //!
 //!
 #![allow(dead_code)]
+pub mod anonymize;
+pub mod avro_utils;
+pub mod binary_utils;
+pub mod compress_utils;
+pub mod coverage;
+pub mod csv_utils;
+pub mod dataset;
 pub mod default_values;
+pub mod infer;
+pub mod invalid;
 pub mod json_utils;
+pub mod parquet_utils;
 pub mod schema;
+pub mod split_utils;
+pub mod sql_utils;
+pub mod template_utils;
+pub mod typed;
+pub mod validate;
+pub mod xml_utils;
+
+pub use typed::generate;
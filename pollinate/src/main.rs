@@ -10,47 +10,485 @@
 //! - **Random Data Generation:** Using the parsed schema, `pollinate` can generate templates containing random values for each specified field.
 //! - **Extensibility:** Custom value types can be easily added by implementing the `Values` trait.
 //!
-//! Usage: pollinate [OPTIONS] --input-schema <INPUT_SCHEMA> --output-schema <OUTPUT>
+//! Usage: pollinate [OPTIONS] --input-schema <INPUT_SCHEMA>
+//!        pollinate dataset <MANIFEST>
+//!        pollinate infer [--output <OUTPUT>] <SAMPLES>...
+//!        pollinate anonymize [--output <OUTPUT>] <--schema <SCHEMA>|--rules <RULES>> <INPUT>
+//!        pollinate validate --schema <SCHEMA> <DATA>
+//! Commands:
+//! dataset    Generate several related tables from a manifest
+//! infer      Infer a schema from example JSON or NDJSON files
+//! anonymize  Replace values of an existing JSON file with generated ones
+//! validate   Check every record of a JSON or NDJSON file against a schema
 //! Options:
-//! -i, --input-schema <INPUT_SCHEMA>    Path to input schema
-//! -o, --output <OUTPUT>  Path to output schema
+//! -i, --input-schema <INPUT_SCHEMA>    Path to input schema, or an Avro schema ending in .avsc, - for stdin
+//! -o, --output <OUTPUT>                Path to output file, - for stdout [default: -]
 //! -c, --count <COUNT>                  Number of objects to create [default: 1]
+//! -f, --format <FORMAT>                Output format, e.g. json, ndjson, csv, sql, yaml, msgpack, xml [default: json]
+//!     --array-delimiter <DELIMITER>    Join arrays in CSV and TSV output instead of writing JSON
+//!     --sql-dialect <DIALECT>          postgres, mysql or sqlite [default: postgres]
+//!     --sql-nesting <NESTING>          Store nested objects as json columns or child tables [default: json]
+//!     --row-group-size <ROWS>          Rows in each row group of Parquet output [default: 10000]
+//!     --pretty [<INDENT>]              Indent JSON output [default: 2]
+//!     --canonical                      Write canonical JSON, sorted keys and normalised numbers
+//!     --compress <COMPRESSION>         gzip, zstd or bzip2, inferred from a .gz, .zst or .bz2 output
+//!     --template <TEMPLATE>            Render each record through a Handlebars template instead
+//!     --split <N>                      Write N records to each file
+//!     --output-template <TEMPLATE>     Name split files, e.g. out/{index:05}-{field:id}.json
+//!     --manifest <MANIFEST>            Write a list of every split file to this path
+//!     --coverage <COVERAGE>            Write the records covering every exhaustive combination or pairwise pair of values
+//!     --edge-cases [<RATIO>]           Prefer boundary values for this share of values [default: 0.5]
+//!     --validate                       Check every record against the schema before writing it
+//!     --invalid                        Break one constraint of every record, annotated with the rule broken
+//!     --violations <VIOLATIONS>        Write what each --invalid record breaks to this path instead
 //! -h, --help                           Print help
 //! -V, --version                        Print version
 
-use clap::Parser;
+use clap::builder::{PossibleValue, PossibleValuesParser, TypedValueParser};
+use clap::{ArgGroup, Parser, Subcommand};
 use pollinate::{
+    anonymize::{anonymize, anonymize_with_schema, read_rules},
+    compress_utils::Compression,
+    coverage::{Coverage, CoveragePlan, CoverageWriter},
+    dataset::{dump_dataset, generate_dataset, read_manifest},
+    infer::{infer_schema, read_samples},
+    invalid::{annotated_schema, InvalidWriter},
     json_utils::{
-        create_json_from_schema, create_json_vec_from_schema, dump_json_array, dump_value,
+        check_unique_capacity, create_output, create_record_writer, dump_value_compressed,
+        read_input, stream_json_from_schema, JsonStyle, OutputFormat, RecordWriter, WriterOptions,
+        STDIO_PATH,
     },
+    parquet_utils::DEFAULT_ROW_GROUP_SIZE,
     schema::*,
+    split_utils::{default_template, OutputTemplate, SplitWriter},
+    sql_utils::{SqlDialect, SqlNesting},
+    template_utils::read_text_template,
+    validate::{ValidatingWriter, Validator},
 };
+use std::error::Error;
+use std::io;
+use std::num::NonZeroU32;
+use std::process;
+use std::str::FromStr;
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true)]
+#[command(group(ArgGroup::new("splitting").args(["split", "output_template"]).multiple(true)))]
 struct Args {
-    /// Path to input schema
-    #[arg(short, long)]
-    input_schema: String,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    /// Path to output schema
-    #[arg(short, long)]
+    /// Path to input schema, or an Avro schema ending in .avsc, - reads the schema from stdin
+    #[arg(short, long, required = true)]
+    input_schema: Option<String>,
+
+    /// Path to output file, - writes to stdout
+    #[arg(short, long, default_value = STDIO_PATH)]
     output: String,
 
     /// Number of objects to create
     #[arg(short, long, default_value_t = 1)]
     count: u32,
+
+    /// Output format, inferred from the output extension when not given [default: json]
+    #[arg(short, long, value_parser = choices(OutputFormat::ALL, OutputFormat::name, format_help))]
+    format: Option<OutputFormat>,
+
+    /// Join arrays in CSV and TSV output with this delimiter instead of writing them as JSON
+    #[arg(long)]
+    array_delimiter: Option<String>,
+
+    /// Database SQL output is written for
+    #[arg(long, default_value = "postgres", value_parser = choices(SqlDialect::ALL, SqlDialect::name, |_| ""))]
+    sql_dialect: SqlDialect,
+
+    /// Store nested objects in SQL output as JSON columns or child tables
+    #[arg(long, default_value = "json", value_parser = choices(SqlNesting::ALL, SqlNesting::name, nesting_help))]
+    sql_nesting: SqlNesting,
+
+    /// Number of rows in each row group of Parquet output
+    #[arg(long, default_value_t = DEFAULT_ROW_GROUP_SIZE, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    row_group_size: usize,
+
+    /// Indent JSON output by this many spaces [default: 2 when given without a value]
+    #[arg(long, global = true, num_args = 0..=1, default_missing_value = "2", value_name = "INDENT")]
+    pretty: Option<usize>,
+
+    /// Write canonical JSON with sorted keys and normalised numbers (RFC 8785)
+    #[arg(long, global = true)]
+    canonical: bool,
+
+    /// Compress the output as it is written, inferred from a .gz, .zst or .bz2 output extension
+    #[arg(long, global = true, value_parser = choices(Compression::ALL, Compression::name, compression_help))]
+    compress: Option<Compression>,
+
+    /// Render each record through a Handlebars template with header, record and footer sections instead of a format
+    #[arg(long)]
+    template: Option<String>,
+
+    /// Write this many records to each file, named by --output-template or by the output path and file index
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), value_name = "N")]
+    split: Option<u32>,
+
+    /// Path of each split file with {index}, {index:05}, {uuid} and {field:path} placeholders [default split: 1]
+    #[arg(long, value_name = "TEMPLATE")]
+    output_template: Option<String>,
+
+    /// Write a JSON list of every split file and its number of records to this path
+    #[arg(long, requires = "splitting")]
+    manifest: Option<String>,
+
+    /// Write the records covering every combination, or every pair, of enum, boolean and range bound values instead of --count
+    #[arg(long, conflicts_with = "count", value_parser = choices(Coverage::ALL, Coverage::name, coverage_help))]
+    coverage: Option<Coverage>,
+
+    /// Prefer boundary values, such as bounds, empty and longest arrays and unicode strings, for this share of values [default: 0.5 when given without a value]
+    #[arg(long, num_args = 0..=1, default_missing_value = "0.5", value_name = "RATIO", value_parser = parse_ratio)]
+    edge_cases: Option<f64>,
+
+    /// Check every record against the schema before writing it, stopping at the first that doesn't match
+    #[arg(long)]
+    validate: bool,
+
+    /// Break one constraint of every record, written along with the rule and path broken
+    #[arg(long, conflicts_with = "validate")]
+    invalid: bool,
+
+    /// Write the records broken by --invalid as they are, and what each one breaks as NDJSON to this path
+    #[arg(long, requires = "invalid")]
+    violations: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate several related tables from a manifest
+    Dataset {
+        /// Path to the dataset manifest
+        manifest: String,
+    },
+    /// Infer a schema from example JSON or NDJSON files
+    Infer {
+        /// Paths to example documents, - reads them from stdin
+        #[arg(required = true)]
+        samples: Vec<String>,
+
+        /// Path to output schema, - writes to stdout
+        #[arg(short, long, default_value = STDIO_PATH)]
+        output: String,
+    },
+    /// Replace values of an existing JSON file with generated ones
+    Anonymize {
+        /// Path to the JSON file to anonymize, - reads it from stdin
+        input: String,
+
+        /// Path to output file, - writes to stdout
+        #[arg(short, long, default_value = STDIO_PATH)]
+        output: String,
+
+        /// Schema describing the values to replace
+        #[arg(long, required_unless_present = "rules", conflicts_with = "rules")]
+        schema: Option<String>,
+
+        /// File mapping JSONPaths to the schema of their replacement values
+        #[arg(long)]
+        rules: Option<String>,
+    },
+    /// Check every record of a JSON or NDJSON file against a schema
+    Validate {
+        /// Path to the records to check, - reads them from stdin
+        data: String,
+
+        /// Schema the records should match
+        #[arg(long)]
+        schema: String,
+    },
+}
+
+/// Parses an option into one of the values of a library enum, listing each with its help.
+fn choices<T>(
+    values: &[T],
+    name: fn(&T) -> &'static str,
+    help: fn(T) -> &'static str,
+) -> impl TypedValueParser<Value = T>
+where
+    T: Copy + FromStr<Err = String> + Send + Sync + 'static,
+{
+    let values = values.iter().map(|x| match help(*x) {
+        "" => PossibleValue::new(name(x)),
+        text => PossibleValue::new(name(x)).help(text),
+    });
+    PossibleValuesParser::new(values).try_map(|x| x.parse::<T>())
+}
+
+fn format_help(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Json => "A JSON array, or a single object when only one record is written",
+        OutputFormat::Ndjson => "One JSON record per line",
+        OutputFormat::Csv => {
+            "Comma separated values with nested objects flattened into dotted columns"
+        }
+        OutputFormat::Tsv => {
+            "Tab separated values with nested objects flattened into dotted columns"
+        }
+        OutputFormat::Sql => "SQL script creating a table and inserting every record",
+        OutputFormat::Sqlite => "SQLite database file with child tables for nested objects",
+        OutputFormat::Yaml => "A stream of YAML documents, one per record",
+        OutputFormat::Toml => {
+            "A TOML array of tables, or a single table when only one record is written"
+        }
+        OutputFormat::Msgpack => {
+            "MessagePack, as a length-delimited stream unless only one record is written"
+        }
+        OutputFormat::Cbor => {
+            "CBOR, as a length-delimited stream unless only one record is written"
+        }
+        OutputFormat::Bson => "BSON documents written back to back",
+        OutputFormat::Avro => "An Avro object container file with the schema embedded",
+        OutputFormat::Parquet => "A Parquet file with columns derived from the schema",
+        OutputFormat::Xml => {
+            "XML with an element for every record, shaped by the schema's x-xml hints"
+        }
+    }
+}
+
+fn nesting_help(nesting: SqlNesting) -> &'static str {
+    match nesting {
+        SqlNesting::Json => "In a JSON column of the parent table",
+        SqlNesting::Tables => {
+            "In child tables, arrays of anything other than objects are still stored as JSON"
+        }
+    }
+}
+
+fn compression_help(compression: Compression) -> &'static str {
+    match compression {
+        Compression::Gzip => "gzip, the extension is .gz",
+        Compression::Zstd => "Zstandard, the extension is .zst",
+        Compression::Bzip2 => "bzip2, the extension is .bz2",
+    }
+}
+
+fn coverage_help(coverage: Coverage) -> &'static str {
+    match coverage {
+        Coverage::Exhaustive => "Every combination of values",
+        Coverage::Pairwise => "Every pair of values of any two fields",
+    }
+}
+
+/// Parses a share of values between 0 and 1.
+fn parse_ratio(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(ratio) if (0.0..=1.0).contains(&ratio) => Ok(ratio),
+        _ => Err(format!("{} is not a number between 0 and 1", value)),
+    }
+}
+
+/// Whether an error was caused by stdout being closed, possibly wrapped by a format's own error type.
+fn is_broken_pipe(error: &io::Error) -> bool {
+    let mut source = error.get_ref().map(|x| x as &(dyn Error + 'static));
+    while let Some(inner) = source {
+        if let Some(inner) = inner.downcast_ref::<io::Error>() {
+            return is_broken_pipe(inner);
+        }
+        source = inner.source();
+    }
+    error.kind() == io::ErrorKind::BrokenPipe
+}
+
+/// Reports an error on stderr and exits, otherwise returns the result's value.
+/// A closed pipe, such as output piped into `head`, ends the program quietly instead.
+fn exit_on_error<T>(result: io::Result<T>) -> T {
+    match result {
+        Err(error) if is_broken_pipe(&error) => process::exit(0),
+        Err(error) => {
+            eprintln!("pollinate: {}", error);
+            process::exit(1);
+        }
+        Ok(value) => value,
+    }
 }
 
 fn main() {
     let args = Args::parse();
-    let count = args.count;
-    let schema = generate_template_from_schema(args.input_schema.as_str());
-    if count == 1 {
-        let json = create_json_from_schema(&schema);
-        _ = dump_value(json, &args.output);
-    } else {
-        let json = create_json_vec_from_schema(&schema, count);
-        _ = dump_json_array(&json, &args.output);
+    let json_style = JsonStyle {
+        indent: args.pretty,
+        canonical: args.canonical,
+    };
+    match args.command {
+        Some(Command::Dataset { manifest }) => {
+            let manifest = exit_on_error(read_manifest(&manifest));
+            let generated = exit_on_error(generate_dataset(&manifest));
+            exit_on_error(dump_dataset(
+                &manifest,
+                &generated,
+                &json_style,
+                args.compress,
+            ));
+            return;
+        }
+        Some(Command::Infer { samples, output }) => {
+            let samples: Vec<_> = samples
+                .iter()
+                .flat_map(|x| exit_on_error(read_samples(x)))
+                .collect();
+            let schema = exit_on_error(infer_schema(&samples));
+            exit_on_error(dump_value_compressed(
+                schema,
+                &output,
+                &json_style,
+                args.compress,
+            ));
+            return;
+        }
+        Some(Command::Anonymize {
+            input,
+            output,
+            schema,
+            rules,
+        }) => {
+            let input = exit_on_error(read_input(&input));
+            let mut document = exit_on_error(serde_json::from_str(&input).map_err(io::Error::from));
+            if let Some(schema) = schema {
+                let schema = exit_on_error(read_input(&schema));
+                let schema = exit_on_error(serde_json::from_str(&schema).map_err(io::Error::from));
+                exit_on_error(anonymize_with_schema(&mut document, &schema));
+            } else {
+                anonymize(&mut document, &exit_on_error(read_rules(&rules.unwrap())));
+            }
+            exit_on_error(dump_value_compressed(
+                document,
+                &output,
+                &json_style,
+                args.compress,
+            ));
+            return;
+        }
+        Some(Command::Validate { data, schema }) => {
+            validate_file(&data, &schema);
+            return;
+        }
+        None => (),
+    }
+    let output = args.output;
+    let template = match (&args.output_template, args.split) {
+        (Some(template), _) => Some(template.clone()),
+        (None, Some(_)) if output == STDIO_PATH => {
+            eprintln!("pollinate: Split output needs an --output path or an --output-template");
+            process::exit(1);
+        }
+        (None, Some(_)) => Some(default_template(&output)),
+        (None, None) => None,
+    };
+    let path = template.as_ref().unwrap_or(&output);
+    let format = args
+        .format
+        .or_else(|| OutputFormat::from_path(path))
+        .unwrap_or(OutputFormat::Json);
+    let compression = args.compress.or_else(|| Compression::from_path(path));
+    let options = WriterOptions {
+        format,
+        array_delimiter: args.array_delimiter,
+        sql_dialect: args.sql_dialect,
+        sql_nesting: args.sql_nesting,
+        row_group_size: args.row_group_size,
+        json_style,
+        compression,
+        template: exit_on_error(args.template.as_deref().map(read_text_template).transpose()),
+    };
+    let schema = exit_on_error(read_schema(&args.input_schema.unwrap()));
+    let plan = args
+        .coverage
+        .map(|coverage| exit_on_error(CoveragePlan::new(&schema, coverage)));
+    let count = match &plan {
+        Some(plan) => {
+            let count = exit_on_error(u32::try_from(plan.len()).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Coverage needs {} records, more than the {} that can be written",
+                        plan.len(),
+                        u32::MAX
+                    ),
+                )
+            }));
+            let covered = match args.coverage {
+                Some(Coverage::Exhaustive) => "every combination",
+                _ => "every pair",
+            };
+            eprintln!(
+                "pollinate: Writing {} records covering {} of values of {} fields",
+                plan.len(),
+                covered,
+                plan.factors().len()
+            );
+            count
+        }
+        None => args.count,
+    };
+    let values = exit_on_error(match args.edge_cases {
+        Some(ratio) => generate_template_from_value(&with_edge_cases(&schema, ratio)),
+        None => generate_template_from_value(&schema),
+    });
+    exit_on_error(check_unique_capacity(&values, count));
+    let writer_schema = match args.invalid && args.violations.is_none() {
+        true => annotated_schema(&schema),
+        false => schema.clone(),
+    };
+    let writer: io::Result<Box<dyn RecordWriter>> = match template {
+        Some(template) => OutputTemplate::parse(&template).map(|template| {
+            let split = args
+                .split
+                .and_then(NonZeroU32::new)
+                .unwrap_or(NonZeroU32::MIN);
+            let mut writer = SplitWriter::new(template, &writer_schema, count, split, options);
+            if let Some(manifest) = &args.manifest {
+                writer = writer.with_manifest(manifest);
+            }
+            Box::new(writer) as Box<dyn RecordWriter>
+        }),
+        None => create_record_writer(&output, &writer_schema, count, &options),
+    };
+    let mut writer = exit_on_error(writer);
+    if args.validate {
+        writer = Box::new(ValidatingWriter::new(writer, &schema));
+    }
+    if args.invalid {
+        writer = Box::new(match &args.violations {
+            Some(path) => {
+                let violations = Box::new(exit_on_error(create_output(path)));
+                InvalidWriter::with_violations(writer, &schema, violations)
+            }
+            None => InvalidWriter::new(writer, &schema),
+        });
+    }
+    if let Some(plan) = plan {
+        writer = Box::new(CoverageWriter::new(writer, plan));
+    }
+    exit_on_error(stream_json_from_schema(&values, count, writer.as_mut()));
+}
+
+/// Validates every record of a file against a schema, printing each error found.
+/// Exits with status 1 when any record doesn't match.
+fn validate_file(data: &str, schema: &str) {
+    let schema = exit_on_error(read_schema(schema));
+    let mut validator = Validator::new(&schema);
+    let records = exit_on_error(read_samples(data));
+    let mut invalid = 0;
+    for (i, record) in records.iter().enumerate() {
+        let errors = validator.validate(record);
+        if !errors.is_empty() {
+            invalid += 1;
+        }
+        for error in errors {
+            println!("record {}: {}", i + 1, error);
+        }
+    }
+    println!(
+        "{} of {} records are valid",
+        records.len() - invalid,
+        records.len()
+    );
+    if invalid > 0 {
+        process::exit(1);
     }
 }
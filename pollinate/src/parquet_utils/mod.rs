@@ -0,0 +1,258 @@
+//! # Pollinate Parquet Module
+//!
+//! The `parquet_utils` module writes generated records to Parquet files.
+//!
+//! ## Overview
+//!
+//! An Arrow schema is derived from the pollinate schema: nested objects become structs, arrays become lists,
+//! maps become maps and strings with the `date` or `date-time` format become dates and timestamps. A column is
+//! nullable when its type includes `null`, or when its object has a `required` list that does not name it.
+//! Records are buffered until a row group is full and then written, so memory use depends on the row group
+//! size rather than the number of records.
+//!
+//! ## Functions
+//!
+//! - `arrow_schema_from_schema`: Derives an Arrow schema from a pollinate schema.
+//! - `dump_parquet`: Dumps a vector of JSON values to a Parquet file.
+//!
+
+use arrow_json::reader::Decoder;
+use arrow_json::ReaderBuilder;
+use arrow_schema::{DataType, Field, Fields, Schema, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde_json::Value;
+use std::io;
+use std::io::Write;
+use std::sync::Arc;
+
+use crate::default_values::StringFormat;
+use crate::json_utils::{dump_records, RecordWriter};
+
+/// Number of rows in each row group when none is given.
+pub const DEFAULT_ROW_GROUP_SIZE: usize = 10_000;
+
+/// Error for a property of a pollinate schema that has no Arrow counterpart.
+fn unsupported(details: &Value) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("No Parquet type can be derived for {}", details),
+    )
+}
+
+/// Derives the Arrow type of a single schema type, ignoring whether it is nullable.
+fn data_type(details: &Value) -> io::Result<DataType> {
+    if let Some(sequence) = details.get("x-sequence") {
+        return Ok(match sequence.get("format") {
+            Some(_) => DataType::Utf8,
+            None => DataType::Int64,
+        });
+    }
+    if let Some(options) = details.get("oneOf") {
+        let options = options.as_array().ok_or_else(|| unsupported(details))?;
+        let mut shared = DataType::Null;
+        for option in options {
+            match data_type(option)? {
+                DataType::Null => {}
+                x if shared == DataType::Null => shared = x,
+                x if x == shared => {}
+                _ => return Err(unsupported(details)),
+            }
+        }
+        return Ok(shared);
+    }
+    let type_name = match details.get("type").ok_or_else(|| unsupported(details))? {
+        Value::Array(types) => types.iter().find(|x| x.as_str() != Some("null")),
+        x => Some(x),
+    };
+    Ok(match type_name.and_then(|x| x.as_str()) {
+        None | Some("null") => DataType::Null,
+        Some("integer") => DataType::Int64,
+        Some("number") => DataType::Float64,
+        Some("boolean") => DataType::Boolean,
+        Some("string") => {
+            let format = details.get("format").and_then(|x| x.as_str());
+            match format.and_then(StringFormat::from_name) {
+                Some(StringFormat::Date) => DataType::Date32,
+                Some(StringFormat::DateTime) => {
+                    DataType::Timestamp(TimeUnit::Millisecond, Some("+00:00".into()))
+                }
+                _ => DataType::Utf8,
+            }
+        }
+        Some("array") => {
+            let items = details.get("items").ok_or_else(|| unsupported(details))?;
+            DataType::List(Arc::new(field("item", items, true)?))
+        }
+        Some("object") => match details.get("properties") {
+            Some(_) => DataType::Struct(struct_fields(details)?),
+            None => {
+                let values = details
+                    .get("additionalProperties")
+                    .ok_or_else(|| unsupported(details))?;
+                let value = field("value", values, true)?;
+                let entries = Fields::from(vec![Field::new("key", DataType::Utf8, false), value]);
+                let entries = Field::new("entries", DataType::Struct(entries), false);
+                DataType::Map(Arc::new(entries), false)
+            }
+        },
+        Some(_) => return Err(unsupported(details)),
+    })
+}
+
+/// Derives a field, nullable when the type includes null or the field is optional.
+fn field(name: &str, details: &Value, optional: bool) -> io::Result<Field> {
+    let nullable = match details.get("type") {
+        Some(Value::Array(types)) => types.contains(&Value::from("null")),
+        Some(x) => x == "null",
+        None => details.get("oneOf").is_some(),
+    };
+    Ok(Field::new(name, data_type(details)?, optional || nullable))
+}
+
+/// Derives a field for every property of an object.
+fn struct_fields(details: &Value) -> io::Result<Fields> {
+    let required = details.get("required").and_then(|x| x.as_array());
+    let properties = details
+        .get("properties")
+        .and_then(|x| x.as_object())
+        .ok_or_else(|| unsupported(details))?;
+    properties
+        .iter()
+        .map(|(property, details)| {
+            let optional = required.is_some_and(|x| !x.contains(&Value::from(property.as_str())));
+            field(property, details, optional)
+        })
+        .collect()
+}
+
+/// Derives an Arrow schema from a pollinate schema.
+/// A `oneOf` becomes the type its options share, and fails with an `InvalidInput` error when they don't share one,
+/// as does a property without a type.
+///
+/// # Examples
+///
+/// ```
+/// use arrow_schema::DataType;
+/// use pollinate::parquet_utils::arrow_schema_from_schema;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "required": ["age"],
+///     "properties": {
+///         "age": {"type": "integer", "minimum": 0, "maximum": 99},
+///         "email": {"type": ["string", "null"], "format": "email"},
+///         "skills": {"type": "array", "maximum": 2, "items": {"type": "string", "enum": ["Java"]}}
+///     }
+/// });
+/// let arrow = arrow_schema_from_schema(&schema).unwrap();
+/// assert_eq!(arrow.field_with_name("age").unwrap().data_type(), &DataType::Int64);
+/// assert!(!arrow.field_with_name("age").unwrap().is_nullable());
+/// assert!(arrow.field_with_name("email").unwrap().is_nullable());
+/// assert!(matches!(arrow.field_with_name("skills").unwrap().data_type(), DataType::List(_)));
+/// ```
+pub fn arrow_schema_from_schema(schema: &Value) -> io::Result<Schema> {
+    Ok(Schema::new(struct_fields(schema)?))
+}
+
+/// Writes records to a Parquet file, one row group for every `row_group_size` records, which must be at least 1.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::RecordWriter;
+/// use pollinate::parquet_utils::ParquetWriter;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {"x": {"type": "integer", "minimum": 0, "maximum": 9}}});
+/// let mut output = Vec::new();
+/// let mut writer = ParquetWriter::new(&mut output, &schema, 2).unwrap();
+/// for x in 0..5 {
+///     writer.write_record(&json!({"x": x})).unwrap();
+/// }
+/// writer.finish().unwrap();
+/// drop(writer);
+/// assert_eq!(&output[..4], b"PAR1");
+/// ```
+pub struct ParquetWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    decoder: Decoder,
+    row_group_size: usize,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    pub fn new(writer: W, schema: &Value, row_group_size: usize) -> io::Result<Self> {
+        if row_group_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Row groups must hold at least one row",
+            ));
+        }
+        let arrow_schema = Arc::new(arrow_schema_from_schema(schema)?);
+        let decoder = ReaderBuilder::new(arrow_schema.clone())
+            .with_batch_size(row_group_size)
+            .with_coerce_primitive(true)
+            .build_decoder()
+            .map_err(io::Error::other)?;
+        let properties = WriterProperties::builder()
+            .set_max_row_group_size(row_group_size)
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(writer, arrow_schema, Some(properties))
+            .map_err(io::Error::other)?;
+        Ok(ParquetWriter {
+            writer,
+            decoder,
+            row_group_size,
+        })
+    }
+
+    /// Writes the buffered records, closing the row group once it is full.
+    fn write_batch(&mut self) -> io::Result<()> {
+        if let Some(batch) = self.decoder.flush().map_err(io::Error::other)? {
+            self.writer.write(&batch).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Send> RecordWriter for ParquetWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        self.decoder
+            .serialize(std::slice::from_ref(record))
+            .map_err(io::Error::other)?;
+        if self.decoder.len() >= self.row_group_size {
+            self.write_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.write_batch()?;
+        self.writer.finish().map_err(io::Error::other)?;
+        self.writer.inner_mut().flush()
+    }
+}
+
+/// Dumps a vector of JSON values to a Parquet file with columns derived from the schema.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::parquet_utils::*;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {"key": {"type": "string", "enum": ["value"]}}});
+/// let json_objects = vec![json!({"key": "value"})];
+///
+/// let result = dump_parquet(&json_objects, &schema, "output.parquet");
+/// assert!(result.is_ok());
+/// # std::fs::remove_file("output.parquet").unwrap();
+/// ```
+pub fn dump_parquet(objects: &[Value], schema: &Value, file: &str) -> io::Result<()> {
+    dump_records(objects, file, None, |writer| {
+        ParquetWriter::new(writer, schema, DEFAULT_ROW_GROUP_SIZE)
+    })
+}
@@ -10,17 +10,49 @@
 //!
 //! - `parse_integer`: Parses integer details from a JSON schema and returns a corresponding `Values` trait object.
 //! - `parse_string`: Parses string details from a JSON schema and returns a corresponding `Values` trait object.
+//! - `parse_number`: Parses number details from a JSON schema and returns a corresponding `Values` trait object.
 //! - `parse_array`: Parses array details from a JSON schema and returns a corresponding `Values` trait object.
 //! - `parse_object`: Parses object details from a JSON schema and returns a corresponding `Values` trait object.
+//! - `parse_map`: Parses an object with `additionalProperties` and returns a generator of random keys.
+//! - `parse_sequence`: Parses an `x-sequence` keyword and returns a counter that keeps state between records.
 //! - `parse_type`: Parses the given details based on the type and returns a corresponding `Values` trait object.
+//! - `with_edge_cases`: Marks every type of a schema to prefer boundary values with `x-edge-cases`.
 //! - `populate_schema`: Generates a template of possible values for different types based on a JSON schema.
+//! - `read_schema`: Reads a JSON schema, or an Avro schema ending in `.avsc`, from a file.
+//! - `generate_template_from_value`: Generates a template to create random values based on a parsed JSON schema.
 //! - `generate_template_from_schema`: Generates a template to create random values based on a JSON schema file.
 //!
+//! ## Templates
+//!
+//! A `Template` wraps a generated template for library users. `Template::iter` returns an endless iterator of
+//! records generated as they are needed, and `Template::iter_with_seed` one that gives the same records for the
+//! same seed. With the `async` feature `Template::stream` returns the records as a `futures::Stream` instead.
+//!
 
+use rand::{rngs::StdRng, SeedableRng};
 use serde_json::*;
 use std::collections::HashMap;
+use std::io;
+
+use crate::avro_utils::schema_from_avro;
+use crate::default_values::{
+    check_sequence_format, with_generator_rng, ArrayValues, DiscreteValues, EdgeCaseValues,
+    FloatValues, MapValues, NullableValues, ObjectValues, OneOfValues, RangedValues, SequenceScope,
+    SequenceValues, StringFormat, StringValues, UniqueValues, Values,
+};
+use crate::json_utils::{create_json_from_schema, read_input, STDIO_PATH};
+
+/// Longest random string generated when a schema gives no `maxLength`.
+const DEFAULT_MAX_LENGTH: u32 = 16;
+
+/// Most entries generated for a map when a schema gives no `maxProperties`.
+const DEFAULT_MAX_PROPERTIES: u32 = 5;
+
+/// Largest share of boundary values for a type marked `x-unique`.
+const MAX_UNIQUE_EDGE_CASES: f64 = 0.5;
 
-use crate::default_values::{ArrayValues, DiscreteValues, ObjectValues, RangedValues, Values};
+/// Bounds used for numbers when a schema gives no `minimum` or `maximum`.
+const DEFAULT_NUMBER_RANGE: f64 = 1e9;
 
 /// Parses integer details and returns a corresponding Values trait object.
 ///
@@ -32,27 +64,28 @@ use crate::default_values::{ArrayValues, DiscreteValues, ObjectValues, RangedVal
 /// use serde_json::json;
 ///
 /// let details = json!({"enum": [1, 2, 3]});
-/// let values = parse_integer(&details);
+/// let values = parse_integer(&details).unwrap();
 /// let result = values.get_value();
 /// let result = result.as_i64().unwrap();
 /// assert!(result == 1 || result == 2 || result == 3);
+///
+/// let details = json!({"type": "integer", "minimum": 0});
+/// assert!(parse_integer(&details).unwrap().get_value().as_i64().unwrap() >= 0);
 /// ```
-pub fn parse_integer(details: &Value) -> Box<dyn Values> {
+pub fn parse_integer(details: &Value) -> io::Result<Box<dyn Values>> {
     if let Some(x) = details.get("enum") {
-        return Box::new(DiscreteValues::new(x.as_array().unwrap())) as Box<dyn Values>;
-    }
-    if let Some(min) = details.get("minimum").unwrap().as_i64() {
-        if let Some(max) = details.get("maximum").unwrap().as_i64() {
-            return Box::new(RangedValues::new(min, max)) as Box<dyn Values>;
-        }
-        Box::new(RangedValues::new(min, i64::MAX)) as Box<dyn Values>
-    } else {
-        let min = i64::MIN;
-        if let Some(max) = details.get("maximum").unwrap().as_i64() {
-            return Box::new(RangedValues::new(min, max)) as Box<dyn Values>;
-        }
-        Box::new(RangedValues::new(min, i64::MAX)) as Box<dyn Values>
+        return Ok(Box::new(DiscreteValues::new(x.as_array().unwrap())) as Box<dyn Values>);
     }
+    let bound = |key: &str, default: i64| match details.get(key) {
+        None => Ok(default),
+        Some(x) => x
+            .as_i64()
+            .ok_or_else(|| invalid_schema(format!("{} must be an integer, found {}", key, x))),
+    };
+    let min = bound("minimum", i64::MIN)?;
+    let max = bound("maximum", i64::MAX)?;
+    check_bounds(details, "minimum", min, "maximum", max)?;
+    Ok(Box::new(RangedValues::new(min, max)) as Box<dyn Values>)
 }
 
 /// Parses string details and returns a corresponding Values trait object.
@@ -64,14 +97,66 @@ pub fn parse_integer(details: &Value) -> Box<dyn Values> {
 /// use serde_json::json;
 ///
 /// let details = json!({"enum": ["A", "B", "C"]});
-/// let values = pollinate::schema::parse_string(&details);
+/// let values = pollinate::schema::parse_string(&details).unwrap();
 /// let result = values.get_value();
 /// let result = result.as_str().unwrap();
 /// assert!(result == "A" || result == "B" || result == "C");
+///
+/// let details = json!({"format": "email"});
+/// let values = pollinate::schema::parse_string(&details).unwrap();
+/// assert!(values.get_value().as_str().unwrap().contains('@'));
+///
+/// let details = json!({"minLength": 5, "maxLength": 2});
+/// assert!(pollinate::schema::parse_string(&details).is_err());
+/// ```
+pub fn parse_string(details: &Value) -> io::Result<Box<dyn Values>> {
+    if let Some(x) = details.get("enum") {
+        return Ok(Box::new(DiscreteValues::new(x.as_array().unwrap())) as Box<dyn Values>);
+    }
+    let format = match details.get("format") {
+        None => None,
+        Some(Value::String(x)) => StringFormat::from_name(x),
+        Some(x) => {
+            return Err(invalid_schema(format!(
+                "format must be a string, found {}",
+                x
+            )))
+        }
+    };
+    let max = length(details, "maxLength")?;
+    // a length of 1 unless the maximum allows only empty strings
+    let min = length(details, "minLength")?.unwrap_or(max.map_or(1, |x| x.min(1)));
+    let max = max.unwrap_or(DEFAULT_MAX_LENGTH.max(min));
+    check_bounds(details, "minLength", min, "maxLength", max)?;
+    Ok(Box::new(StringValues::new(min, max, format)) as Box<dyn Values>)
+}
+
+/// Parses number details and returns a corresponding Values trait object.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+///
+/// let details = json!({"type": "number", "minimum": 0.5, "maximum": 2.5});
+/// let values = pollinate::schema::parse_number(&details).unwrap();
+/// let result = values.get_value().as_f64().unwrap();
+/// assert!((0.5..=2.5).contains(&result));
 /// ```
-pub fn parse_string(details: &Value) -> Box<dyn Values> {
-    let enum_values = details.get("enum").unwrap().as_array().unwrap();
-    Box::new(DiscreteValues::new(enum_values)) as Box<dyn Values>
+pub fn parse_number(details: &Value) -> io::Result<Box<dyn Values>> {
+    if let Some(x) = details.get("enum") {
+        return Ok(Box::new(DiscreteValues::new(x.as_array().unwrap())) as Box<dyn Values>);
+    }
+    let bound = |key: &str, default: f64| match details.get(key) {
+        None => Ok(default),
+        Some(x) => x
+            .as_f64()
+            .ok_or_else(|| invalid_schema(format!("{} must be a number, found {}", key, x))),
+    };
+    let min = bound("minimum", -DEFAULT_NUMBER_RANGE)?;
+    let max = bound("maximum", DEFAULT_NUMBER_RANGE)?;
+    check_bounds(details, "minimum", min, "maximum", max)?;
+    Ok(Box::new(FloatValues::new(min, max)) as Box<dyn Values>)
 }
 
 /// Parses array details and returns a corresponding Values trait object.
@@ -83,7 +168,7 @@ pub fn parse_string(details: &Value) -> Box<dyn Values> {
 /// use serde_json::json;
 ///
 /// let details = json!({"type": "array", "maximum": 5, "minimum": 2, "items": {"type": "string", "enum": ["X", "Y"]}});
-/// let values = pollinate::schema::parse_array(&details);
+/// let values = pollinate::schema::parse_array(&details).unwrap();
 /// let result = values.get_value();
 /// let result = result.as_array().unwrap();
 /// let len = result.len();
@@ -93,38 +178,311 @@ pub fn parse_string(details: &Value) -> Box<dyn Values> {
 ///   assert!(item == "X" || item == "Y");
 /// }
 /// ```
-pub fn parse_array(details: &Value) -> Box<dyn Values> {
-    if let Some(x) = details.get("maximum") {
-        let mut min = 0;
-        let max = x.as_u64().unwrap() as u32;
-        if let Some(n) = details.get("minimum") {
-            min = n.as_u64().unwrap() as u32;
-        }
-        let types = parse_type(details.get("items").unwrap()).unwrap();
-        Box::new(ArrayValues::new(min, max, types)) as Box<dyn Values>
+pub fn parse_array(details: &Value) -> io::Result<Box<dyn Values>> {
+    if let Some(max) = length(details, "maximum")? {
+        let min = length(details, "minimum")?.unwrap_or(0);
+        check_bounds(details, "minimum", min, "maximum", max)?;
+        let types = required_type(details, "items")?;
+        Ok(Box::new(ArrayValues::new(min, max, types)) as Box<dyn Values>)
     } else {
-        panic!("Arrays must have max value")
+        Err(invalid_schema(format!(
+            "Arrays must have max value, found {}",
+            details
+        )))
     }
 }
 
 /// Parses object details and returns a corresponding Values trait object.
-pub fn parse_object(details: &Value) -> Box<dyn Values> {
-    let properties = details.get("properties").unwrap();
-    let properties = properties.as_object().unwrap();
-    let schema = populate_schema(properties);
-    Box::new(ObjectValues::new(schema)) as Box<dyn Values>
+/// An object without `properties` but with an `additionalProperties` schema is a map.
+pub fn parse_object(details: &Value) -> io::Result<Box<dyn Values>> {
+    if details.get("properties").is_none() && details.get("additionalProperties").is_some() {
+        return parse_map(details);
+    }
+    let schema = populate_schema(properties_of(details)?)?;
+    Ok(Box::new(ObjectValues::new(schema)) as Box<dyn Values>)
+}
+
+/// Parses map details and returns a corresponding Values trait object.
+/// Every value follows the `additionalProperties` schema, the number of entries
+/// is between `minProperties` (default 0) and `maxProperties` (default 5).
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::Values;
+/// use serde_json::json;
+///
+/// let details = json!({"type": "object", "maxProperties": 2, "additionalProperties": {"type": "boolean"}});
+/// let values = pollinate::schema::parse_map(&details).unwrap();
+/// let result = values.get_value();
+/// assert!(result.as_object().unwrap().len() <= 2);
+/// ```
+pub fn parse_map(details: &Value) -> io::Result<Box<dyn Values>> {
+    let min = length(details, "minProperties")?.unwrap_or(0);
+    let max = length(details, "maxProperties")?.unwrap_or(DEFAULT_MAX_PROPERTIES.max(min));
+    check_bounds(details, "minProperties", min, "maxProperties", max)?;
+    let types = required_type(details, "additionalProperties")?;
+    Ok(Box::new(MapValues::new(min, max, types)) as Box<dyn Values>)
+}
+
+/// Parses `x-sequence` details and returns a corresponding Values trait object.
+/// Accepts `start` (default 1), `step` (default 1), an optional `format` such as
+/// `"ORD-{:06}"` and a `scope` of `"parent"` (default) or `"global"`.
+/// Fails with an `InvalidInput` error for any other keyword value, such as a malformed format.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::default_values::Values;
+/// use serde_json::json;
+///
+/// let details = json!({"type": "integer", "x-sequence": {"start": 10, "step": 5}});
+/// let values = pollinate::schema::parse_sequence(&details).unwrap();
+/// assert_eq!(values.get_value(), json!(10));
+/// assert_eq!(values.get_value(), json!(15));
+///
+/// let details = json!({"type": "integer", "x-sequence": {"scope": "table"}});
+/// assert!(pollinate::schema::parse_sequence(&details).is_err());
+/// ```
+pub fn parse_sequence(details: &Value) -> io::Result<Box<dyn Values>> {
+    let sequence = details.get("x-sequence").and_then(|x| x.as_object());
+    let sequence = sequence.ok_or_else(|| {
+        invalid_schema(format!("x-sequence must be an object, found {}", details))
+    })?;
+    let integer = |key: &str, default: i64| match sequence.get(key) {
+        None => Ok(default),
+        Some(x) => x.as_i64().ok_or_else(|| {
+            invalid_schema(format!(
+                "x-sequence {} must be an integer, found {}",
+                key, x
+            ))
+        }),
+    };
+    let start = integer("start", 1)?;
+    let step = integer("step", 1)?;
+    let format = match sequence.get("format") {
+        None => None,
+        Some(Value::String(x)) => {
+            check_sequence_format(x).map_err(invalid_schema)?;
+            Some(x.to_owned())
+        }
+        Some(x) => {
+            return Err(invalid_schema(format!(
+                "x-sequence format must be a string, found {}",
+                x
+            )))
+        }
+    };
+    let scope = match sequence.get("scope") {
+        None => SequenceScope::Parent,
+        Some(x) if x == "parent" => SequenceScope::Parent,
+        Some(x) if x == "global" => SequenceScope::Global,
+        Some(x) => {
+            return Err(invalid_schema(format!(
+                "x-sequence scope must be \"parent\" or \"global\", found {}",
+                x
+            )))
+        }
+    };
+    Ok(Box::new(SequenceValues::new(start, step, format, scope)) as Box<dyn Values>)
 }
 
 /// Parses the given details based on the type and returns a corresponding Values trait object.
-pub fn parse_type(details: &Value) -> Option<Box<dyn Values>> {
-    match details.get("type").unwrap().as_str() {
-        Some("string") => return Some(parse_string(details)),
-        Some("integer") => return Some(parse_integer(details)),
-        Some("array") => return Some(parse_array(details)),
-        Some("object") => return Some(parse_object(details)),
-        Some(_) => todo!(),
-        None => todo!(),
+/// A type such as `["string", "null"]` is nullable and sometimes generates null.
+/// A `oneOf` list picks one of its schemas at random for every value.
+/// Types marked with `"x-edge-cases": ratio` return boundary values for that share of their values.
+/// Types marked with `"x-unique": true` never repeat a value.
+/// Returns `None` for details without a type, or with a type values can't be generated for.
+/// Fails with an `InvalidInput` error for details that can't be used, such as a malformed `x-sequence`
+/// or an empty `enum`.
+pub fn parse_type(details: &Value) -> io::Result<Option<Box<dyn Values>>> {
+    if let Some(options) = details.get("enum") {
+        if options.as_array().is_none_or(|x| x.is_empty()) {
+            return Err(invalid_schema(format!(
+                "enum must be a list of at least one value, found {}",
+                options
+            )));
+        }
+    }
+    let values = if details.get("x-sequence").is_some() {
+        parse_sequence(details)?
+    } else if let Some(options) = details.get("oneOf") {
+        let options = options.as_array().unwrap();
+        let options = options
+            .iter()
+            .map(parse_type)
+            .collect::<io::Result<Vec<_>>>()?;
+        let Some(options) = options.into_iter().collect::<Option<_>>() else {
+            return Ok(None);
+        };
+        Box::new(OneOfValues::new(options)) as Box<dyn Values>
+    } else {
+        let Some(x) = details.get("type") else {
+            return Ok(None);
+        };
+        match x {
+            Value::Array(types) => match parse_nullable(details, types)? {
+                Some(values) => values,
+                None => return Ok(None),
+            },
+            x => match x.as_str() {
+                Some("string") => parse_string(details)?,
+                Some("integer") => parse_integer(details)?,
+                Some("number") => parse_number(details)?,
+                Some("boolean") => Box::new(DiscreteValues::new(&[json!(true), json!(false)])),
+                Some("null") => Box::new(DiscreteValues::new(&[Value::Null])),
+                Some("array") => parse_array(details)?,
+                Some("object") => parse_object(details)?,
+                _ => return Ok(None),
+            },
+        }
+    };
+    let values = match details.get("x-edge-cases").and_then(|x| x.as_f64()) {
+        Some(ratio) if details.get("x-sequence").is_none() => {
+            // unique values need random ones too once the few boundary values are used up
+            let max = match details.get("x-unique") {
+                Some(Value::Bool(true)) => MAX_UNIQUE_EDGE_CASES,
+                _ => 1.0,
+            };
+            Box::new(EdgeCaseValues::new(values, ratio.clamp(0.0, max))) as Box<dyn Values>
+        }
+        _ => values,
     };
+    if details.get("x-unique") == Some(&Value::Bool(true)) {
+        return Ok(Some(Box::new(UniqueValues::new(values)) as Box<dyn Values>));
+    }
+    Ok(Some(values))
+}
+
+/// Parses the schema under a keyword, such as `items`, that must have a type values can be generated for.
+fn required_type(details: &Value, key: &str) -> io::Result<Box<dyn Values>> {
+    let schema = details.get(key).unwrap_or(&Value::Null);
+    parse_type(schema)?.ok_or_else(|| {
+        invalid_schema(format!(
+            "{} has no type values can be generated for, found {}",
+            key, details
+        ))
+    })
+}
+
+/// Returns the `properties` of an object schema.
+fn properties_of(details: &Value) -> io::Result<&Map<String, Value>> {
+    details
+        .get("properties")
+        .and_then(|x| x.as_object())
+        .ok_or_else(|| invalid_schema(format!("Objects must have properties, found {}", details)))
+}
+
+/// Reads a length, such as `maxLength`, which must be a whole number that fits in 32 bits.
+fn length(details: &Value, key: &str) -> io::Result<Option<u32>> {
+    match details.get(key) {
+        None => Ok(None),
+        Some(x) => match x.as_u64().and_then(|x| u32::try_from(x).ok()) {
+            Some(x) => Ok(Some(x)),
+            None => Err(invalid_schema(format!(
+                "{} must be a whole number up to {}, found {}",
+                key,
+                u32::MAX,
+                x
+            ))),
+        },
+    }
+}
+
+/// Fails when a lower bound is above its upper bound, as nothing could be generated between them.
+fn check_bounds<T: PartialOrd + std::fmt::Display>(
+    details: &Value,
+    min_key: &str,
+    min: T,
+    max_key: &str,
+    max: T,
+) -> io::Result<()> {
+    if min > max {
+        return Err(invalid_schema(format!(
+            "{} {} is above {} {}, found {}",
+            min_key, min, max_key, max, details
+        )));
+    }
+    Ok(())
+}
+
+/// Error for a part of a schema values can't be generated from.
+fn invalid_schema(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Parses a list of types made up of `"null"` and at most one other type.
+fn parse_nullable(details: &Value, types: &[Value]) -> io::Result<Option<Box<dyn Values>>> {
+    let other: Vec<&Value> = types
+        .iter()
+        .filter(|x| x.as_str() != Some("null"))
+        .collect();
+    let mut details = details.clone();
+    // uniqueness and edge cases are applied once by the caller around the nullable value
+    details.as_object_mut().unwrap().remove("x-unique");
+    details.as_object_mut().unwrap().remove("x-edge-cases");
+    match other[..] {
+        [] => details["type"] = json!("null"),
+        [x] => details["type"] = x.clone(),
+        _ => {
+            return Err(invalid_schema(format!(
+                "Only one type besides null is supported, found {:?}",
+                types
+            )))
+        }
+    }
+    let Some(values) = parse_type(&details)? else {
+        return Ok(None);
+    };
+    if other.len() < types.len() {
+        return Ok(Some(
+            Box::new(NullableValues::new(values)) as Box<dyn Values>
+        ));
+    }
+    Ok(Some(values))
+}
+
+/// Returns a copy of a schema where every type, including nested properties, items and `oneOf` schemas,
+/// prefers boundary values for the given share of its values. Types with their own `x-edge-cases` keep it.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {
+///     "age": {"type": "integer", "minimum": 0, "maximum": 100},
+///     "tags": {"type": "array", "maximum": 2, "items": {"type": "string", "x-edge-cases": 1.0}}
+/// }});
+/// let schema = pollinate::schema::with_edge_cases(&schema, 0.25);
+/// assert_eq!(schema["properties"]["age"]["x-edge-cases"], json!(0.25));
+/// assert_eq!(schema["properties"]["tags"]["items"]["x-edge-cases"], json!(1.0));
+/// ```
+pub fn with_edge_cases(schema: &Value, ratio: f64) -> Value {
+    let mut schema = schema.clone();
+    mark_edge_cases(&mut schema, ratio);
+    schema
+}
+
+fn mark_edge_cases(details: &mut Value, ratio: f64) {
+    let Some(details) = details.as_object_mut() else {
+        return;
+    };
+    for (key, value) in details.iter_mut() {
+        match (key.as_str(), value) {
+            ("properties", Value::Object(properties)) => properties
+                .values_mut()
+                .for_each(|x| mark_edge_cases(x, ratio)),
+            ("oneOf", Value::Array(options)) => {
+                options.iter_mut().for_each(|x| mark_edge_cases(x, ratio))
+            }
+            ("items" | "additionalProperties", x) => mark_edge_cases(x, ratio),
+            _ => (),
+        }
+    }
+    if !details.contains_key("x-edge-cases") {
+        details.insert("x-edge-cases".to_owned(), json!(ratio));
+    }
 }
 
 /// Gets possible values for a type based on a JSON schema.
@@ -132,14 +490,45 @@ pub fn parse_type(details: &Value) -> Option<Box<dyn Values>> {
 /// ```
 pub fn populate_schema<'a>(
     properties: &Map<String, Value>,
-) -> HashMap<String, Box<dyn Values + 'a>> {
+) -> io::Result<HashMap<String, Box<dyn Values + 'a>>> {
     let mut enumerated_schema = HashMap::<String, Box<dyn Values>>::new();
     for (property, details) in properties.iter() {
-        if let Some(val) = parse_type(details) {
+        if let Some(val) = parse_type(details)? {
             enumerated_schema.insert(property.to_owned(), val);
         }
     }
-    enumerated_schema
+    Ok(enumerated_schema)
+}
+
+/// Reads a JSON schema from a file, or from standard input when the path is `-`.
+/// Files ending in `.avsc` are read as Avro schemas and converted, as are Avro records read from standard input.
+/// Fails when the file can't be read, doesn't hold JSON or holds an Avro schema that can't be converted.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::schema::read_schema;
+///
+/// let schema = read_schema("test_data/person_schema.json").unwrap();
+/// assert!(schema.get("properties").is_some());
+/// assert!(read_schema("test_data/missing.json").is_err());
+/// ```
+pub fn read_schema(schema_path: &str) -> io::Result<Value> {
+    let schema_string = read_input(schema_path)?;
+    let schema: Value = serde_json::from_str(&schema_string)?;
+    let avro_record = schema_path == STDIO_PATH && schema.get("type") == Some(&json!("record"));
+    if schema_path.ends_with(".avsc") || avro_record {
+        return schema_from_avro(&schema);
+    }
+    Ok(schema)
+}
+
+/// Generates a template for creating JSON objects from an already parsed schema.
+/// Fails with an `InvalidInput` error for schemas values can't be generated from.
+pub fn generate_template_from_value<'a>(
+    schema: &Value,
+) -> io::Result<HashMap<String, Box<dyn Values + 'a>>> {
+    populate_schema(properties_of(schema)?)
 }
 
 /// Generates a template for creating JSON objects.
@@ -147,17 +536,155 @@ pub fn populate_schema<'a>(
 /// See main.rs for example
 pub fn generate_template_from_schema<'a>(
     schema_path: &str,
-) -> HashMap<String, Box<dyn Values + 'a>> {
-    let schema_string =
-        std::fs::read_to_string(schema_path).expect("Should have been able to read the file");
-    let parsed_schema: Map<String, Value> = serde_json::from_str(&schema_string).unwrap();
-    let enumerated_schema = populate_schema(
-        parsed_schema
-            .get("properties")
-            .unwrap()
-            .as_object()
-            .unwrap(),
-    );
-    //temp return value for
-    enumerated_schema
+) -> io::Result<HashMap<String, Box<dyn Values + 'a>>> {
+    generate_template_from_value(&read_schema(schema_path)?)
+}
+
+/// Generators for the fields of a schema, ready to produce records.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::schema::Template;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {
+///     "id": {"type": "integer", "x-sequence": {"start": 1}},
+///     "name": {"type": "string", "maxLength": 8}
+/// }});
+/// let template = Template::new(&schema).unwrap();
+/// let ids: Vec<_> = template.iter().take(3).map(|x| x["id"].clone()).collect();
+/// assert_eq!(ids, vec![json!(1), json!(2), json!(3)]);
+///
+/// let first: Vec<_> = template.iter_with_seed(42).take(5).collect();
+/// let second: Vec<_> = template.iter_with_seed(42).take(5).collect();
+/// assert_eq!(first, second);
+/// ```
+#[derive(Clone)]
+pub struct Template<'a> {
+    fields: HashMap<String, Box<dyn Values + 'a>>,
+    // untouched copy of the generators that every iterator starts from
+    initial: HashMap<String, Box<dyn Values + 'a>>,
+}
+
+impl<'a> Template<'a> {
+    /// Builds the generators for an already parsed schema.
+    pub fn new(schema: &Value) -> io::Result<Self> {
+        Ok(Template::from_fields(generate_template_from_value(schema)?))
+    }
+
+    /// Reads a schema from a file, as `read_schema` does, and builds its generators.
+    pub fn from_file(schema_path: &str) -> io::Result<Self> {
+        Template::new(&read_schema(schema_path)?)
+    }
+
+    /// Wraps generators that were built by hand.
+    pub fn from_fields(fields: HashMap<String, Box<dyn Values + 'a>>) -> Self {
+        Template {
+            initial: fields.clone(),
+            fields,
+        }
+    }
+
+    pub fn fields(&self) -> &HashMap<String, Box<dyn Values + 'a>> {
+        &self.fields
+    }
+
+    /// Generates a single record.
+    pub fn generate(&self) -> Value {
+        create_json_from_schema(&self.fields)
+    }
+
+    /// Returns an endless iterator of records, seeded from the operating system.
+    pub fn iter(&self) -> Records<'a> {
+        self.records(StdRng::from_entropy())
+    }
+
+    /// Returns an endless iterator giving the same records every time it is made with the same seed.
+    pub fn iter_with_seed(&self, seed: u64) -> Records<'a> {
+        self.records(StdRng::seed_from_u64(seed))
+    }
+
+    /// Returns an endless stream of records, seeded from the operating system.
+    #[cfg(feature = "async")]
+    pub fn stream(&self) -> RecordStream<'a> {
+        RecordStream {
+            records: self.iter(),
+        }
+    }
+
+    /// Returns an endless stream giving the same records every time it is made with the same seed.
+    #[cfg(feature = "async")]
+    pub fn stream_with_seed(&self, seed: u64) -> RecordStream<'a> {
+        RecordStream {
+            records: self.iter_with_seed(seed),
+        }
+    }
+
+    fn records(&self, rng: StdRng) -> Records<'a> {
+        Records {
+            fields: self.initial.clone(),
+            rng,
+        }
+    }
+}
+
+/// Endless iterator of records returned by `Template::iter`, generating each record when it is asked for.
+/// It keeps its own copy of the generators and random number generator, so sequences and unique values
+/// start over for every iterator, whatever `Template::generate` has given out, and several iterators
+/// can be used side by side.
+#[derive(Clone)]
+pub struct Records<'a> {
+    fields: HashMap<String, Box<dyn Values + 'a>>,
+    rng: StdRng,
+}
+
+impl Iterator for Records<'_> {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        let fields = &self.fields;
+        Some(with_generator_rng(&mut self.rng, || {
+            create_json_from_schema(fields)
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+/// Endless stream of records returned by `Template::stream`, which is always ready with the next record.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{executor::block_on, StreamExt};
+/// use pollinate::schema::Template;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {"id": {"type": "integer", "x-sequence": {}}}});
+/// let records: Vec<_> = block_on(Template::new(&schema).unwrap().stream().take(2).collect());
+/// assert_eq!(records, vec![json!({"id": 1}), json!({"id": 2})]);
+/// ```
+#[cfg(feature = "async")]
+#[derive(Clone)]
+pub struct RecordStream<'a> {
+    records: Records<'a>,
+}
+
+#[cfg(feature = "async")]
+impl futures::Stream for RecordStream<'_> {
+    type Item = Value;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        _: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Value>> {
+        std::task::Poll::Ready(self.get_mut().records.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
 }
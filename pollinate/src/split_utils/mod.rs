@@ -0,0 +1,284 @@
+//! # Pollinate Split Module
+//!
+//! The `split_utils` module spreads generated records over many files named by an output template.
+//!
+//! ## Overview
+//!
+//! A new file is started every `split` records. Its path is found by rendering the output template, which can
+//! hold these placeholders:
+//!
+//! - `{index}`: The number of the file, counting from 0. `{index:05}` pads it with zeros to 5 digits.
+//! - `{uuid}`: A random version 4 UUID, not drawn from the seeded generator.
+//! - `{field:path}`: The value of a field of the first record in the file, with dots separating nested fields.
+//!
+//! Directories in the rendered paths are created as needed. Once every record is written a manifest listing
+//! each file and its number of records can be written as well.
+//!
+//! ## Functions
+//!
+//! - `default_template`: Derives an output template from a path by adding the file index before its extension.
+//!
+
+use rand::{thread_rng, Rng};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::num::NonZeroU32;
+use std::path::Path;
+
+use crate::compress_utils::strip_extension;
+use crate::default_values::format_uuid;
+use crate::json_utils::{create_record_writer, dump_value_with_style, RecordWriter, WriterOptions};
+
+/// A piece of an output template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Index(usize),
+    Uuid,
+    Field(Vec<String>),
+}
+
+/// A file path with placeholders for the file index, a random UUID and field values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputTemplate {
+    segments: Vec<Segment>,
+}
+
+fn invalid_template(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+impl OutputTemplate {
+    /// Parses a template, failing on unclosed braces and unknown placeholders.
+    pub fn parse(template: &str) -> io::Result<Self> {
+        let mut segments = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| invalid_template(format!("Unclosed placeholder in {}", template)))?;
+            let placeholder = &rest[start + 1..start + end];
+            segments.push(match placeholder.split_once(':') {
+                None if placeholder == "index" => Segment::Index(0),
+                None if placeholder == "uuid" => Segment::Uuid,
+                Some(("index", width)) => Segment::Index(
+                    width
+                        .parse()
+                        .map_err(|_| invalid_template(format!("Invalid index width {}", width)))?,
+                ),
+                Some(("field", path)) if !path.is_empty() => {
+                    Segment::Field(path.split('.').map(String::from).collect())
+                }
+                _ => {
+                    return Err(invalid_template(format!(
+                        "Unknown placeholder {{{}}}",
+                        placeholder
+                    )))
+                }
+            });
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        Ok(OutputTemplate { segments })
+    }
+
+    /// Renders the path of a file from its index and the first record written to it.
+    /// Path separators in field values are replaced, as are values of `.` and `..`, so a value cannot add
+    /// directories or leave the one it is in. UUIDs are drawn from their own generator, so naming files does not
+    /// change the records generated from a seed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pollinate::split_utils::OutputTemplate;
+    /// use serde_json::json;
+    ///
+    /// let template = OutputTemplate::parse("out/{index:05}-{field:user.id}.json").unwrap();
+    /// let path = template.render(42, &json!({"user": {"id": "a/b"}})).unwrap();
+    /// assert_eq!(path, "out/00042-a_b.json");
+    /// ```
+    pub fn render(&self, index: u64, record: &Value) -> io::Result<String> {
+        let mut path = String::new();
+        for segment in self.segments.iter() {
+            match segment {
+                Segment::Text(text) => path.push_str(text),
+                Segment::Index(width) => path.push_str(&format!("{:0width$}", index)),
+                Segment::Uuid => path.push_str(&format_uuid(thread_rng().gen())),
+                Segment::Field(fields) => {
+                    let value = fields
+                        .iter()
+                        .try_fold(record, |value, field| value.get(field))
+                        .ok_or_else(|| {
+                            invalid_template(format!("Record has no field {}", fields.join(".")))
+                        })?;
+                    let value = match value {
+                        Value::String(x) => x.clone(),
+                        x => x.to_string(),
+                    };
+                    let value = value.replace(['/', '\\'], "_");
+                    match value.as_str() {
+                        "." | ".." => path.push_str(&"_".repeat(value.len())),
+                        _ => path.push_str(&value),
+                    }
+                }
+            }
+        }
+        Ok(path)
+    }
+}
+
+/// Derives an output template from a path by adding the file index before its extension.
+/// A compression extension is kept after the extension of the format.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::split_utils::default_template;
+///
+/// assert_eq!(default_template("out/users.json"), "out/users-{index}.json");
+/// assert_eq!(default_template("out/users.json.gz"), "out/users-{index}.json.gz");
+/// assert_eq!(default_template("users"), "users-{index}");
+/// ```
+pub fn default_template(output: &str) -> String {
+    let path = strip_extension(output);
+    let compression = &output[path.len()..];
+    match Path::new(path).extension().and_then(|x| x.to_str()) {
+        Some(extension) => {
+            let stem = &path[..path.len() - extension.len() - 1];
+            format!("{}-{{index}}.{}{}", stem, extension, compression)
+        }
+        None => format!("{}-{{index}}{}", path, compression),
+    }
+}
+
+/// Writes every `split` records to a new file in the format given by the options.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::{RecordWriter, WriterOptions};
+/// use pollinate::split_utils::{OutputTemplate, SplitWriter};
+/// use serde_json::json;
+/// use std::num::NonZeroU32;
+///
+/// let schema = json!({"type": "object", "properties": {"id": {"type": "integer", "minimum": 0, "maximum": 9}}});
+/// let template = OutputTemplate::parse("split_example/{index}.json").unwrap();
+/// let split = NonZeroU32::new(2).unwrap();
+/// let mut writer = SplitWriter::new(template, &schema, 3, split, WriterOptions::default());
+/// for id in 0..3 {
+///     writer.write_record(&json!({"id": id})).unwrap();
+/// }
+/// writer.finish().unwrap();
+/// assert_eq!(std::fs::read_to_string("split_example/1.json").unwrap(), r#"{"id":2}"#);
+/// # std::fs::remove_dir_all("split_example").unwrap();
+/// ```
+pub struct SplitWriter {
+    template: OutputTemplate,
+    schema: Value,
+    options: WriterOptions,
+    split: u32,
+    remaining: u32,
+    writer: Option<Box<dyn RecordWriter>>,
+    files: Vec<(String, u32)>,
+    paths: HashSet<String>,
+    manifest: Option<String>,
+}
+
+impl SplitWriter {
+    /// Creates a writer for `count` records, `split` of them in each file.
+    pub fn new(
+        template: OutputTemplate,
+        schema: &Value,
+        count: u32,
+        split: NonZeroU32,
+        options: WriterOptions,
+    ) -> Self {
+        SplitWriter {
+            template,
+            schema: schema.clone(),
+            options,
+            split: split.get(),
+            remaining: count,
+            writer: None,
+            files: Vec::new(),
+            paths: HashSet::new(),
+            manifest: None,
+        }
+    }
+
+    /// Writes a manifest listing every file and its number of records to this path when finished.
+    pub fn with_manifest(mut self, manifest: &str) -> Self {
+        self.manifest = Some(manifest.to_string());
+        self
+    }
+
+    /// Paths of the files written so far along with their number of records.
+    pub fn files(&self) -> &[(String, u32)] {
+        &self.files
+    }
+
+    /// Finishes the current file and starts the next one, named after its first record.
+    fn next_file(&mut self, record: &Value) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        let path = self.template.render(self.files.len() as u64, record)?;
+        if !self.paths.insert(path.clone()) {
+            return Err(invalid_template(format!(
+                "Output template names more than one file {}",
+                path
+            )));
+        }
+        if let Some(parent) = Path::new(&path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let count = self.split.min(self.remaining.max(1));
+        self.writer = Some(create_record_writer(
+            &path,
+            &self.schema,
+            count,
+            &self.options,
+        )?);
+        self.files.push((path, 0));
+        Ok(())
+    }
+}
+
+impl RecordWriter for SplitWriter {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        let full = self.files.last().is_none_or(|x| x.1 == self.split);
+        if full {
+            self.next_file(record)?;
+        }
+        self.writer.as_mut().unwrap().write_record(record)?;
+        self.files.last_mut().unwrap().1 += 1;
+        self.remaining = self.remaining.saturating_sub(1);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        if let Some(manifest) = &self.manifest {
+            let files: Vec<Value> = self
+                .files
+                .iter()
+                .map(|(path, records)| json!({"path": path, "records": records}))
+                .collect();
+            dump_value_with_style(
+                json!({ "files": files }),
+                manifest,
+                &self.options.json_style,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,765 @@
+//! # Pollinate SQL Module
+//!
+//! The `sql_utils` module writes generated records as a SQL script for seeding a database.
+//!
+//! ## Overview
+//!
+//! A `CREATE TABLE` statement is derived from the schema, followed by batched multi-row `INSERT` statements.
+//! Schema types are mapped to column types of the chosen dialect, with `date`, `date-time` and `uuid` string
+//! formats getting their own types where the dialect has them. Nested objects and arrays are either stored in
+//! JSON columns, or split into child tables linked to their parent by generated `_id` and `_parent_id` keys.
+//! Records can also be inserted directly into a SQLite database file using the same tables.
+//!
+//! ## Functions
+//!
+//! - `table_name_from_schema`: Derives a table name from the schema's title.
+//! - `tables_from_schema`: Maps a schema to one or more tables.
+//! - `create_table_sql`: Creates the `CREATE TABLE` statement for a table.
+//! - `sql_literal`: Renders a value as an escaped SQL literal.
+//!
+
+use rusqlite::{params_from_iter, Connection};
+use serde_json::Value;
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::default_values::StringFormat;
+use crate::json_utils::RecordWriter;
+
+/// Rows written by a single `INSERT` statement.
+const SQL_BATCH_SIZE: usize = 100;
+
+/// Column holding the generated key of each row when nested values are split into tables.
+pub const ID_COLUMN: &str = "_id";
+
+/// Column of a child table holding the generated key of its parent row.
+pub const PARENT_ID_COLUMN: &str = "_parent_id";
+
+/// Databases SQL can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    Postgres,
+    Mysql,
+    Sqlite,
+}
+
+impl SqlDialect {
+    /// Every SQL dialect, in the order they are listed in help.
+    pub const ALL: &'static [SqlDialect] =
+        &[SqlDialect::Postgres, SqlDialect::Mysql, SqlDialect::Sqlite];
+
+    /// Returns the name the SQL dialect is parsed from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pollinate::sql_utils::SqlDialect;
+    ///
+    /// assert_eq!("mysql".parse::<SqlDialect>(), Ok(SqlDialect::Mysql));
+    /// assert_eq!(SqlDialect::Mysql.name(), "mysql");
+    /// assert!("oracle".parse::<SqlDialect>().is_err());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            SqlDialect::Postgres => "postgres",
+            SqlDialect::Mysql => "mysql",
+            SqlDialect::Sqlite => "sqlite",
+        }
+    }
+}
+
+impl FromStr for SqlDialect {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        SqlDialect::ALL
+            .iter()
+            .find(|x| x.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = SqlDialect::ALL.iter().map(|x| x.name()).collect();
+                format!(
+                    "Unknown SQL dialect {}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// How nested objects and arrays are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlNesting {
+    /// In a JSON column of the parent table
+    Json,
+    /// In child tables, arrays of anything other than objects are still stored as JSON
+    Tables,
+}
+
+impl SqlNesting {
+    /// Every SQL nesting, in the order they are listed in help.
+    pub const ALL: &'static [SqlNesting] = &[SqlNesting::Json, SqlNesting::Tables];
+
+    /// Returns the name the SQL nesting is parsed from.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SqlNesting::Json => "json",
+            SqlNesting::Tables => "tables",
+        }
+    }
+}
+
+impl FromStr for SqlNesting {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        SqlNesting::ALL
+            .iter()
+            .find(|x| x.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = SqlNesting::ALL.iter().map(|x| x.name()).collect();
+                format!(
+                    "Unknown SQL nesting {}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Column types values are mapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlType {
+    Integer,
+    Real,
+    Text,
+    Boolean,
+    Date,
+    Timestamp,
+    Uuid,
+    Json,
+}
+
+impl SqlType {
+    /// Returns the name of the type in a dialect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pollinate::sql_utils::*;
+    ///
+    /// assert_eq!(SqlType::Json.name(SqlDialect::Postgres), "JSONB");
+    /// assert_eq!(SqlType::Json.name(SqlDialect::Sqlite), "TEXT");
+    /// ```
+    pub fn name(&self, dialect: SqlDialect) -> &'static str {
+        use SqlDialect::*;
+        match (self, dialect) {
+            (SqlType::Integer, Sqlite) => "INTEGER",
+            (SqlType::Integer, _) => "BIGINT",
+            (SqlType::Real, Postgres) => "DOUBLE PRECISION",
+            (SqlType::Real, Mysql) => "DOUBLE",
+            (SqlType::Real, Sqlite) => "REAL",
+            (SqlType::Text, _) => "TEXT",
+            (SqlType::Boolean, Sqlite) => "INTEGER",
+            (SqlType::Boolean, _) => "BOOLEAN",
+            (SqlType::Date, Sqlite) => "TEXT",
+            (SqlType::Date, _) => "DATE",
+            (SqlType::Timestamp, Postgres) => "TIMESTAMPTZ",
+            (SqlType::Timestamp, Mysql) => "DATETIME",
+            (SqlType::Timestamp, Sqlite) => "TEXT",
+            (SqlType::Uuid, Postgres) => "UUID",
+            (SqlType::Uuid, Mysql) => "CHAR(36)",
+            (SqlType::Uuid, Sqlite) => "TEXT",
+            (SqlType::Json, Postgres) => "JSONB",
+            (SqlType::Json, Mysql) => "JSON",
+            (SqlType::Json, Sqlite) => "TEXT",
+        }
+    }
+
+    /// Maps the details of a schema property to a column type.
+    pub fn from_details(details: &Value) -> Self {
+        let type_name = match details.get("type") {
+            Some(Value::Array(types)) => types.iter().find(|x| x.as_str() != Some("null")),
+            x => x,
+        };
+        let formatted = details
+            .get("x-sequence")
+            .is_some_and(|x| x.get("format").is_some());
+        match type_name.and_then(|x| x.as_str()) {
+            _ if formatted => SqlType::Text,
+            Some("integer") => SqlType::Integer,
+            Some("number") => SqlType::Real,
+            Some("boolean") => SqlType::Boolean,
+            Some("string") => {
+                let format = details.get("format").and_then(|x| x.as_str());
+                match format.and_then(StringFormat::from_name) {
+                    Some(StringFormat::Date) => SqlType::Date,
+                    Some(StringFormat::DateTime) => SqlType::Timestamp,
+                    Some(StringFormat::Uuid) => SqlType::Uuid,
+                    _ => SqlType::Text,
+                }
+            }
+            Some("object") | Some("array") => SqlType::Json,
+            _ => SqlType::Text,
+        }
+    }
+}
+
+/// A single column of a table.
+#[derive(Debug, Clone)]
+pub struct SqlColumn {
+    pub name: String,
+    pub sql_type: SqlType,
+    pub nullable: bool,
+}
+
+/// A table records are stored in.
+#[derive(Debug, Clone)]
+pub struct SqlTable {
+    pub name: String,
+    /// Index of the parent table, `None` for the top level table
+    pub parent: Option<usize>,
+    /// Property of the parent record holding this table's values
+    pub property: String,
+    /// Whether the property is an array of objects rather than a single object
+    pub many: bool,
+    /// Whether rows have generated `_id` and `_parent_id` columns,
+    /// always the case for tables without columns of their own so they have at least one
+    pub keyed: bool,
+    /// Columns taken from the record, not including generated keys
+    pub columns: Vec<SqlColumn>,
+}
+
+/// Derives a table name from the schema's title, lowercased with anything but letters and digits replaced by `_`.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::sql_utils::table_name_from_schema;
+/// use serde_json::json;
+///
+/// assert_eq!(table_name_from_schema(&json!({"title": "Order Item"})), "order_item");
+/// assert_eq!(table_name_from_schema(&json!({})), "records");
+/// ```
+pub fn table_name_from_schema(schema: &Value) -> String {
+    match schema.get("title").and_then(|x| x.as_str()) {
+        Some(title) => title
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect(),
+        None => "records".to_owned(),
+    }
+}
+
+/// Maps a schema to tables. The first table holds the top level records, any others are child tables
+/// which always come after their parent.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::schema::read_schema;
+/// use pollinate::sql_utils::*;
+///
+/// let schema = read_schema("test_data/person_schema.json").unwrap();
+/// let tables = tables_from_schema(&schema, "person", SqlNesting::Tables);
+/// let names: Vec<&str> = tables.iter().map(|x| x.name.as_str()).collect();
+/// assert_eq!(names, vec!["person", "person_partner"]);
+/// ```
+pub fn tables_from_schema(schema: &Value, name: &str, nesting: SqlNesting) -> Vec<SqlTable> {
+    let mut tables = Vec::<SqlTable>::new();
+    collect_tables(schema, name, None, "", false, nesting, &mut tables);
+    tables
+}
+
+fn is_nested_object(details: &Value) -> bool {
+    details.get("type").and_then(|x| x.as_str()) == Some("object")
+        && details.get("enum").is_none()
+        && details.get("properties").is_some()
+}
+
+fn collect_tables(
+    details: &Value,
+    name: &str,
+    parent: Option<usize>,
+    property: &str,
+    many: bool,
+    nesting: SqlNesting,
+    tables: &mut Vec<SqlTable>,
+) {
+    let index = tables.len();
+    tables.push(SqlTable {
+        name: name.to_owned(),
+        parent,
+        property: property.to_owned(),
+        many,
+        keyed: nesting == SqlNesting::Tables,
+        columns: Vec::new(),
+    });
+    let properties = details.get("properties").and_then(|x| x.as_object());
+    for (property, details) in properties.into_iter().flatten() {
+        let child_name = format!("{}_{}", name, property);
+        let items = details.get("items");
+        if nesting == SqlNesting::Tables && is_nested_object(details) {
+            collect_tables(
+                details,
+                &child_name,
+                Some(index),
+                property,
+                false,
+                nesting,
+                tables,
+            );
+        } else if nesting == SqlNesting::Tables
+            && details.get("type").and_then(|x| x.as_str()) == Some("array")
+            && items.is_some_and(is_nested_object)
+        {
+            collect_tables(
+                items.unwrap(),
+                &child_name,
+                Some(index),
+                property,
+                true,
+                nesting,
+                tables,
+            );
+        } else {
+            let nullable = match details.get("type") {
+                Some(Value::Array(types)) => types.contains(&Value::from("null")),
+                _ => false,
+            };
+            tables[index].columns.push(SqlColumn {
+                name: property.to_owned(),
+                sql_type: SqlType::from_details(details),
+                nullable,
+            });
+        }
+    }
+    if tables[index].columns.is_empty() {
+        tables[index].keyed = true;
+    }
+}
+
+/// Quotes an identifier for a dialect.
+pub fn quote_identifier(name: &str, dialect: SqlDialect) -> String {
+    match dialect {
+        SqlDialect::Mysql => format!("`{}`", name.replace('`', "``")),
+        _ => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+/// Creates the `CREATE TABLE` statement for one of the tables.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::sql_utils::*;
+/// use serde_json::json;
+///
+/// let schema = json!({"properties": {"age": {"type": "integer", "minimum": 0, "maximum": 100}}});
+/// let tables = tables_from_schema(&schema, "person", SqlNesting::Json);
+/// assert_eq!(
+///     create_table_sql(&tables, 0, SqlDialect::Postgres),
+///     "CREATE TABLE \"person\" (\n  \"age\" BIGINT NOT NULL\n);\n"
+/// );
+/// ```
+pub fn create_table_sql(tables: &[SqlTable], index: usize, dialect: SqlDialect) -> String {
+    let table = &tables[index];
+    let mut definitions = Vec::<String>::new();
+    if table.keyed {
+        definitions.push(format!(
+            "{} {} PRIMARY KEY",
+            quote_identifier(ID_COLUMN, dialect),
+            SqlType::Integer.name(dialect)
+        ));
+    }
+    if let Some(parent) = table.parent.filter(|_| table.keyed) {
+        definitions.push(format!(
+            "{} {} NOT NULL REFERENCES {} ({})",
+            quote_identifier(PARENT_ID_COLUMN, dialect),
+            SqlType::Integer.name(dialect),
+            quote_identifier(&tables[parent].name, dialect),
+            quote_identifier(ID_COLUMN, dialect)
+        ));
+    }
+    for column in table.columns.iter() {
+        definitions.push(format!(
+            "{} {}{}",
+            quote_identifier(&column.name, dialect),
+            column.sql_type.name(dialect),
+            if column.nullable { "" } else { " NOT NULL" }
+        ));
+    }
+    format!(
+        "CREATE TABLE {} (\n  {}\n);\n",
+        quote_identifier(&table.name, dialect),
+        definitions.join(",\n  ")
+    )
+}
+
+/// Renders a value as an escaped SQL literal for a column of the given type.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::sql_utils::*;
+/// use serde_json::json;
+///
+/// assert_eq!(sql_literal(&json!("O'Hiatt"), SqlType::Text, SqlDialect::Postgres), "'O''Hiatt'");
+/// assert_eq!(sql_literal(&json!("a\\b"), SqlType::Text, SqlDialect::Mysql), "'a\\\\b'");
+/// assert_eq!(sql_literal(&json!(true), SqlType::Boolean, SqlDialect::Sqlite), "1");
+/// assert_eq!(sql_literal(&json!(null), SqlType::Integer, SqlDialect::Sqlite), "NULL");
+/// ```
+pub fn sql_literal(value: &Value, sql_type: SqlType, dialect: SqlDialect) -> String {
+    match (value, sql_type) {
+        (Value::Null, _) => "NULL".to_owned(),
+        (Value::Bool(x), _) if dialect == SqlDialect::Postgres => x.to_string().to_uppercase(),
+        (Value::Bool(x), _) => (*x as u8).to_string(),
+        (Value::Number(x), SqlType::Integer | SqlType::Real) => x.to_string(),
+        (Value::String(x), SqlType::Timestamp) if dialect == SqlDialect::Mysql => {
+            let x = x.trim_end_matches(['Z', 'z']).replacen(['T', 't'], " ", 1);
+            quote_string(&x, dialect)
+        }
+        (Value::String(x), SqlType::Json) => {
+            quote_string(&Value::from(x.as_str()).to_string(), dialect)
+        }
+        (Value::String(x), _) => quote_string(x, dialect),
+        (value, _) => quote_string(&value.to_string(), dialect),
+    }
+}
+
+fn quote_string(value: &str, dialect: SqlDialect) -> String {
+    let escaped = value.replace('\'', "''");
+    match dialect {
+        SqlDialect::Mysql => format!("'{}'", escaped.replace('\\', "\\\\")),
+        _ => format!("'{}'", escaped),
+    }
+}
+
+/// Splits records into rows for each table, generating keys to link child rows to their parents.
+#[derive(Debug, Clone)]
+pub struct RowSplitter {
+    pub tables: Vec<SqlTable>,
+    next_ids: Vec<u64>,
+}
+
+impl RowSplitter {
+    pub fn new(tables: Vec<SqlTable>) -> Self {
+        let next_ids = vec![1; tables.len()];
+        RowSplitter { tables, next_ids }
+    }
+
+    /// Returns every row created from a record along with the index of its table.
+    /// Generated `_id` and `_parent_id` keys come first in a row, followed by the table's columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pollinate::sql_utils::*;
+    /// use serde_json::json;
+    ///
+    /// let schema = json!({"properties": {
+    ///     "name": {"type": "string", "enum": ["Adam"]},
+    ///     "pets": {"type": "array", "maximum": 2, "items": {"type": "object", "properties": {
+    ///         "name": {"type": "string", "enum": ["Rex"]}
+    ///     }}}
+    /// }});
+    /// let mut splitter = RowSplitter::new(tables_from_schema(&schema, "person", SqlNesting::Tables));
+    /// let rows = splitter.split(&json!({"name": "Adam", "pets": [{"name": "Rex"}, {"name": "Tom"}]}));
+    /// assert_eq!(rows, vec![
+    ///     (0, vec![json!(1), json!("Adam")]),
+    ///     (1, vec![json!(1), json!(1), json!("Rex")]),
+    ///     (1, vec![json!(2), json!(1), json!("Tom")]),
+    /// ]);
+    /// ```
+    pub fn split(&mut self, record: &Value) -> Vec<(usize, Vec<Value>)> {
+        let mut rows = Vec::new();
+        self.split_into(0, record, None, &mut rows);
+        rows
+    }
+
+    fn split_into(
+        &mut self,
+        index: usize,
+        record: &Value,
+        parent_id: Option<u64>,
+        rows: &mut Vec<(usize, Vec<Value>)>,
+    ) {
+        let table = &self.tables[index];
+        let mut row = Vec::<Value>::with_capacity(table.columns.len() + 2);
+        let id = self.next_ids[index];
+        if table.keyed {
+            self.next_ids[index] += 1;
+            row.push(Value::from(id));
+            if let Some(parent_id) = parent_id {
+                row.push(Value::from(parent_id));
+            }
+        }
+        for column in table.columns.iter() {
+            row.push(record.get(&column.name).cloned().unwrap_or(Value::Null));
+        }
+        rows.push((index, row));
+
+        let children: Vec<usize> = (0..self.tables.len())
+            .filter(|x| self.tables[*x].parent == Some(index))
+            .collect();
+        for child in children {
+            match (
+                record.get(&self.tables[child].property),
+                self.tables[child].many,
+            ) {
+                (Some(Value::Array(values)), true) => {
+                    for value in values {
+                        self.split_into(child, value, Some(id), rows);
+                    }
+                }
+                (Some(value @ Value::Object(_)), false) => {
+                    self.split_into(child, value, Some(id), rows)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Names of every column of a table, including generated keys.
+    pub fn column_names(&self, index: usize) -> Vec<String> {
+        let table = &self.tables[index];
+        let mut names = Vec::<String>::new();
+        if table.keyed {
+            names.push(ID_COLUMN.to_owned());
+            if table.parent.is_some() {
+                names.push(PARENT_ID_COLUMN.to_owned());
+            }
+        }
+        names.extend(table.columns.iter().map(|x| x.name.clone()));
+        names
+    }
+
+    /// Types of every column of a table, including generated keys.
+    pub fn column_types(&self, index: usize) -> Vec<SqlType> {
+        let table = &self.tables[index];
+        let keys = self.column_names(index).len() - table.columns.len();
+        let mut types = vec![SqlType::Integer; keys];
+        types.extend(table.columns.iter().map(|x| x.sql_type));
+        types
+    }
+}
+
+/// Writes records as a SQL script, `CREATE TABLE` statements followed by batched `INSERT` statements
+/// inside a single transaction.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::RecordWriter;
+/// use pollinate::sql_utils::*;
+/// use serde_json::json;
+///
+/// let schema = json!({"properties": {"name": {"type": "string", "enum": ["Adam"]}}});
+/// let tables = tables_from_schema(&schema, "person", SqlNesting::Json);
+/// let mut output = Vec::new();
+/// let mut writer = SqlWriter::new(&mut output, tables, SqlDialect::Sqlite);
+/// writer.write_record(&json!({"name": "Adam"})).unwrap();
+/// writer.write_record(&json!({"name": "Ted"})).unwrap();
+/// writer.finish().unwrap();
+/// let output = String::from_utf8(output).unwrap();
+/// assert!(output.contains("INSERT INTO \"person\" (\"name\") VALUES\n  ('Adam'),\n  ('Ted');"));
+/// ```
+pub struct SqlWriter<W: Write> {
+    writer: W,
+    splitter: RowSplitter,
+    dialect: SqlDialect,
+    pending: Vec<Vec<Vec<Value>>>,
+    started: bool,
+}
+
+impl<W: Write> SqlWriter<W> {
+    pub fn new(writer: W, tables: Vec<SqlTable>, dialect: SqlDialect) -> Self {
+        let pending = vec![Vec::new(); tables.len()];
+        SqlWriter {
+            writer,
+            splitter: RowSplitter::new(tables),
+            dialect,
+            pending,
+            started: false,
+        }
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        if !self.started {
+            self.started = true;
+            for index in 0..self.splitter.tables.len() {
+                let statement = create_table_sql(&self.splitter.tables, index, self.dialect);
+                writeln!(self.writer, "{}", statement)?;
+            }
+            let begin = match self.dialect {
+                SqlDialect::Mysql => "START TRANSACTION;",
+                _ => "BEGIN;",
+            };
+            writeln!(self.writer, "{}", begin)?;
+        }
+        Ok(())
+    }
+
+    /// Writes every pending row, parents before children so foreign keys are satisfied.
+    fn flush_rows(&mut self) -> io::Result<()> {
+        for index in 0..self.pending.len() {
+            if self.pending[index].is_empty() {
+                continue;
+            }
+            let names: Vec<String> = self
+                .splitter
+                .column_names(index)
+                .iter()
+                .map(|x| quote_identifier(x, self.dialect))
+                .collect();
+            let types = self.splitter.column_types(index);
+            let rows: Vec<String> = self.pending[index]
+                .drain(..)
+                .map(|row| {
+                    let literals: Vec<String> = row
+                        .iter()
+                        .zip(types.iter())
+                        .map(|(value, sql_type)| sql_literal(value, *sql_type, self.dialect))
+                        .collect();
+                    format!("({})", literals.join(", "))
+                })
+                .collect();
+            writeln!(
+                self.writer,
+                "INSERT INTO {} ({}) VALUES\n  {};",
+                quote_identifier(&self.splitter.tables[index].name, self.dialect),
+                names.join(", "),
+                rows.join(",\n  ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> RecordWriter for SqlWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        self.start()?;
+        for (index, row) in self.splitter.split(record) {
+            self.pending[index].push(row);
+        }
+        if self.pending.iter().any(|x| x.len() >= SQL_BATCH_SIZE) {
+            self.flush_rows()?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.start()?;
+        self.flush_rows()?;
+        writeln!(self.writer, "COMMIT;")?;
+        self.writer.flush()
+    }
+}
+
+/// Converts a value to the type SQLite stores it as, arrays and objects are stored as JSON text.
+fn sqlite_value(value: &Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value as Sqlite;
+    match value {
+        Value::Null => Sqlite::Null,
+        Value::Bool(x) => Sqlite::Integer(*x as i64),
+        Value::Number(x) => match x.as_i64() {
+            Some(x) => Sqlite::Integer(x),
+            None => Sqlite::Real(x.as_f64().unwrap()),
+        },
+        Value::String(x) => Sqlite::Text(x.clone()),
+        value => Sqlite::Text(value.to_string()),
+    }
+}
+
+/// Inserts records directly into a new SQLite database inside a single transaction.
+/// Nested objects and arrays of objects are stored in child tables.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::RecordWriter;
+/// use pollinate::sql_utils::*;
+/// use serde_json::json;
+///
+/// let schema = json!({"properties": {"name": {"type": "string", "enum": ["Adam"]}}});
+/// let tables = tables_from_schema(&schema, "person", SqlNesting::Tables);
+/// let mut writer = SqliteWriter::open(":memory:", tables).unwrap();
+/// writer.write_record(&json!({"name": "Adam"})).unwrap();
+/// writer.finish().unwrap();
+/// ```
+pub struct SqliteWriter {
+    connection: Connection,
+    splitter: RowSplitter,
+    inserts: Vec<String>,
+}
+
+impl SqliteWriter {
+    /// Creates a database at the path, replacing any existing file, and creates every table.
+    pub fn open(file: &str, tables: Vec<SqlTable>) -> io::Result<Self> {
+        if file != ":memory:" && std::path::Path::new(file).exists() {
+            std::fs::remove_file(file)?;
+        }
+        let connection = Connection::open(file).map_err(io::Error::other)?;
+        let splitter = RowSplitter::new(tables);
+        let mut inserts = Vec::<String>::new();
+        let mut statements = String::from("BEGIN;\n");
+        for index in 0..splitter.tables.len() {
+            statements.push_str(&create_table_sql(
+                &splitter.tables,
+                index,
+                SqlDialect::Sqlite,
+            ));
+            let names = splitter.column_names(index);
+            let quoted: Vec<String> = names
+                .iter()
+                .map(|x| quote_identifier(x, SqlDialect::Sqlite))
+                .collect();
+            inserts.push(format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                quote_identifier(&splitter.tables[index].name, SqlDialect::Sqlite),
+                quoted.join(", "),
+                vec!["?"; names.len()].join(", ")
+            ));
+        }
+        connection
+            .execute_batch(&statements)
+            .map_err(io::Error::other)?;
+        Ok(SqliteWriter {
+            connection,
+            splitter,
+            inserts,
+        })
+    }
+}
+
+impl RecordWriter for SqliteWriter {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        for (index, row) in self.splitter.split(record) {
+            let mut statement = self
+                .connection
+                .prepare_cached(&self.inserts[index])
+                .map_err(io::Error::other)?;
+            statement
+                .execute(params_from_iter(row.iter().map(sqlite_value)))
+                .map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if !self.connection.is_autocommit() {
+            self.connection
+                .execute_batch("COMMIT;")
+                .map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+//! # Pollinate Template Module
+//!
+//! The `template_utils` module renders generated records through a Handlebars template, so a schema can drive
+//! any textual format such as fixed-width files or XML-like payloads.
+//!
+//! ## Overview
+//!
+//! A template file is split into sections by lines holding only a section comment:
+//!
+//! ```handlebars
+//! {{!-- header --}}
+//! <people count="{{count}}">
+//! {{!-- record --}}
+//!   <person name="{{xml firstName}}">{{#each skills}}<skill>{{this}}</skill>{{/each}}</person>
+//! {{!-- footer --}}
+//! </people>
+//! ```
+//!
+//! The record section is rendered once for every record with the record as its context, while the header and
+//! footer are rendered once with the number of records as `count`. Text before the first section comment
+//! belongs to the record section, so a template without any is rendered for each record. Output is not escaped,
+//! the helpers below format values:
+//!
+//! - `pad value width fill=" "`: Left aligns a value in a field of `width` characters, cutting off longer values.
+//! - `pad_left value width fill=" "`: Right aligns a value in a field of `width` characters.
+//! - `fixed value digits`: Writes a number with a fixed number of decimal places.
+//! - `upper value`, `lower value`: Changes the case of a value.
+//! - `xml value`: Escapes `&`, `<`, `>`, `"` and `'`.
+//! - `json value`: Writes a value as JSON.
+//!
+//! ## Functions
+//!
+//! - `read_text_template`: Reads and compiles a template file.
+//!
+
+use handlebars::{handlebars_helper, no_escape, Handlebars};
+use serde_json::{json, Value};
+use std::io;
+use std::io::Write;
+
+use crate::json_utils::{read_input, RecordWriter};
+
+const HEADER: &str = "header";
+const RECORD: &str = "record";
+const FOOTER: &str = "footer";
+
+/// Text of a value as it is written by the helpers, with strings unquoted and null left empty.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(x) => x.clone(),
+        Value::Null => String::new(),
+        x => x.to_string(),
+    }
+}
+
+/// Fits text into a field of `width` characters, filling on the left or right side.
+fn fit(value: &Value, width: u64, fill: &str, left: bool) -> String {
+    let value: String = text(value).chars().take(width as usize).collect();
+    let missing = width as usize - value.chars().count();
+    let padding: String = fill.chars().cycle().take(missing).collect();
+    match left {
+        true => padding + &value,
+        false => value + &padding,
+    }
+}
+
+handlebars_helper!(pad: |value: Json, width: u64, { fill: str = " " }| fit(value, width, fill, false));
+handlebars_helper!(pad_left: |value: Json, width: u64, { fill: str = " " }| fit(value, width, fill, true));
+handlebars_helper!(fixed: |value: f64, digits: u64| format!("{:.*}", digits as usize, value));
+handlebars_helper!(upper: |value: Json| text(value).to_uppercase());
+handlebars_helper!(lower: |value: Json| text(value).to_lowercase());
+handlebars_helper!(json: |value: Json| value.to_string());
+handlebars_helper!(xml: |value: Json| {
+    text(value)
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+});
+
+/// A compiled template with header, record and footer sections.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::template_utils::TextTemplate;
+/// use serde_json::json;
+///
+/// let template = TextTemplate::parse("{{pad name 6 fill=\".\"}}|{{pad_left age 3 fill=\"0\"}}\n").unwrap();
+/// assert_eq!(template.render_record(&json!({"name": "Ann", "age": 7})).unwrap(), "Ann...|007\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextTemplate {
+    registry: Handlebars<'static>,
+}
+
+impl TextTemplate {
+    /// Splits a template into its sections and compiles them, failing on invalid Handlebars syntax.
+    pub fn parse(source: &str) -> io::Result<Self> {
+        let mut sections = [String::new(), String::new(), String::new()];
+        let mut current = 1;
+        for line in source.split_inclusive('\n') {
+            let marker = line
+                .trim()
+                .strip_prefix("{{!--")
+                .and_then(|x| x.strip_suffix("--}}"));
+            match marker.map(|x| x.trim()) {
+                Some(HEADER) => current = 0,
+                Some(RECORD) => current = 1,
+                Some(FOOTER) => current = 2,
+                _ => sections[current].push_str(line),
+            }
+        }
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(no_escape);
+        registry.register_helper("pad", Box::new(pad));
+        registry.register_helper("pad_left", Box::new(pad_left));
+        registry.register_helper("fixed", Box::new(fixed));
+        registry.register_helper("upper", Box::new(upper));
+        registry.register_helper("lower", Box::new(lower));
+        registry.register_helper("json", Box::new(json));
+        registry.register_helper("xml", Box::new(xml));
+        for (name, section) in [HEADER, RECORD, FOOTER].iter().zip(sections) {
+            registry
+                .register_template_string(name, section)
+                .map_err(|x| io::Error::new(io::ErrorKind::InvalidInput, x))?;
+        }
+        Ok(TextTemplate { registry })
+    }
+
+    /// Renders the header, which is given the number of records as `count`.
+    pub fn render_header(&self, count: u32) -> io::Result<String> {
+        self.render(HEADER, &json!({ "count": count }))
+    }
+
+    /// Renders a single record.
+    pub fn render_record(&self, record: &Value) -> io::Result<String> {
+        self.render(RECORD, record)
+    }
+
+    /// Renders the footer, which is given the number of records as `count`.
+    pub fn render_footer(&self, count: u32) -> io::Result<String> {
+        self.render(FOOTER, &json!({ "count": count }))
+    }
+
+    fn render(&self, section: &str, data: &Value) -> io::Result<String> {
+        self.registry
+            .render(section, data)
+            .map_err(io::Error::other)
+    }
+}
+
+/// Reads a template file, or a template from standard input when the path is `-`.
+pub fn read_text_template(template_path: &str) -> io::Result<TextTemplate> {
+    TextTemplate::parse(&read_input(template_path)?)
+}
+
+/// Writes each record rendered through a template, between the header and footer.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::RecordWriter;
+/// use pollinate::template_utils::{TemplateWriter, TextTemplate};
+/// use serde_json::json;
+///
+/// let source = "{{!-- header --}}\n<people>\n{{!-- record --}}\n<person>{{xml name}}</person>\n{{!-- footer --}}\n</people>\n";
+/// let mut output = Vec::new();
+/// let mut writer = TemplateWriter::new(&mut output, TextTemplate::parse(source).unwrap(), 1);
+/// writer.write_record(&json!({"name": "Tom & Jerry"})).unwrap();
+/// writer.finish().unwrap();
+/// drop(writer);
+/// assert_eq!(String::from_utf8(output).unwrap(), "<people>\n<person>Tom &amp; Jerry</person>\n</people>\n");
+/// ```
+pub struct TemplateWriter<W: Write> {
+    writer: W,
+    template: TextTemplate,
+    count: u32,
+    header_written: bool,
+}
+
+impl<W: Write> TemplateWriter<W> {
+    pub fn new(writer: W, template: TextTemplate, count: u32) -> Self {
+        TemplateWriter {
+            writer,
+            template,
+            count,
+            header_written: false,
+        }
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if !self.header_written {
+            self.header_written = true;
+            let header = self.template.render_header(self.count)?;
+            self.writer.write_all(header.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> RecordWriter for TemplateWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        self.write_header()?;
+        let rendered = self.template.render_record(record)?;
+        self.writer.write_all(rendered.as_bytes())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.write_header()?;
+        let footer = self.template.render_footer(self.count)?;
+        self.writer.write_all(footer.as_bytes())?;
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,401 @@
+//! # Pollinate Typed Module
+//!
+//! The `typed` module generates records straight into Rust types and derives pollinate schemas from standard
+//! JSON Schemas, such as the ones `schemars` writes for a Rust type.
+//!
+//! ## Overview
+//!
+//! Any type implementing `serde::Deserialize` can be generated from a `Template`, so tests can work with their own
+//! structs instead of `serde_json::Value`. With the `schemars` feature the schema can come from the struct too,
+//! making it the source of truth for both the schema and the generated values.
+//!
+//! A standard JSON Schema is converted by inlining `$ref` definitions, turning `anyOf` into `oneOf` or a nullable
+//! type, `const` into a single value `enum`, and filling in the bounds pollinate needs: integers get the range of
+//! their `format`, such as `int32` or `uint8`, and arrays get a `maximum` length. Tuples and schemas accepting
+//! any value can't be generated. A recursive type is only supported where it is optional, in which case it is always null.
+//! Schemas that can't be converted are reported as an `InvalidInput` error naming the part that isn't supported.
+//!
+//! ## Functions
+//!
+//! - `generate`: Generates a record from a template and deserializes it into a Rust type.
+//! - `schema_from_json_schema`: Converts a standard JSON Schema to a pollinate schema.
+//! - `schema_for`: Derives a pollinate schema from a type implementing `schemars::JsonSchema`.
+//! - `template_for`: Builds a template for a type implementing `schemars::JsonSchema`.
+//!
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use std::io;
+
+use crate::schema::Template;
+
+/// Most items generated for arrays without a `maxItems`.
+const DEFAULT_MAX_ITEMS: u64 = 5;
+
+/// Keywords of a standard JSON Schema that have no meaning for a single generated value.
+const DROPPED_KEYWORDS: &[&str] = &["$schema", "$id", "$defs", "definitions", "$ref"];
+
+/// Generates a record and deserializes it into `T`, failing when the record doesn't fit the type.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::schema::Template;
+/// use serde::Deserialize;
+/// use serde_json::json;
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: u8,
+/// }
+///
+/// let template = Template::new(&json!({"type": "object", "properties": {
+///     "name": {"type": "string", "maxLength": 8},
+///     "age": {"type": "integer", "minimum": 18, "maximum": 99}
+/// }})).unwrap();
+/// let user: User = pollinate::generate(&template).unwrap();
+/// assert!((18..=99).contains(&user.age));
+/// assert!(user.name.chars().count() <= 8);
+/// ```
+pub fn generate<T: DeserializeOwned>(template: &Template) -> serde_json::Result<T> {
+    serde_json::from_value(template.generate())
+}
+
+/// Smallest and largest values of an integer `format`, limited to what pollinate can generate.
+fn integer_bounds(format: Option<&str>) -> (i64, i64) {
+    match format {
+        Some("int8") => (i8::MIN.into(), i8::MAX.into()),
+        Some("int16") => (i16::MIN.into(), i16::MAX.into()),
+        Some("int32") => (i32::MIN.into(), i32::MAX.into()),
+        Some("uint8") => (0, u8::MAX.into()),
+        Some("uint16") => (0, u16::MAX.into()),
+        Some("uint32") => (0, u32::MAX.into()),
+        Some("uint" | "uint64" | "uint128") => (0, i64::MAX),
+        _ => (i64::MIN, i64::MAX),
+    }
+}
+
+/// Name of the JSON type of a value, used for an `enum` or `const` without a `type`.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(x) if x.is_i64() || x.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Error for a part of a schema that can't be converted.
+fn unsupported(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Returns the name of the definition a `$ref` such as `#/$defs/User` points to.
+fn reference_name(reference: &str) -> io::Result<&str> {
+    reference
+        .strip_prefix("#/$defs/")
+        .or_else(|| reference.strip_prefix("#/definitions/"))
+        .ok_or_else(|| {
+            unsupported(format!(
+                "Only references to $defs are supported, found {}",
+                reference
+            ))
+        })
+}
+
+/// True when a schema is a `$ref` to a definition that is already being converted.
+fn is_recursive(schema: &Value, expanding: &[String]) -> bool {
+    schema
+        .get("$ref")
+        .and_then(|x| x.as_str())
+        .and_then(|x| reference_name(x).ok())
+        .is_some_and(|x| expanding.iter().any(|name| name == x))
+}
+
+/// Returns the schemas listed under a keyword such as `anyOf`.
+fn schema_list<'a>(schema: &Value, list: &'a Value) -> io::Result<&'a [Value]> {
+    list.as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| unsupported(format!("Expected a list of schemas in {}", schema)))
+}
+
+/// Converts `anyOf` or `oneOf` options, using a nullable type when they are a single type and `null`.
+/// Optional references to a type that is being converted are left out, so recursive types end with null.
+fn union_schema(
+    options: &[Value],
+    definitions: &Map<String, Value>,
+    expanding: &mut Vec<String>,
+) -> io::Result<Value> {
+    let nullable = options
+        .iter()
+        .any(|x| x.get("type") == Some(&json!("null")));
+    let other: Vec<&Value> = options
+        .iter()
+        .filter(|x| x.get("type") != Some(&json!("null")))
+        .filter(|x| !(nullable && is_recursive(x, expanding)))
+        .collect();
+    if let [option] = other[..] {
+        let mut converted = convert_schema(option, definitions, expanding)?;
+        if !nullable {
+            return Ok(converted);
+        }
+        if let Some(Value::String(type_name)) = converted.get("type").cloned() {
+            if converted.get("oneOf").is_none() {
+                converted["type"] = json!([type_name, "null"]);
+                return Ok(converted);
+            }
+        }
+        return Ok(json!({"oneOf": [converted, {"type": "null"}]}));
+    }
+    let mut options = other
+        .into_iter()
+        .map(|x| convert_schema(x, definitions, expanding))
+        .collect::<io::Result<Vec<Value>>>()?;
+    if nullable {
+        options.push(json!({"type": "null"}));
+    }
+    Ok(match options.len() {
+        1 => options.pop().unwrap(),
+        _ => json!({"oneOf": options}),
+    })
+}
+
+fn convert_schema(
+    schema: &Value,
+    definitions: &Map<String, Value>,
+    expanding: &mut Vec<String>,
+) -> io::Result<Value> {
+    let Some(map) = schema.as_object() else {
+        return Err(unsupported(format!(
+            "Schemas accepting any value can't be generated, found {}",
+            schema
+        )));
+    };
+    if let Some(reference) = map.get("$ref").and_then(|x| x.as_str()) {
+        let name = reference_name(reference)?;
+        if expanding.iter().any(|x| x == name) {
+            return Err(unsupported(format!(
+                "Recursive type {} is only supported where it is optional",
+                name
+            )));
+        }
+        let definition = definitions
+            .get(name)
+            .ok_or_else(|| unsupported(format!("Unknown definition {}", name)))?;
+        expanding.push(name.to_owned());
+        let converted = convert_schema(definition, definitions, expanding);
+        expanding.pop();
+        let mut converted = converted?;
+        // keywords next to a reference, such as a description, refine the definition
+        for (key, value) in map.iter() {
+            if !DROPPED_KEYWORDS.contains(&key.as_str()) {
+                converted[key] = value.clone();
+            }
+        }
+        return Ok(converted);
+    }
+    if let Some(options) = map.get("anyOf").or(map.get("oneOf")) {
+        return union_schema(schema_list(schema, options)?, definitions, expanding);
+    }
+    if let Some(parts) = map.get("allOf") {
+        return match schema_list(schema, parts)? {
+            [part] => convert_schema(part, definitions, expanding),
+            _ => Err(unsupported(format!(
+                "allOf with several schemas is not supported, found {}",
+                schema
+            ))),
+        };
+    }
+    if map.contains_key("prefixItems") {
+        return Err(unsupported(format!(
+            "Tuples are not supported, found {}",
+            schema
+        )));
+    }
+    let mut converted: Map<String, Value> = map
+        .iter()
+        .filter(|(key, _)| !DROPPED_KEYWORDS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if let Some(value) = converted.remove("const") {
+        converted.insert("enum".to_owned(), json!([value]));
+    }
+    if !converted.contains_key("type") {
+        let Some(first) = converted.get("enum").and_then(|x| x.get(0)) else {
+            return Err(unsupported(format!(
+                "Schemas without a type can't be generated, found {}",
+                schema
+            )));
+        };
+        converted.insert("type".to_owned(), json!(type_name(first)));
+    }
+    let types: Vec<String> = match &converted["type"] {
+        Value::Array(types) => types
+            .iter()
+            .filter_map(|x| x.as_str())
+            .map(String::from)
+            .collect(),
+        x => x.as_str().map(String::from).into_iter().collect(),
+    };
+    let has_type = |name: &str| types.iter().any(|x| x == name);
+    if has_type("integer") && !converted.contains_key("enum") {
+        let (min, max) = integer_bounds(converted.get("format").and_then(|x| x.as_str()));
+        let bound = |key: &str, default: i64| {
+            converted
+                .get(key)
+                .and_then(|x| x.as_i64())
+                .unwrap_or(default)
+        };
+        let (min, max) = (bound("minimum", min), bound("maximum", max));
+        converted.insert("minimum".to_owned(), json!(min));
+        converted.insert("maximum".to_owned(), json!(max));
+    }
+    if has_type("array") {
+        let Some(items) = converted.get("items") else {
+            return Err(unsupported(format!(
+                "Arrays without items can't be generated, found {}",
+                schema
+            )));
+        };
+        let items = convert_schema(items, definitions, expanding)?;
+        let length = |key: &str| {
+            converted.get(key).map(|x| {
+                x.as_u64().ok_or_else(|| {
+                    unsupported(format!("{} must be a whole number, found {}", key, x))
+                })
+            })
+        };
+        let min = length("minItems").transpose()?.unwrap_or(0);
+        let max = length("maxItems")
+            .transpose()?
+            .unwrap_or(DEFAULT_MAX_ITEMS.max(min));
+        converted.insert("items".to_owned(), items);
+        converted.insert("minimum".to_owned(), json!(min));
+        converted.insert("maximum".to_owned(), json!(max));
+    }
+    if has_type("object") {
+        if let Some(Value::Object(additional)) = converted.get("additionalProperties") {
+            let additional =
+                convert_schema(&Value::Object(additional.clone()), definitions, expanding)?;
+            converted.insert("additionalProperties".to_owned(), additional);
+        }
+        let properties: Map<String, Value> = converted
+            .get("properties")
+            .and_then(|x| x.as_object())
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| Ok((key.clone(), convert_schema(value, definitions, expanding)?)))
+            .collect::<io::Result<_>>()?;
+        let map = converted
+            .get("additionalProperties")
+            .is_some_and(|x| x.is_object());
+        if !properties.is_empty() || !map {
+            converted.insert("properties".to_owned(), Value::Object(properties));
+        }
+    }
+    Ok(Value::Object(converted))
+}
+
+/// Converts a standard JSON Schema, such as one written by `schemars`, to a pollinate schema.
+/// The top level type must be an object with properties.
+/// Fails with an `InvalidInput` error for schemas that can't be generated, such as tuples.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::typed::schema_from_json_schema;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "title": "User",
+///     "type": "object",
+///     "properties": {
+///         "id": {"type": "integer", "format": "uint32", "minimum": 0},
+///         "tags": {"type": "array", "items": {"type": "string"}},
+///         "plan": {"anyOf": [{"$ref": "#/$defs/Plan"}, {"type": "null"}]}
+///     },
+///     "$defs": {"Plan": {"type": "string", "enum": ["free", "pro"]}}
+/// });
+/// let schema = schema_from_json_schema(&schema).unwrap();
+/// assert_eq!(schema["properties"]["id"]["maximum"], json!(u32::MAX));
+/// assert_eq!(schema["properties"]["tags"]["maximum"], json!(5));
+/// assert_eq!(schema["properties"]["plan"], json!({"type": ["string", "null"], "enum": ["free", "pro"]}));
+///
+/// let tuple = json!({"type": "object", "properties": {"point": {"type": "array", "prefixItems": [{"type": "number"}]}}});
+/// assert!(schema_from_json_schema(&tuple).is_err());
+/// ```
+pub fn schema_from_json_schema(schema: &Value) -> io::Result<Value> {
+    let empty = Map::new();
+    let definitions = schema
+        .get("$defs")
+        .or(schema.get("definitions"))
+        .and_then(|x| x.as_object())
+        .unwrap_or(&empty);
+    let converted = convert_schema(schema, definitions, &mut Vec::new())?;
+    if converted.get("properties").is_none() {
+        return Err(unsupported(
+            "Schemas must have an object with properties at the top level".to_owned(),
+        ));
+    }
+    Ok(converted)
+}
+
+/// Derives a pollinate schema from a type implementing `schemars::JsonSchema`.
+/// Fails like `schema_from_json_schema` for types that can't be generated, such as tuples.
+///
+/// # Examples
+///
+/// ```
+/// use schemars::JsonSchema;
+/// use serde_json::json;
+///
+/// #[derive(JsonSchema)]
+/// struct User {
+///     name: String,
+///     age: u8,
+///     nickname: Option<String>,
+/// }
+///
+/// let schema = pollinate::typed::schema_for::<User>().unwrap();
+/// assert_eq!(schema["title"], json!("User"));
+/// assert_eq!(schema["properties"]["age"]["maximum"], json!(255));
+/// assert_eq!(schema["properties"]["nickname"]["type"], json!(["string", "null"]));
+/// ```
+#[cfg(feature = "schemars")]
+pub fn schema_for<T: schemars::JsonSchema>() -> io::Result<Value> {
+    schema_from_json_schema(&schemars::schema_for!(T).to_value())
+}
+
+/// Builds a template for a type implementing `schemars::JsonSchema`, so it can be generated with `generate`.
+///
+/// # Examples
+///
+/// ```
+/// use schemars::JsonSchema;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// enum Plan {
+///     Free,
+///     Pro,
+/// }
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// struct User {
+///     id: u32,
+///     plan: Plan,
+///     tags: Vec<String>,
+/// }
+///
+/// let template = pollinate::typed::template_for::<User>().unwrap();
+/// let user: User = pollinate::generate(&template).unwrap();
+/// assert!(user.tags.len() <= 5);
+/// ```
+#[cfg(feature = "schemars")]
+pub fn template_for<T: schemars::JsonSchema>() -> io::Result<Template<'static>> {
+    Template::new(&schema_for::<T>()?)
+}
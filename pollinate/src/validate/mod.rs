@@ -0,0 +1,562 @@
+//! # Pollinate Validate Module
+//!
+//! The `validate` module checks JSON values against the schemas pollinate generates from.
+//!
+//! ## Overview
+//!
+//! Every keyword pollinate supports is checked: `type`, including lists such as `["string", "null"]`, `enum`,
+//! `minimum` and `maximum`, `minLength` and `maxLength`, the `email`, `date-time`, `date` and `uuid` formats,
+//! `items` along with the array length given by `minimum` and `maximum` (or `minItems` and `maxItems`),
+//! `properties`, `required`, `additionalProperties`, `minProperties` and `maxProperties`, `oneOf`,
+//! `x-sequence` and `x-unique`. Unknown keywords and formats are ignored.
+//!
+//! A `Validator` remembers the values it has seen so `x-unique` is checked across every record it validates.
+//! Errors name the offending value with a JSONPath such as `$.partner.skills[2]`.
+//!
+//! ## Functions
+//!
+//! - `validate`: Validates a single value against a schema.
+//!
+
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io;
+
+use crate::default_values::StringFormat;
+use crate::json_utils::RecordWriter;
+
+/// A value that doesn't conform to its schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// JSONPath of the value, `$` for the root
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Values seen so far for every schema marked `x-unique`, keyed by the schema's location.
+type Seen = HashMap<String, HashSet<String>>;
+
+/// Validates values against a schema, checking `x-unique` across every value validated.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::validate::Validator;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {"id": {"type": "integer", "x-unique": true}}});
+/// let mut validator = Validator::new(&schema);
+/// assert!(validator.validate(&json!({"id": 1})).is_empty());
+/// let errors = validator.validate(&json!({"id": 1}));
+/// assert_eq!(errors[0].to_string(), "$.id: 1 is repeated");
+/// ```
+pub struct Validator {
+    schema: Value,
+    seen: Seen,
+}
+
+impl Validator {
+    pub fn new(schema: &Value) -> Self {
+        Validator {
+            schema: schema.clone(),
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Validates a value, returning every error found.
+    pub fn validate(&mut self, value: &Value) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        validate_node(
+            value,
+            &self.schema,
+            "$",
+            "#",
+            &mut errors,
+            Some(&mut self.seen),
+        );
+        errors
+    }
+}
+
+/// Validates a single value against a schema, returning every error found.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::validate::validate;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "array", "maximum": 2, "items": {"type": "string", "format": "date"}});
+/// assert!(validate(&json!(["2024-02-29"]), &schema).is_empty());
+///
+/// let errors = validate(&json!(["2023-02-29", "x", 1]), &schema);
+/// let errors: Vec<String> = errors.iter().map(|x| x.to_string()).collect();
+/// assert_eq!(errors, vec![
+///     "$: expected at most 2 items, found 3",
+///     "$[0]: \"2023-02-29\" is not a valid date",
+///     "$[1]: \"x\" is not a valid date",
+///     "$[2]: expected string, found integer",
+/// ]);
+/// ```
+pub fn validate(value: &Value, schema: &Value) -> Vec<ValidationError> {
+    Validator::new(schema).validate(value)
+}
+
+fn error(errors: &mut Vec<ValidationError>, path: &str, message: String) {
+    errors.push(ValidationError {
+        path: path.to_string(),
+        message,
+    });
+}
+
+/// Name of the JSON Schema type of a value, `integer` for numbers without a fraction.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(x) if x.is_i64() || x.is_u64() => "integer",
+        Value::Number(x) if x.as_f64().unwrap().fract() == 0.0 => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(value: &Value, expected: &str) -> bool {
+    let actual = type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+/// Equality that treats numbers with the same value as equal, such as `1` and `1.0`.
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_i64(), y.as_i64()) {
+            (Some(x), Some(y)) => x == y,
+            _ => x.as_f64() == y.as_f64(),
+        },
+        _ => a == b,
+    }
+}
+
+/// Compares a number with a bound, exactly when both are integers.
+fn compare(value: &Value, bound: &Value) -> Option<std::cmp::Ordering> {
+    match (value.as_i64(), bound.as_i64()) {
+        (Some(x), Some(y)) => Some(x.cmp(&y)),
+        _ => value.as_f64()?.partial_cmp(&bound.as_f64()?),
+    }
+}
+
+fn is_leap_year(year: u32) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+/// Parses digits of an exact length.
+fn digits(text: &str) -> Option<u32> {
+    match text.bytes().all(|x| x.is_ascii_digit()) && !text.is_empty() {
+        true => text.parse().ok(),
+        false => None,
+    }
+}
+
+fn is_date(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' || !text.is_ascii() {
+        return false;
+    }
+    let (Some(year), Some(month), Some(day)) =
+        (digits(&text[..4]), digits(&text[5..7]), digits(&text[8..]))
+    else {
+        return false;
+    };
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+/// Checks an RFC 3339 timestamp such as `2021-03-04T05:06:07.5+01:00`.
+fn is_date_time(text: &str) -> bool {
+    if !text.is_ascii() || text.len() < 20 {
+        return false;
+    }
+    let (date, time) = text.split_at(10);
+    let Some(time) = time.strip_prefix(['T', 't']) else {
+        return false;
+    };
+    let valid_time = |time: &str, max_second: u32| {
+        let parts: Vec<&str> = time.split(':').collect();
+        match parts[..] {
+            [hour, minute, second] if hour.len() == 2 && minute.len() == 2 && second.len() == 2 => {
+                digits(hour).is_some_and(|x| x < 24)
+                    && digits(minute).is_some_and(|x| x < 60)
+                    && digits(second).is_some_and(|x| x <= max_second)
+            }
+            [hour, minute] if hour.len() == 2 && minute.len() == 2 => {
+                digits(hour).is_some_and(|x| x < 24) && digits(minute).is_some_and(|x| x < 60)
+            }
+            _ => false,
+        }
+    };
+    let (time, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => return false,
+    };
+    let offset_valid = match offset {
+        "Z" | "z" => true,
+        x => x.len() == 6 && x[1..].contains(':') && valid_time(&x[1..], 0),
+    };
+    let (time, fraction) = time.split_once('.').unwrap_or((time, "0"));
+    is_date(date)
+        && offset_valid
+        && time.len() == 8
+        && valid_time(time, 60)
+        && digits(fraction).is_some()
+}
+
+fn is_email(text: &str) -> bool {
+    match text.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !domain.contains('@')
+                && !text.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+fn is_uuid(text: &str) -> bool {
+    text.len() == 36
+        && text.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+fn validate_string(text: &str, schema: &Value, path: &str, errors: &mut Vec<ValidationError>) {
+    let length = text.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(|x| x.as_u64()) {
+        if length < min {
+            error(
+                errors,
+                path,
+                format!("expected at least {} characters, found {}", min, length),
+            );
+        }
+    }
+    if let Some(max) = schema.get("maxLength").and_then(|x| x.as_u64()) {
+        if length > max {
+            error(
+                errors,
+                path,
+                format!("expected at most {} characters, found {}", max, length),
+            );
+        }
+    }
+    let format = schema.get("format").and_then(|x| x.as_str());
+    let valid = match format.and_then(StringFormat::from_name) {
+        Some(StringFormat::Email) => is_email(text),
+        Some(StringFormat::DateTime) => is_date_time(text),
+        Some(StringFormat::Date) => is_date(text),
+        Some(StringFormat::Uuid) => is_uuid(text),
+        None => true,
+    };
+    if !valid {
+        let message = format!("{:?} is not a valid {}", text, format.unwrap());
+        error(errors, path, message);
+    }
+}
+
+fn validate_number(value: &Value, schema: &Value, path: &str, errors: &mut Vec<ValidationError>) {
+    if let Some(min) = schema.get("minimum") {
+        if compare(value, min) == Some(std::cmp::Ordering::Less) {
+            error(
+                errors,
+                path,
+                format!("{} is less than the minimum {}", value, min),
+            );
+        }
+    }
+    if let Some(max) = schema.get("maximum") {
+        if compare(value, max) == Some(std::cmp::Ordering::Greater) {
+            error(
+                errors,
+                path,
+                format!("{} is greater than the maximum {}", value, max),
+            );
+        }
+    }
+}
+
+fn validate_array(
+    items: &[Value],
+    schema: &Value,
+    path: &str,
+    schema_path: &str,
+    errors: &mut Vec<ValidationError>,
+    mut seen: Option<&mut Seen>,
+) {
+    let length = items.len() as u64;
+    let bound = |keys: [&str; 2]| keys.iter().find_map(|x| schema.get(*x)?.as_u64());
+    if let Some(min) = bound(["minItems", "minimum"]) {
+        if length < min {
+            error(
+                errors,
+                path,
+                format!("expected at least {} items, found {}", min, length),
+            );
+        }
+    }
+    if let Some(max) = bound(["maxItems", "maximum"]) {
+        if length > max {
+            error(
+                errors,
+                path,
+                format!("expected at most {} items, found {}", max, length),
+            );
+        }
+    }
+    if let Some(item_schema) = schema.get("items") {
+        let schema_path = format!("{}/items", schema_path);
+        for (i, item) in items.iter().enumerate() {
+            let path = format!("{}[{}]", path, i);
+            validate_node(
+                item,
+                item_schema,
+                &path,
+                &schema_path,
+                errors,
+                seen.as_deref_mut(),
+            );
+        }
+    }
+}
+
+fn validate_object(
+    map: &Map<String, Value>,
+    schema: &Value,
+    path: &str,
+    schema_path: &str,
+    errors: &mut Vec<ValidationError>,
+    mut seen: Option<&mut Seen>,
+) {
+    let count = map.len() as u64;
+    if let Some(min) = schema.get("minProperties").and_then(|x| x.as_u64()) {
+        if count < min {
+            error(
+                errors,
+                path,
+                format!("expected at least {} properties, found {}", min, count),
+            );
+        }
+    }
+    if let Some(max) = schema.get("maxProperties").and_then(|x| x.as_u64()) {
+        if count > max {
+            error(
+                errors,
+                path,
+                format!("expected at most {} properties, found {}", max, count),
+            );
+        }
+    }
+    let required = schema.get("required").and_then(|x| x.as_array());
+    for name in required.into_iter().flatten().filter_map(|x| x.as_str()) {
+        if !map.contains_key(name) {
+            error(
+                errors,
+                path,
+                format!("missing required property {:?}", name),
+            );
+        }
+    }
+    let properties = schema.get("properties").and_then(|x| x.as_object());
+    let additional = schema.get("additionalProperties");
+    for (key, value) in map.iter() {
+        let property_path = format!("{}.{}", path, key);
+        if let Some(details) = properties.and_then(|x| x.get(key)) {
+            let schema_path = format!("{}/properties/{}", schema_path, key);
+            let seen = seen.as_deref_mut();
+            validate_node(value, details, &property_path, &schema_path, errors, seen);
+        } else if additional == Some(&Value::Bool(false)) {
+            error(errors, path, format!("unexpected property {:?}", key));
+        } else if let Some(details @ Value::Object(_)) = additional {
+            let schema_path = format!("{}/additionalProperties", schema_path);
+            let seen = seen.as_deref_mut();
+            validate_node(value, details, &property_path, &schema_path, errors, seen);
+        }
+    }
+}
+
+/// Validates a value and everything in it.
+/// `seen` is left out while trying the schemas of a `oneOf`, which must not record values.
+fn validate_node(
+    value: &Value,
+    schema: &Value,
+    path: &str,
+    schema_path: &str,
+    errors: &mut Vec<ValidationError>,
+    mut seen: Option<&mut Seen>,
+) {
+    let found = errors.len();
+    validate_keywords(
+        value,
+        schema,
+        path,
+        schema_path,
+        errors,
+        seen.as_deref_mut(),
+    );
+    if schema.get("x-unique") == Some(&Value::Bool(true)) && errors.len() == found {
+        if let Some(seen) = seen {
+            let values = seen.entry(schema_path.to_string()).or_default();
+            if !values.insert(value.to_string()) {
+                error(errors, path, format!("{} is repeated", value));
+            }
+        }
+    }
+}
+
+fn validate_keywords(
+    value: &Value,
+    schema: &Value,
+    path: &str,
+    schema_path: &str,
+    errors: &mut Vec<ValidationError>,
+    seen: Option<&mut Seen>,
+) {
+    if let Some(sequence) = schema.get("x-sequence") {
+        let expected = match sequence.get("format") {
+            Some(_) => "string",
+            None => "integer",
+        };
+        if !matches_type(value, expected) {
+            let message = format!("expected {}, found {}", expected, type_name(value));
+            error(errors, path, message);
+        }
+        return;
+    }
+    if let Some(options) = schema.get("oneOf").and_then(|x| x.as_array()) {
+        let matching = options
+            .iter()
+            .filter(|option| {
+                let mut option_errors = Vec::new();
+                validate_node(value, option, path, schema_path, &mut option_errors, None);
+                option_errors.is_empty()
+            })
+            .count();
+        match matching {
+            1 => (),
+            0 => error(
+                errors,
+                path,
+                "matches none of the oneOf schemas".to_string(),
+            ),
+            n => error(
+                errors,
+                path,
+                format!("matches {} of the oneOf schemas, expected 1", n),
+            ),
+        }
+        return;
+    }
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::Array(types)) => types.iter().filter_map(|x| x.as_str()).collect(),
+        Some(Value::String(x)) => vec![x.as_str()],
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|x| matches_type(value, x)) {
+        let message = format!(
+            "expected {}, found {}",
+            types.join(" or "),
+            type_name(value)
+        );
+        error(errors, path, message);
+        return;
+    }
+    if value.is_null() {
+        return;
+    }
+    if let Some(options) = schema.get("enum").and_then(|x| x.as_array()) {
+        if !options.iter().any(|x| equal(x, value)) {
+            let options = Value::Array(options.clone());
+            error(errors, path, format!("{} is not one of {}", value, options));
+        }
+        return;
+    }
+    match value {
+        Value::String(text) => validate_string(text, schema, path, errors),
+        Value::Number(_) => validate_number(value, schema, path, errors),
+        Value::Array(items) => validate_array(items, schema, path, schema_path, errors, seen),
+        Value::Object(map) => validate_object(map, schema, path, schema_path, errors, seen),
+        _ => (),
+    }
+}
+
+/// Validates every record before passing it on to another writer.
+/// The first record that doesn't match the schema stops writing with an error listing what is wrong.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::{NdjsonWriter, RecordWriter};
+/// use pollinate::validate::ValidatingWriter;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {"x": {"type": "integer", "minimum": 0, "maximum": 9}}});
+/// let mut writer = ValidatingWriter::new(Box::new(NdjsonWriter::new(std::io::sink())), &schema);
+/// assert!(writer.write_record(&json!({"x": 3})).is_ok());
+/// let error = writer.write_record(&json!({"x": 10})).unwrap_err();
+/// assert_eq!(error.to_string(), "Record 2 doesn't match the schema, $.x: 10 is greater than the maximum 9");
+/// ```
+pub struct ValidatingWriter {
+    writer: Box<dyn RecordWriter>,
+    validator: Validator,
+    written: u64,
+}
+
+impl ValidatingWriter {
+    pub fn new(writer: Box<dyn RecordWriter>, schema: &Value) -> Self {
+        ValidatingWriter {
+            writer,
+            validator: Validator::new(schema),
+            written: 0,
+        }
+    }
+}
+
+impl RecordWriter for ValidatingWriter {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        self.written += 1;
+        let errors = self.validator.validate(record);
+        if !errors.is_empty() {
+            let errors: Vec<String> = errors.iter().map(|x| x.to_string()).collect();
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Record {} doesn't match the schema, {}",
+                    self.written,
+                    errors.join(", ")
+                ),
+            ));
+        }
+        self.writer.write_record(record)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.finish()
+    }
+}
@@ -0,0 +1,375 @@
+//! # Pollinate XML Module
+//!
+//! The `xml_utils` module writes generated records as XML elements.
+//!
+//! ## Overview
+//!
+//! Every record becomes an element named after the schema's `title`, and each of its properties a child element.
+//! The `x-xml` keyword of a schema or property takes the same hints as OpenAPI's `xml` object:
+//!
+//! - `name`: Name of the element or attribute instead of the property name.
+//! - `namespace`: Namespace declared on the element.
+//! - `prefix`: Prefix of the element or attribute name, bound to `namespace`. An attribute's namespace is
+//!   declared on the element it belongs to.
+//! - `attribute`: Writes a string, number or boolean property as an attribute of its parent instead.
+//! - `wrapped`: Writes the items of an array inside an element named after the array.
+//!
+//! Items of an unwrapped array are written directly in the parent, each named after the array's items or the
+//! array itself. Maps become `entry` elements with a `key` attribute, and null values are left out. Text and
+//! attribute values are escaped, and characters XML 1.0 doesn't allow at all are dropped. Property names and
+//! `x-xml` names that aren't valid XML names are fixed up with `_`, so `first name` becomes `first_name` and `1st` `_1st`.
+//!
+//! ## Functions
+//!
+//! - `encode_xml`: Encodes a single record as an XML element.
+//! - `dump_xml`: Dumps a vector of JSON values to an XML file.
+//!
+
+use serde_json::{Map, Value};
+use std::io;
+use std::io::Write;
+
+use crate::json_utils::{dump_records, RecordWriter};
+
+const DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+/// Name of the root element holding every record when more than one is written.
+const ROOT: &str = "records";
+
+/// Hints from the `x-xml` keyword of a schema.
+#[derive(Debug, Default)]
+struct XmlHints<'a> {
+    name: Option<&'a str>,
+    namespace: Option<&'a str>,
+    prefix: Option<&'a str>,
+    attribute: bool,
+    wrapped: bool,
+}
+
+fn hints(details: Option<&Value>) -> XmlHints<'_> {
+    let Some(xml) = details.and_then(|x| x.get("x-xml")) else {
+        return XmlHints::default();
+    };
+    let text = |key: &str| xml.get(key).and_then(|x| x.as_str());
+    let flag = |key: &str| xml.get(key).and_then(|x| x.as_bool()).unwrap_or(false);
+    XmlHints {
+        name: text("name"),
+        namespace: text("namespace"),
+        prefix: text("prefix"),
+        attribute: flag("attribute"),
+        wrapped: flag("wrapped"),
+    }
+}
+
+impl XmlHints<'_> {
+    /// Name with its prefix, falling back to the given name when the hints have none.
+    fn qualified(&self, name: &str) -> String {
+        let name = xml_name(self.name.unwrap_or(name));
+        match self.prefix {
+            Some(prefix) => format!("{}:{}", xml_name(prefix), name),
+            None => name,
+        }
+    }
+
+    fn namespace_declaration(&self) -> String {
+        match (self.namespace, self.prefix) {
+            (Some(namespace), Some(prefix)) => {
+                format!(
+                    " xmlns:{}=\"{}\"",
+                    xml_name(prefix),
+                    escape(namespace, true)
+                )
+            }
+            (Some(namespace), None) => format!(" xmlns=\"{}\"", escape(namespace, true)),
+            (None, _) => String::new(),
+        }
+    }
+}
+
+/// Turns a property name or title into a valid XML name.
+/// Characters other than letters, digits, `-` and `.` become `_`, as does a name not starting with a letter.
+fn xml_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' || c == '.' {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    match name.starts_with(char::is_alphabetic) || name.starts_with('_') {
+        true => name,
+        false => format!("_{}", name),
+    }
+}
+
+/// Escapes text for an element, or for an attribute value in double quotes.
+/// Characters that XML 1.0 doesn't allow, even as references, are dropped.
+fn escape(text: &str, attribute: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' if attribute => escaped.push_str(&format!("&#{};", c as u32)),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => (),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(x) => x.clone(),
+        x => x.to_string(),
+    }
+}
+
+/// Details of a property of an object schema.
+fn property<'a>(details: Option<&'a Value>, key: &str) -> Option<&'a Value> {
+    details?.get("properties")?.get(key)
+}
+
+/// Whether a value is written as an attribute rather than an element.
+/// Only strings, numbers and booleans can be attributes.
+fn is_attribute(xml: &XmlHints, value: &Value) -> bool {
+    xml.attribute && !matches!(value, Value::Null | Value::Array(_) | Value::Object(_))
+}
+
+/// Writes an element for a value, along with the attributes and children of an object.
+/// `attributes` are written in the start tag before any derived from the value.
+fn write_element(
+    out: &mut String,
+    name: &str,
+    attributes: &str,
+    details: Option<&Value>,
+    value: &Value,
+) {
+    let xml = hints(details);
+    let name = xml.qualified(name);
+    out.push('<');
+    out.push_str(&name);
+    out.push_str(&xml.namespace_declaration());
+    out.push_str(attributes);
+    let mut content = String::new();
+    let entries = details
+        .filter(|x| x.get("properties").is_none())
+        .and_then(|x| x.get("additionalProperties"));
+    match value {
+        Value::Object(map) if entries.is_some() => {
+            for (key, value) in map.iter().filter(|x| !x.1.is_null()) {
+                let key = format!(" key=\"{}\"", escape(key, true));
+                write_element(&mut content, "entry", &key, entries, value);
+            }
+        }
+        Value::Object(map) => {
+            let mut declared = vec![xml.namespace_declaration()];
+            for (key, value) in map.iter() {
+                let xml = hints(property(details, key));
+                if is_attribute(&xml, value) {
+                    let value = escape(&text(value), true);
+                    out.push_str(&format!(" {}=\"{}\"", xml.qualified(key), value));
+                    // A prefixed attribute's namespace is declared on the element that owns it,
+                    // while an unprefixed attribute belongs to no namespace at all.
+                    let declaration = xml.namespace_declaration();
+                    if xml.prefix.is_some() && !declared.contains(&declaration) {
+                        out.push_str(&declaration);
+                        declared.push(declaration);
+                    }
+                }
+            }
+            write_children(&mut content, details, map);
+        }
+        Value::Array(values) => {
+            let items = details.and_then(|x| x.get("items"));
+            for value in values.iter().filter(|x| !x.is_null()) {
+                write_element(&mut content, "item", "", items, value);
+            }
+        }
+        Value::Null => (),
+        x => content.push_str(&escape(&text(x), false)),
+    }
+    if content.is_empty() {
+        out.push_str("/>");
+    } else {
+        out.push('>');
+        out.push_str(&content);
+        out.push_str(&format!("</{}>", name));
+    }
+}
+
+/// Writes the properties of an object that aren't attributes as child elements.
+fn write_children(out: &mut String, details: Option<&Value>, map: &Map<String, Value>) {
+    for (key, value) in map.iter() {
+        let details = property(details, key);
+        let xml = hints(details);
+        match value {
+            Value::Null => (),
+            Value::Array(values) => {
+                let items = details.and_then(|x| x.get("items"));
+                let item_name = hints(items).name.or(xml.name).unwrap_or(key);
+                let mut elements = String::new();
+                for value in values.iter().filter(|x| !x.is_null()) {
+                    write_element(&mut elements, item_name, "", items, value);
+                }
+                if xml.wrapped {
+                    let name = xml.qualified(key);
+                    out.push_str(&format!("<{}{}", name, xml.namespace_declaration()));
+                    match elements.is_empty() {
+                        true => out.push_str("/>"),
+                        false => out.push_str(&format!(">{}</{}>", elements, name)),
+                    }
+                } else {
+                    out.push_str(&elements);
+                }
+            }
+            _ if is_attribute(&xml, value) => (),
+            _ => write_element(out, key, "", details, value),
+        }
+    }
+}
+
+/// Name of the element holding a record when its schema has no `x-xml.name`, from the schema's `title`.
+fn record_name(schema: &Value) -> String {
+    match schema.get("title").and_then(|x| x.as_str()) {
+        Some(title) => xml_name(title),
+        None => "record".to_owned(),
+    }
+}
+
+/// Encodes a single record as an XML element, using the `x-xml` hints of its schema.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::xml_utils::encode_xml;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "title": "person",
+///     "type": "object",
+///     "properties": {
+///         "id": {"type": "integer", "minimum": 1, "maximum": 9, "x-xml": {"attribute": true}},
+///         "name": {"type": "string", "maxLength": 9},
+///         "skills": {"type": "array", "items": {"type": "string", "x-xml": {"name": "skill"}}, "x-xml": {"wrapped": true}}
+///     }
+/// });
+/// let record = json!({"id": 7, "name": "Tom & Jerry", "skills": ["a", "b"]});
+/// assert_eq!(
+///     encode_xml(&record, &schema),
+///     r#"<person id="7"><name>Tom &amp; Jerry</name><skills><skill>a</skill><skill>b</skill></skills></person>"#
+/// );
+/// ```
+pub fn encode_xml(record: &Value, schema: &Value) -> String {
+    let mut out = String::new();
+    write_element(&mut out, &record_name(schema), "", Some(schema), record);
+    out
+}
+
+/// Writes records as XML, either as one document or as children of a `records` element.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::json_utils::RecordWriter;
+/// use pollinate::xml_utils::XmlWriter;
+/// use serde_json::json;
+///
+/// let schema = json!({"title": "point", "type": "object", "properties": {"x": {"type": "integer", "minimum": 0, "maximum": 9}}});
+/// let mut output = Vec::new();
+/// let mut writer = XmlWriter::new(&mut output, &schema);
+/// writer.write_record(&json!({"x": 1})).unwrap();
+/// writer.write_record(&json!({"x": 2})).unwrap();
+/// writer.finish().unwrap();
+/// drop(writer);
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<records>\n  <point><x>1</x></point>\n  <point><x>2</x></point>\n</records>\n"
+/// );
+/// ```
+pub struct XmlWriter<W: Write> {
+    writer: W,
+    schema: Value,
+    single: bool,
+    written: u64,
+}
+
+impl<W: Write> XmlWriter<W> {
+    pub fn new(writer: W, schema: &Value) -> Self {
+        XmlWriter {
+            writer,
+            schema: schema.clone(),
+            single: false,
+            written: 0,
+        }
+    }
+
+    pub fn single(writer: W, schema: &Value) -> Self {
+        XmlWriter {
+            writer,
+            schema: schema.clone(),
+            single: true,
+            written: 0,
+        }
+    }
+}
+
+impl<W: Write> RecordWriter for XmlWriter<W> {
+    fn write_record(&mut self, record: &Value) -> io::Result<()> {
+        if self.single && self.written > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only one record can be written as a single document",
+            ));
+        }
+        if self.written == 0 {
+            self.writer.write_all(DECLARATION.as_bytes())?;
+            if !self.single {
+                writeln!(self.writer, "<{}>", ROOT)?;
+            }
+        }
+        let element = encode_xml(record, &self.schema);
+        match self.single {
+            true => writeln!(self.writer, "{}", element)?,
+            false => writeln!(self.writer, "  {}", element)?,
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if !self.single {
+            if self.written == 0 {
+                self.writer.write_all(DECLARATION.as_bytes())?;
+                writeln!(self.writer, "<{}/>", ROOT)?;
+            } else {
+                writeln!(self.writer, "</{}>", ROOT)?;
+            }
+        }
+        self.writer.flush()
+    }
+}
+
+/// Dumps a vector of JSON values to an XML file with elements derived from the schema.
+///
+/// # Examples
+///
+/// ```
+/// use pollinate::xml_utils::*;
+/// use serde_json::json;
+///
+/// let schema = json!({"type": "object", "properties": {"key": {"type": "string", "enum": ["value"]}}});
+/// let json_objects = vec![json!({"key": "value"})];
+///
+/// let result = dump_xml(&json_objects, &schema, "output.xml");
+/// assert!(result.is_ok());
+/// # std::fs::remove_file("output.xml").unwrap();
+/// ```
+pub fn dump_xml(objects: &[Value], schema: &Value, file: &str) -> io::Result<()> {
+    dump_records(objects, file, None, |writer| {
+        Ok(XmlWriter::new(writer, schema))
+    })
+}
@@ -0,0 +1,95 @@
+use pollinate::anonymize::*;
+use serde_json::json;
+
+#[test]
+fn anonymize_with_path_rules() {
+    let mut document = json!({
+        "users": [
+            {"id": 1, "email": "adam@hiatt.com", "lastName": "Hiatt", "partner": {"lastName": "Diddly"}},
+            {"id": 2, "email": null, "lastName": "Schmidt"}
+        ],
+        "count": 2
+    });
+    anonymize(
+        &mut document,
+        &read_rules("test_data/anonymize_rules.json").unwrap(),
+    );
+    let users = document["users"].as_array().unwrap();
+    assert_eq!(users[0]["id"], json!(1));
+    assert!(users[0]["email"]
+        .as_str()
+        .unwrap()
+        .ends_with("@example.com"));
+    assert_eq!(users[1]["email"], json!(null));
+    for last_name in [
+        &users[0]["lastName"],
+        &users[0]["partner"]["lastName"],
+        &users[1]["lastName"],
+    ] {
+        assert!(last_name == "Smith" || last_name == "Jones");
+    }
+    assert_eq!(document["count"], json!(2));
+}
+
+#[test]
+fn anonymize_records_with_schema() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "firstName": {"type": "string", "enum": ["X"]},
+            "partner": {
+                "type": "object",
+                "properties": {"firstName": {"type": "string", "enum": ["Y"]}}
+            }
+        }
+    });
+    let mut document = json!([
+        {"firstName": "Adam", "age": 30, "partner": {"firstName": "John", "age": 31}},
+        {"firstName": "Ted", "age": 40}
+    ]);
+    anonymize_with_schema(&mut document, &schema).unwrap();
+    assert_eq!(
+        document,
+        json!([
+            {"firstName": "X", "age": 30, "partner": {"firstName": "Y", "age": 31}},
+            {"firstName": "X", "age": 40}
+        ])
+    );
+}
+
+#[test]
+fn parse_bracket_paths() {
+    assert_eq!(
+        parse_path("$['first name'][2].*..zip").unwrap(),
+        vec![
+            PathSegment::Key("first name".to_string()),
+            PathSegment::Index(2),
+            PathSegment::Wildcard,
+            PathSegment::Descendant("zip".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn malformed_paths_are_errors() {
+    for path in ["users[0]", "$.users[x]", "$.users[0"] {
+        let error = parse_path(path).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn untyped_properties_are_kept() {
+    let schema = json!({
+        "type": "object",
+        "properties": {"name": {"type": "string", "enum": ["X"]}, "note": {"description": "free text"}}
+    });
+    let mut document = json!({"name": "Adam", "note": "kept"});
+    anonymize_with_schema(&mut document, &schema).unwrap();
+    assert_eq!(document, json!({"name": "X", "note": "kept"}));
+
+    let path = std::env::temp_dir().join("pollinate_untyped_rules.json");
+    std::fs::write(&path, r#"{"$.note": {"description": "free text"}}"#).unwrap();
+    let error = read_rules(path.to_str().unwrap()).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,151 @@
+use apache_avro::types::Value as AvroValue;
+use apache_avro::{Reader, Schema};
+use pollinate::{
+    avro_utils::{avro_schema_from_schema, avro_value, schema_from_avro},
+    json_utils::{create_record_writer, stream_json_from_schema, OutputFormat, WriterOptions},
+    schema::{generate_template_from_value, read_schema},
+};
+use serde_json::json;
+
+fn write_avro(schema_path: &str, file_name: &str, count: u32) -> Vec<u8> {
+    let schema = read_schema(schema_path).unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let path = std::env::temp_dir().join(file_name);
+    let path = path.to_str().unwrap();
+    assert_eq!(OutputFormat::from_path(path), Some(OutputFormat::Avro));
+    let options = WriterOptions {
+        format: OutputFormat::Avro,
+        ..Default::default()
+    };
+    let mut writer = create_record_writer(path, &schema, count, &options).unwrap();
+    stream_json_from_schema(&template, count, writer.as_mut()).unwrap();
+    let output = std::fs::read(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    output
+}
+
+#[test]
+fn avsc_converted_to_schema() {
+    let schema = read_schema("test_data/user.avsc").unwrap();
+    let properties = &schema["properties"];
+    assert_eq!(schema["title"], json!("User"));
+    assert_eq!(
+        properties["role"],
+        json!({"type": "string", "enum": ["ADMIN", "MEMBER", "GUEST"]})
+    );
+    assert_eq!(
+        properties["fingerprint"],
+        json!({"type": "string", "minLength": 8, "maxLength": 8})
+    );
+    assert_eq!(
+        properties["counters"]["additionalProperties"]["type"],
+        "integer"
+    );
+    assert_eq!(
+        properties["previousAddress"]["type"],
+        json!(["object", "null"])
+    );
+    assert_eq!(properties["contact"]["oneOf"].as_array().unwrap().len(), 3);
+
+    let record = pollinate::json_utils::create_json_from_schema(
+        &generate_template_from_value(&schema).unwrap(),
+    );
+    assert!(record["id"].as_str().unwrap().len() == 36);
+    assert!(record["address"]["city"].is_string());
+    assert!(record["counters"].is_object());
+}
+
+#[test]
+fn avro_container_from_avsc() {
+    let output = write_avro("test_data/user.avsc", "pollinate_users.avro", 2500);
+    let reader = Reader::new(output.as_slice()).unwrap();
+    let expected = Schema::parse(&read_schema("test_data/user.avsc").unwrap()["x-avro"]).unwrap();
+    assert_eq!(reader.writer_schema(), &expected);
+    let records: Vec<AvroValue> = reader.map(|x| x.unwrap()).collect();
+    assert_eq!(records.len(), 2500);
+    let AvroValue::Record(fields) = &records[0] else {
+        panic!("Expected a record, found {:?}", records[0]);
+    };
+    let field = |name: &str| &fields.iter().find(|(x, _)| x == name).unwrap().1;
+    assert!(matches!(field("id"), AvroValue::Uuid(_)));
+    assert!(matches!(field("createdAt"), AvroValue::TimestampMillis(_)));
+    assert!(matches!(field("balance"), AvroValue::Decimal(_)));
+    assert!(matches!(field("role"), AvroValue::Enum(_, _)));
+    assert!(matches!(field("fingerprint"), AvroValue::Fixed(8, _)));
+}
+
+#[test]
+fn avro_container_from_schema() {
+    let output = write_avro("test_data/person_schema.json", "pollinate_people.avro", 10);
+    let reader = Reader::new(output.as_slice()).unwrap();
+    let records: Vec<serde_json::Value> = reader
+        .map(|x| serde_json::Value::try_from(x.unwrap()).unwrap())
+        .collect();
+    assert_eq!(records.len(), 10);
+    for record in records {
+        assert!(record["firstName"].is_string());
+        assert!(record["partner"]["skills"].is_array());
+    }
+}
+
+#[test]
+fn avro_problems_are_errors() {
+    let schema = json!({
+        "type": "object",
+        "properties": {"id": {"oneOf": [{"type": "integer"}, {"enum": ["a"]}]}}
+    });
+    assert!(avro_schema_from_schema(&schema).is_err());
+
+    let avro = json!({"type": "record", "name": "Count", "fields": [
+        {"name": "n", "type": ["null", "int"]}
+    ]});
+    assert!(avro_value(&json!({"n": 7}), &avro).is_ok());
+    assert!(avro_value(&json!({"n": 1_i64 << 40}), &avro).is_err());
+}
+
+#[test]
+fn unconvertible_avro_schemas_are_errors() {
+    let record =
+        |fields: serde_json::Value| json!({"type": "record", "name": "User", "fields": fields});
+    let cases = [
+        (
+            record(json!([{"name": "id", "type": "Identifier"}])),
+            "Unknown Avro type Identifier",
+        ),
+        (
+            json!({"type": "record", "name": "Node", "fields": [
+                {"name": "next", "type": ["null", "Node"]}
+            ]}),
+            "Recursive Avro type Node",
+        ),
+        (
+            record(json!([{"name": "role", "type": {"type": "enum", "symbols": ["ADMIN"]}}])),
+            "Avro enum has no name",
+        ),
+        (
+            json!({"type": "record", "name": "User"}),
+            "Avro record User has no list of fields",
+        ),
+        (
+            record(json!([{"type": "string"}])),
+            "A field of Avro record User has no name",
+        ),
+        (
+            record(json!([{"name": "id"}])),
+            "Field id of Avro record User has no type",
+        ),
+        (
+            record(json!([{"name": "price", "type": {"type": "bytes", "logicalType": "decimal"}}])),
+            "Avro decimal has no whole precision",
+        ),
+        (
+            json!({"type": "array", "items": "string"}),
+            "Avro schemas must have a record at the top level",
+        ),
+    ];
+    for (avro, message) in cases {
+        let error = schema_from_avro(&avro).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(error.to_string().starts_with(message), "{}", error);
+    }
+}
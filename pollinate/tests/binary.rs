@@ -0,0 +1,91 @@
+use pollinate::{
+    binary_utils::*,
+    json_utils::{create_record_writer, stream_json_from_schema, OutputFormat, WriterOptions},
+    schema::{generate_template_from_value, read_schema},
+};
+use serde_json::{json, Value};
+
+fn person_records(format: BinaryFormat, count: u32) -> Vec<u8> {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let mut output = Vec::new();
+    let mut writer = BinaryWriter::new(&mut output, format);
+    stream_json_from_schema(&template, count, &mut writer).unwrap();
+    output
+}
+
+#[test]
+fn msgpack_stream_round_trip() {
+    let output = person_records(BinaryFormat::MessagePack, 10);
+    let records = read_length_delimited(&output).unwrap();
+    assert_eq!(records.len(), 10);
+    for record in records {
+        let record: Value = rmp_serde::from_slice(&record).unwrap();
+        assert!(record["firstName"].is_string());
+        assert!(record["partner"].is_object());
+    }
+}
+
+#[test]
+fn cbor_stream_round_trip() {
+    let output = person_records(BinaryFormat::Cbor, 10);
+    let records = read_length_delimited(&output).unwrap();
+    assert_eq!(records.len(), 10);
+    for record in records {
+        let record: Value = ciborium::from_reader(record.as_slice()).unwrap();
+        assert!(record["age"].is_u64());
+        assert!(record["skills"].is_array());
+    }
+}
+
+#[test]
+fn bson_documents_back_to_back() {
+    let output = person_records(BinaryFormat::Bson, 10);
+    let mut rest = output.as_slice();
+    let mut count = 0;
+    while !rest.is_empty() {
+        let length = i32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+        let (document, next) = rest.split_at(length);
+        assert_eq!(document.last(), Some(&0));
+        assert!(document.windows(11).any(|x| x == b"\x02firstName\x00"));
+        assert!(document.windows(9).any(|x| x == b"\x03partner\x00"));
+        rest = next;
+        count += 1;
+    }
+    assert_eq!(count, 10);
+}
+
+#[test]
+fn bson_nested_values() {
+    let document = json!({"n": null, "list": [true], "sub": {"x": 5000000000_i64}});
+    let bytes = encode_bson(document.as_object().unwrap()).unwrap();
+    let mut expected = vec![0; 4];
+    expected.extend(b"\x04list\x00\x09\x00\x00\x00\x080\x00\x01\x00");
+    expected.extend(b"\x0an\x00");
+    expected.extend(b"\x03sub\x00\x10\x00\x00\x00\x12x\x00");
+    expected.extend(5000000000_i64.to_le_bytes());
+    expected.extend([0, 0]);
+    expected[0] = expected.len() as u8;
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn single_binary_record_from_extension() {
+    assert_eq!(
+        OutputFormat::from_path("people.mpk"),
+        Some(OutputFormat::Msgpack)
+    );
+    let schema =
+        json!({"type": "object", "properties": {"name": {"type": "string", "enum": ["Adam"]}}});
+    let template = generate_template_from_value(&schema).unwrap();
+    let path = std::env::temp_dir().join("pollinate_single.cbor");
+    let options = WriterOptions {
+        format: OutputFormat::Cbor,
+        ..Default::default()
+    };
+    let mut writer = create_record_writer(path.to_str().unwrap(), &schema, 1, &options).unwrap();
+    stream_json_from_schema(&template, 1, writer.as_mut()).unwrap();
+    let record: Value = ciborium::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(record, json!({"name": "Adam"}));
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,141 @@
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn pollinate(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pollinate"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn schema_from_stdin_to_stdout() {
+    let schema = std::fs::read("test_data/person_schema.json").unwrap();
+    let output = pollinate(&["-i", "-", "-c", "3", "-f", "ndjson"], &schema);
+    assert!(output.status.success());
+    let records: Vec<Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|x| serde_json::from_str(x).unwrap())
+        .collect();
+    assert_eq!(records.len(), 3);
+    assert!(records.iter().all(|x| x["partner"].is_object()));
+}
+
+#[test]
+fn generated_records_pipe_into_infer() {
+    let generated = pollinate(&["-i", "test_data/person_schema.json", "-c", "5"], &[]);
+    let inferred = pollinate(&["infer", "-"], &generated.stdout);
+    assert!(inferred.status.success());
+    let schema: Value = serde_json::from_slice(&inferred.stdout).unwrap();
+    assert_eq!(schema["properties"]["age"]["type"], "integer");
+}
+
+#[test]
+fn errors_go_to_stderr() {
+    let output = pollinate(&["-i", "test_data/person_schema.json", "-f", "sqlite"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("SQLite output can only be written to a file"));
+}
+
+#[test]
+fn unique_capacity_is_reported_before_writing() {
+    let schema = br#"{"type": "object", "properties": {
+        "tags": {"type": "array", "maximum": 2, "items": {"type": "boolean", "x-unique": true}}
+    }}"#;
+    let output = pollinate(&["-i", "-", "-c", "2"], schema);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("only has 1 possible values left"));
+}
+
+#[test]
+fn unreadable_schemas_are_reported() {
+    for (args, stdin) in [
+        (&["-i", "test_data/missing.json", "-c", "1"][..], &b""[..]),
+        (&["-i", "-", "-c", "1"], b"{\"type\": "),
+        (
+            &["validate", "--schema", "test_data/missing.json", "-"],
+            b"",
+        ),
+    ] {
+        let output = pollinate(args, stdin);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.starts_with("pollinate: "));
+        assert_eq!(stderr.lines().count(), 1);
+    }
+}
+
+#[test]
+fn integers_without_bounds_span_the_whole_range() {
+    let schema = br#"{"type": "object", "properties": {"n": {"type": "integer"}}}"#;
+    let output = pollinate(&["-i", "-", "-c", "3", "-f", "ndjson"], schema);
+    assert!(output.status.success());
+    for line in String::from_utf8(output.stdout).unwrap().lines() {
+        let record: Value = serde_json::from_str(line).unwrap();
+        assert!(record["n"].is_i64());
+    }
+}
+
+#[test]
+fn unreadable_samples_are_reported() {
+    // a missing file is reported before stdin is read, so it is given nothing to read
+    for (args, stdin) in [
+        (&["infer", "test_data/missing.ndjson"][..], &b""[..]),
+        (&["infer", "-"], b"{\"a\": "),
+        (
+            &["validate", "--schema", "test_data/person_schema.json", "-"],
+            b"{\"a\": ",
+        ),
+    ] {
+        let output = pollinate(args, stdin);
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.starts_with("pollinate: "));
+        assert!(!stderr.contains("panicked"));
+    }
+}
+
+#[test]
+fn anonymize_reports_malformed_rule_paths() {
+    let rules = std::env::temp_dir().join("pollinate_bad_rules.json");
+    std::fs::write(&rules, r#"{"users[0].name": {"type": "string"}}"#).unwrap();
+    let output = pollinate(
+        &["anonymize", "--rules", rules.to_str().unwrap(), "-"],
+        b"{}",
+    );
+    std::fs::remove_file(&rules).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("must start with $"));
+    assert!(!stderr.contains("panicked"));
+}
+
+#[test]
+fn anonymize_reports_unreadable_input() {
+    let output = pollinate(
+        &[
+            "anonymize",
+            "--rules",
+            "test_data/anonymize_rules.json",
+            "-",
+        ],
+        b"{",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("pollinate: "));
+    assert!(!stderr.contains("panicked"));
+}
@@ -0,0 +1,87 @@
+use pollinate::{
+    compress_utils::Compression,
+    json_utils::{
+        create_record_writer, dump_json_array, dump_value_compressed, stream_json_from_schema,
+        JsonStyle, OutputFormat, WriterOptions,
+    },
+    schema::{generate_template_from_value, read_schema},
+};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Read;
+
+fn write_compressed(file: &str, count: u32) {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let options = WriterOptions {
+        format: OutputFormat::from_path(file).unwrap(),
+        compression: Compression::from_path(file),
+        ..WriterOptions::default()
+    };
+    let mut writer = create_record_writer(file, &schema, count, &options).unwrap();
+    stream_json_from_schema(&template, count, writer.as_mut()).unwrap();
+}
+
+#[test]
+fn gzip_ndjson_round_trip() {
+    write_compressed("compressed.ndjson.gz", 50);
+    let mut text = String::new();
+    flate2::read::GzDecoder::new(fs::File::open("compressed.ndjson.gz").unwrap())
+        .read_to_string(&mut text)
+        .unwrap();
+    let records: Vec<Value> = text
+        .lines()
+        .map(|x| serde_json::from_str(x).unwrap())
+        .collect();
+    assert_eq!(records.len(), 50);
+    fs::remove_file("compressed.ndjson.gz").unwrap();
+}
+
+#[test]
+fn zstd_csv_round_trip() {
+    write_compressed("compressed.csv.zst", 20);
+    let bytes = zstd::decode_all(fs::File::open("compressed.csv.zst").unwrap()).unwrap();
+    let text = String::from_utf8(bytes).unwrap();
+    assert!(text.starts_with("age,firstName,partner.age"));
+    assert_eq!(text.lines().count(), 21);
+    fs::remove_file("compressed.csv.zst").unwrap();
+}
+
+#[test]
+fn bzip2_json_round_trip() {
+    write_compressed("compressed.json.bz2", 10);
+    let mut text = String::new();
+    bzip2::read::BzDecoder::new(fs::File::open("compressed.json.bz2").unwrap())
+        .read_to_string(&mut text)
+        .unwrap();
+    let records: Vec<Value> = serde_json::from_str(&text).unwrap();
+    assert_eq!(records.len(), 10);
+    fs::remove_file("compressed.json.bz2").unwrap();
+}
+
+#[test]
+fn dumped_arrays_are_compressed_by_extension() {
+    dump_json_array(&[json!({"a": 1}), json!({"a": 2})], "dumped.json.gz").unwrap();
+    let mut text = String::new();
+    flate2::read::GzDecoder::new(fs::File::open("dumped.json.gz").unwrap())
+        .read_to_string(&mut text)
+        .unwrap();
+    let records: Vec<Value> = serde_json::from_str(&text).unwrap();
+    assert_eq!(records, vec![json!({"a": 1}), json!({"a": 2})]);
+    fs::remove_file("dumped.json.gz").unwrap();
+}
+
+#[test]
+fn dumped_values_use_the_compression_given() {
+    let style = JsonStyle::default();
+    dump_value_compressed(
+        json!({"a": 1}),
+        "dumped.json",
+        &style,
+        Some(Compression::Zstd),
+    )
+    .unwrap();
+    let bytes = zstd::decode_all(fs::File::open("dumped.json").unwrap()).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), r#"{"a":1}"#);
+    fs::remove_file("dumped.json").unwrap();
+}
@@ -0,0 +1,149 @@
+use pollinate::{
+    coverage::{factors, Coverage, CoveragePlan, CoverageWriter},
+    json_utils::{stream_json_from_schema, NdjsonWriter},
+    schema::generate_template_from_value,
+    validate::validate,
+};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::process::Command;
+
+fn schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "os": {"type": "string", "enum": ["linux", "mac", "windows", "android"]},
+            "browser": {"type": "string", "enum": ["firefox", "chrome", "safari"]},
+            "mobile": {"type": "boolean"},
+            "name": {"type": "string", "maxLength": 8},
+            "retries": {"type": "integer", "minimum": 0, "maximum": 5},
+            "account": {
+                "type": "object",
+                "properties": {
+                    "plan": {"type": ["string", "null"], "enum": ["free", "pro"]},
+                    "id": {"type": "integer", "minimum": 1, "maximum": 1000, "x-unique": true}
+                }
+            }
+        }
+    })
+}
+
+/// Writes the planned records and returns them.
+fn generate(schema: &Value, coverage: Coverage) -> Vec<Value> {
+    let plan = CoveragePlan::new(schema, coverage).unwrap();
+    let count = plan.len() as u32;
+    let mut output = Vec::new();
+    let mut writer = CoverageWriter::new(Box::new(NdjsonWriter::new(&mut output)), plan);
+    stream_json_from_schema(
+        &generate_template_from_value(schema).unwrap(),
+        count,
+        &mut writer,
+    )
+    .unwrap();
+    drop(writer);
+    let records: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|x| serde_json::from_str(x).unwrap())
+        .collect();
+    assert_eq!(records.len(), count as usize);
+    assert!(records.iter().all(|x| validate(x, schema).is_empty()));
+    records
+}
+
+#[test]
+fn exhaustive_covers_every_combination() {
+    let schema = schema();
+    let pointers: Vec<String> = factors(&schema).into_iter().map(|x| x.pointer).collect();
+    assert_eq!(
+        pointers,
+        ["/account/plan", "/browser", "/mobile", "/os", "/retries"]
+    );
+
+    let records = generate(&schema, Coverage::Exhaustive);
+    assert_eq!(records.len(), 3 * 3 * 2 * 4 * 2);
+    let combinations: HashSet<String> = records
+        .iter()
+        .map(|x| {
+            pointers
+                .iter()
+                .map(|p| x.pointer(p).unwrap().to_string())
+                .collect()
+        })
+        .collect();
+    assert_eq!(combinations.len(), records.len());
+}
+
+#[test]
+fn pairwise_covers_every_pair() {
+    let schema = schema();
+    let factors = factors(&schema);
+    let records = generate(&schema, Coverage::Pairwise);
+    assert!(records.len() < 30, "{} records", records.len());
+    for (i, first) in factors.iter().enumerate() {
+        for second in factors.iter().skip(i + 1) {
+            let pairs: HashSet<(String, String)> = records
+                .iter()
+                .map(|x| {
+                    let value = |pointer| x.pointer(pointer).unwrap().to_string();
+                    (value(&first.pointer), value(&second.pointer))
+                })
+                .collect();
+            assert_eq!(pairs.len(), first.levels.len() * second.levels.len());
+        }
+    }
+}
+
+#[test]
+fn coverage_count_reported_upfront() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pollinate"))
+        .args([
+            "-i",
+            "test_data/person_schema.json",
+            "--coverage",
+            "exhaustive",
+            "-f",
+            "ndjson",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "pollinate: Writing 36 records covering every combination of values of 4 fields\n"
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().lines().count(),
+        36
+    );
+}
+
+#[test]
+fn exhaustive_plans_are_not_stored() {
+    let properties: serde_json::Map<String, Value> = (0..31)
+        .map(|i| (format!("flag{:02}", i), json!({"type": "boolean"})))
+        .collect();
+    let schema = json!({"type": "object", "properties": properties});
+    let plan = CoveragePlan::new(&schema, Coverage::Exhaustive).unwrap();
+    assert_eq!(plan.len(), 1 << 31);
+
+    let mut record = json!({});
+    for i in 0..31 {
+        record[format!("flag{:02}", i)] = json!(true);
+    }
+    plan.apply(plan.len() - 2, &mut record);
+    assert_eq!(record["flag00"], json!(false));
+    assert_eq!(record["flag29"], json!(false));
+    assert_eq!(record["flag30"], json!(true));
+}
+
+#[test]
+fn missing_fields_are_added() {
+    let plan = CoveragePlan::new(&schema(), Coverage::Exhaustive).unwrap();
+    let mut record = json!({"os": "mac"});
+    plan.apply(plan.len() - 1, &mut record);
+    assert_eq!(
+        record,
+        json!({"os": "android", "browser": "safari", "mobile": false, "retries": 5, "account": {"plan": null}})
+    );
+}
@@ -0,0 +1,83 @@
+use pollinate::{
+    csv_utils::*,
+    json_utils::{stream_json_from_schema, RecordWriter},
+    schema::{generate_template_from_value, read_schema},
+};
+use serde_json::json;
+
+#[test]
+fn csv_header_from_schema() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    assert_eq!(
+        columns_from_schema(&schema),
+        vec![
+            "age",
+            "firstName",
+            "partner.age",
+            "partner.firstName",
+            "partner.skills",
+            "skills"
+        ]
+    );
+}
+
+#[test]
+fn csv_from_person_schema() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let mut output = Vec::new();
+    let mut writer = CsvWriter::new(
+        &mut output,
+        columns_from_schema(&schema),
+        ',',
+        Some(";".to_string()),
+    );
+    stream_json_from_schema(&template, 25, &mut writer).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let rows: Vec<&str> = output.split_terminator("\r\n").collect();
+    assert_eq!(rows.len(), 26);
+    assert_eq!(
+        rows[0],
+        "age,firstName,partner.age,partner.firstName,partner.skills,skills"
+    );
+    for row in &rows[1..] {
+        assert_eq!(row.split(',').count(), 6);
+    }
+}
+
+#[test]
+fn tsv_quotes_special_characters() {
+    let mut output = Vec::new();
+    let columns = vec!["note".to_string(), "tags".to_string()];
+    let mut writer = CsvWriter::new(&mut output, columns, '\t', None);
+    writer
+        .write_record(&json!({"note": "line one\nline\ttwo", "tags": ["a", "b"]}))
+        .unwrap();
+    writer.write_record(&json!({"note": null})).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "note\ttags\r\n\"line one\nline\ttwo\"\t\"[\"\"a\"\",\"\"b\"\"]\"\r\n\t\r\n"
+    );
+}
+
+#[test]
+fn csv_keeps_dotted_property_names() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "v1.2": {"type": "string", "enum": ["x"]},
+            "partner": {"type": "object", "properties": {"id": {"type": "integer", "enum": [7]}}}
+        }
+    });
+    let mut output = Vec::new();
+    let mut writer = CsvWriter::from_schema(&mut output, &schema, ',', None);
+    writer
+        .write_record(&json!({"v1.2": "x", "partner": {"id": 7}}))
+        .unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "partner.id,v1.2\r\n7,x\r\n"
+    );
+}
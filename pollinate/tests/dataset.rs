@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use pollinate::dataset::*;
+use serde_json::Value;
+
+#[test]
+fn tables_generated_in_dependency_order() {
+    let manifest = read_manifest("test_data/dataset/manifest.json").unwrap();
+    let names: Vec<String> = dependency_order(&manifest)
+        .unwrap()
+        .into_iter()
+        .map(|x| x.name)
+        .collect();
+    assert_eq!(names, vec!["customers", "orders", "order_items"]);
+}
+
+#[test]
+fn foreign_keys_reference_parent_records() {
+    let manifest = read_manifest("test_data/dataset/manifest.json").unwrap();
+    let tables = generate_dataset(&manifest).unwrap();
+    assert_eq!(tables["customers"].len(), 10);
+
+    let order_ids: Vec<&Value> = tables["orders"].iter().map(|x| &x["id"]).collect();
+    for item in tables["order_items"].iter() {
+        assert!(order_ids.contains(&&item["orderId"]));
+    }
+}
+
+#[test]
+fn per_parent_cardinality() {
+    let manifest = read_manifest("test_data/dataset/manifest.json").unwrap();
+    let tables = generate_dataset(&manifest).unwrap();
+    let mut orders_per_customer = HashMap::<String, u32>::new();
+    for order in tables["orders"].iter() {
+        *orders_per_customer
+            .entry(order["customerId"].to_string())
+            .or_default() += 1;
+    }
+    assert_eq!(orders_per_customer.len(), 10);
+    for count in orders_per_customer.values() {
+        assert!((1..=5).contains(count));
+    }
+}
+
+#[test]
+fn per_bounds_are_checked() {
+    let path = std::env::temp_dir().join("pollinate_reversed_manifest.json");
+    std::fs::write(
+        &path,
+        r#"{"tables": [{"name": "orders", "schema": "orders.json", "output": "orders.json",
+            "per": {"table": "customers", "minimum": 5, "maximum": 1}}]}"#,
+    )
+    .unwrap();
+    let error = read_manifest(path.to_str().unwrap()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("orders"));
+}
+
+/// Writes a manifest and its schemas to a directory of their own.
+fn write_manifest(name: &str, manifest: &str, schemas: &[(&str, &str)]) -> String {
+    let dir = std::env::temp_dir().join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, schema) in schemas {
+        std::fs::write(dir.join(file), schema).unwrap();
+    }
+    std::fs::write(dir.join("manifest.json"), manifest).unwrap();
+    dir.join("manifest.json").to_string_lossy().into_owned()
+}
+
+#[test]
+fn manifest_problems_are_errors() {
+    let path = write_manifest(
+        "pollinate_cyclic_manifest",
+        r#"{"tables": [
+            {"name": "a", "schema": "a.json", "output": "a.json", "per": {"table": "b", "maximum": 1}},
+            {"name": "b", "schema": "b.json", "output": "b.json", "per": {"table": "a", "maximum": 1}}
+        ]}"#,
+        &[
+            (
+                "a.json",
+                r#"{"type": "object", "properties": {"b": {"x-foreign-key": "b.id"}}}"#,
+            ),
+            (
+                "b.json",
+                r#"{"type": "object", "properties": {"a": {"x-foreign-key": "a.id"}}}"#,
+            ),
+        ],
+    );
+    let manifest = read_manifest(&path).unwrap();
+    let error = generate_dataset(&manifest).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("cycle"));
+
+    let path = write_manifest(
+        "pollinate_malformed_manifest",
+        r#"{"tables": [{"name": "a", "schema": "a.json", "output": "a.json", "count": 1}]}"#,
+        &[(
+            "a.json",
+            r#"{"type": "object", "properties": {"b": {"x-foreign-key": "b"}}}"#,
+        )],
+    );
+    let error = dependency_order(&read_manifest(&path).unwrap()).unwrap_err();
+    assert!(error.to_string().contains("table.column"));
+
+    let path = write_manifest(
+        "pollinate_broken_schema_manifest",
+        r#"{"tables": [{"name": "a", "schema": "a.json", "output": "a.json", "count": 1}]}"#,
+        &[("a.json", r#"{"type": "object", "properties": }"#)],
+    );
+    let manifest = read_manifest(&path).unwrap();
+    let error = dependency_order(&manifest).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    let error = generate_dataset(&manifest).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    let path = write_manifest(
+        "pollinate_missing_schema_manifest",
+        r#"{"tables": [{"name": "a", "schema": "missing.json", "output": "a.json", "count": 1}]}"#,
+        &[],
+    );
+    let error = generate_dataset(&read_manifest(&path).unwrap()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+
+    let path = write_manifest(
+        "pollinate_duplicate_manifest",
+        r#"{"tables": [
+            {"name": "a", "schema": "a.json", "output": "a.json", "count": 1},
+            {"name": "a", "schema": "a.json", "output": "b.json", "count": 1}
+        ]}"#,
+        &[("a.json", r#"{"type": "object", "properties": {}}"#)],
+    );
+    let error = read_manifest(&path).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error
+        .to_string()
+        .contains("Table a is listed more than once"));
+}
+
+#[test]
+fn foreign_keys_take_the_type_of_their_column() {
+    let path = write_manifest(
+        "pollinate_typed_manifest",
+        r#"{"tables": [
+            {"name": "customers", "schema": "customers.json", "output": "customers.json", "count": 3},
+            {"name": "orders", "schema": "orders.json", "output": "orders.json", "count": 5}
+        ]}"#,
+        &[
+            (
+                "customers.json",
+                r#"{"type": "object", "properties": {"id": {"type": "integer", "x-sequence": {}}}}"#,
+            ),
+            (
+                "orders.json",
+                r#"{"type": "object", "properties": {"customerId": {"x-foreign-key": "customers.id"}}}"#,
+            ),
+        ],
+    );
+    let manifest = read_manifest(&path).unwrap();
+    let tables = generate_dataset(&manifest).unwrap();
+    let schema =
+        serde_json::json!({"type": "object", "properties": {"customerId": {"type": "integer"}}});
+    for order in tables["orders"].iter() {
+        assert!(pollinate::validate::validate(order, &schema).is_empty());
+    }
+}
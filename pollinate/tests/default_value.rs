@@ -1,6 +1,13 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use pollinate::{default_values::*, json_utils::create_json_from_schema};
+use pollinate::{
+    default_values::*,
+    json_utils::{
+        check_unique_capacity, create_json_from_schema, create_json_vec_from_schema,
+        stream_json_from_schema, NdjsonWriter,
+    },
+    schema::{parse_type, populate_schema},
+};
 use serde::Serialize;
 use serde_json::{json, Value};
 
@@ -51,6 +58,7 @@ fn get_more_values_than_string_struct() {
 }
 
 #[test]
+#[allow(clippy::let_unit_value)]
 fn get_range_of_values() {
     let zero_to_100 = Box::new(RangedValues::<u32>::new(0, 100)) as Box<dyn Values>;
     let mut schema = HashMap::new();
@@ -77,3 +85,194 @@ fn get_range_of_values() {
         };
     }
 }
+
+#[test]
+fn sequence_counts_across_records() {
+    let ids = Box::new(SequenceValues::new(1, 1, None, SequenceScope::Parent)) as Box<dyn Values>;
+    let mut schema = HashMap::new();
+    schema.insert("id".to_owned(), ids);
+    let objects = create_json_vec_from_schema(&schema, 5).unwrap();
+    for (i, val) in objects.iter().enumerate() {
+        assert_eq!(val["id"], json!(i as i64 + 1));
+    }
+}
+
+#[test]
+fn sequence_restarts_per_parent_array() {
+    let details = json!({
+        "type": "object",
+        "properties": {
+            "orderId": {"type": "string", "x-sequence": {"format": "ORD-{:06}"}},
+            "lines": {
+                "type": "array",
+                "minimum": 1,
+                "maximum": 4,
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "line": {"type": "integer", "x-sequence": {"start": 1}},
+                        "globalLine": {"type": "integer", "x-sequence": {"scope": "global"}}
+                    }
+                }
+            }
+        }
+    });
+    let schema = populate_schema(details["properties"].as_object().unwrap()).unwrap();
+    let objects = create_json_vec_from_schema(&schema, 3).unwrap();
+    let mut global = 1;
+    for (i, val) in objects.iter().enumerate() {
+        assert_eq!(val["orderId"], json!(format!("ORD-{:06}", i + 1)));
+        for (j, line) in val["lines"].as_array().unwrap().iter().enumerate() {
+            assert_eq!(line["line"], json!(j as i64 + 1));
+            assert_eq!(line["globalLine"], json!(global));
+            global += 1;
+        }
+    }
+}
+
+#[test]
+fn unique_values_never_repeat() {
+    let details = json!({
+        "id": {"type": "integer", "minimum": 0, "maximum": 50, "x-unique": true},
+        "name": {"type": "string", "enum": ["Adam", "John", "Ted"], "x-unique": true}
+    });
+    let schema = populate_schema(details.as_object().unwrap()).unwrap();
+    let objects = create_json_vec_from_schema(&schema, 3).unwrap();
+    let ids: HashSet<String> = objects.iter().map(|x| x["id"].to_string()).collect();
+    let names: HashSet<String> = objects.iter().map(|x| x["name"].to_string()).collect();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(names.len(), 3);
+}
+
+#[test]
+fn unique_values_exhaust_domain() {
+    let values = UniqueValues::new(Box::new(RangedValues::new(0, 1000)));
+    let seen: HashSet<String> = (0..1000).map(|_| values.get_value().to_string()).collect();
+    assert_eq!(seen.len(), 1000);
+}
+
+#[test]
+fn unique_values_fail_when_count_exceeds_domain() {
+    let details = json!({
+        "name": {"type": "string", "enum": ["Adam", "John", "Ted"], "x-unique": true}
+    });
+    let schema = populate_schema(details.as_object().unwrap()).unwrap();
+    let error = create_json_vec_from_schema(&schema, 4).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("only has 3 possible values"));
+}
+
+#[test]
+fn sequence_reaches_the_largest_integer() {
+    let ids = SequenceValues::new(
+        i64::MAX - 1,
+        1,
+        Some("#{:03}".to_owned()),
+        SequenceScope::Parent,
+    );
+    assert_eq!(ids.get_value(), json!(format!("#{}", i64::MAX - 1)));
+    assert_eq!(ids.get_value(), json!(format!("#{}", i64::MAX)));
+}
+
+#[test]
+fn sequence_keywords_are_checked() {
+    for sequence in [
+        json!({"format": "A}{"}),
+        json!({"format": 7}),
+        json!({"start": "1"}),
+        json!({"step": 0.5}),
+        json!({"scope": "table"}),
+        json!(true),
+    ] {
+        let details = json!({"type": "string", "x-sequence": sequence});
+        let error = pollinate::schema::parse_sequence(&details).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+    let error = populate_schema(
+        json!({"id": {"x-sequence": {"format": "A}{"}}})
+            .as_object()
+            .unwrap(),
+    )
+    .err()
+    .unwrap();
+    assert!(error.to_string().contains("unclosed placeholder"));
+}
+
+#[test]
+fn empty_enums_are_rejected() {
+    for details in [
+        json!({"type": "string", "enum": []}),
+        json!({"type": ["integer", "null"], "enum": []}),
+        json!({"type": "number", "enum": 1}),
+    ] {
+        let error = parse_type(&details).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn empty_strings_only_when_max_length_is_zero() {
+    let values = parse_type(&json!({"type": "string", "maxLength": 0}))
+        .unwrap()
+        .unwrap();
+    assert_eq!(values.get_value(), json!(""));
+}
+
+#[test]
+fn inverted_bounds_are_rejected() {
+    for details in [
+        json!({"type": "string", "minLength": 5, "maxLength": 2}),
+        json!({"type": "string", "format": 5}),
+        json!({"type": "number", "minimum": 2.5, "maximum": 0.5}),
+        json!({"type": "number", "minimum": "0"}),
+        json!({"type": "array", "minimum": 3, "maximum": 1, "items": {"type": "boolean"}}),
+        json!({"type": "object", "minProperties": 3, "maxProperties": 1, "additionalProperties": {"type": "boolean"}}),
+    ] {
+        let error = parse_type(&details).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn sequence_overflow_is_reported_before_generating() {
+    let details = json!({"id": {"type": "integer", "x-sequence": {"start": i64::MAX - 1}}});
+    let schema = populate_schema(details.as_object().unwrap()).unwrap();
+    let mut output = Vec::new();
+    let error =
+        stream_json_from_schema(&schema, 3, &mut NdjsonWriter::new(&mut output)).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(output.is_empty());
+    stream_json_from_schema(&schema, 2, &mut NdjsonWriter::new(Vec::new())).unwrap();
+}
+
+#[test]
+fn unique_ranges_include_the_maximum() {
+    let details = json!({
+        "id": {"type": "integer", "minimum": 1, "maximum": 3, "x-unique": true}
+    });
+    let schema = populate_schema(details.as_object().unwrap()).unwrap();
+    let mut output = Vec::new();
+    stream_json_from_schema(&schema, 3, &mut NdjsonWriter::new(&mut output)).unwrap();
+    let mut ids: Vec<String> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+    ids.sort();
+    assert_eq!(ids, vec![r#"{"id":1}"#, r#"{"id":2}"#, r#"{"id":3}"#]);
+
+    let error =
+        stream_json_from_schema(&schema, 4, &mut NdjsonWriter::new(Vec::new())).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn unique_short_strings_are_enumerated() {
+    let details =
+        json!({"code": {"type": "string", "minLength": 1, "maxLength": 1, "x-unique": true}});
+    let schema = populate_schema(details.as_object().unwrap()).unwrap();
+    let objects = create_json_vec_from_schema(&schema, 62).unwrap();
+    let codes: HashSet<String> = objects.iter().map(|x| x["code"].to_string()).collect();
+    assert_eq!(codes.len(), 62);
+    assert!(check_unique_capacity(&schema, 1).is_err());
+}
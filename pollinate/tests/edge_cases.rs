@@ -0,0 +1,75 @@
+use pollinate::{
+    default_values::{ArrayValues, EdgeCaseValues, StringValues, Values},
+    json_utils::create_json_from_schema,
+    schema::{generate_template_from_value, read_schema, with_edge_cases},
+    validate::Validator,
+};
+use serde_json::json;
+use std::collections::HashSet;
+
+#[test]
+fn bounds_are_hit() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "age": {"type": "integer", "minimum": -5, "maximum": 100},
+            "score": {"type": "number", "minimum": 0.5, "maximum": 2.5},
+            "plan": {"type": "string", "enum": ["free", "team", "pro"]}
+        }
+    });
+    let template = generate_template_from_value(&with_edge_cases(&schema, 1.0)).unwrap();
+    let mut ages = HashSet::new();
+    let mut scores = Vec::new();
+    let mut plans = HashSet::new();
+    for _ in 0..200 {
+        let record = create_json_from_schema(&template);
+        ages.insert(record["age"].as_i64().unwrap());
+        scores.push(record["score"].as_f64().unwrap());
+        plans.insert(record["plan"].as_str().unwrap().to_owned());
+    }
+    assert_eq!(ages, HashSet::from([-5, -4, 0, 99, 100]));
+    assert!(scores.contains(&0.5) && scores.contains(&2.5));
+    assert!(scores.iter().all(|x| (0.5..=2.5).contains(x)));
+    assert_eq!(plans, HashSet::from(["free".to_owned(), "pro".to_owned()]));
+}
+
+#[test]
+fn arrays_and_strings_at_their_limits() {
+    let items = Box::new(EdgeCaseValues::new(
+        Box::new(StringValues::new(0, 4, None)),
+        1.0,
+    ));
+    let values = EdgeCaseValues::new(Box::new(ArrayValues::new(0, 3, items)), 1.0);
+    let mut lengths = HashSet::new();
+    let mut unicode = false;
+    let mut whitespace = false;
+    for _ in 0..200 {
+        let value = values.get_value();
+        let value = value.as_array().unwrap();
+        lengths.insert(value.len());
+        for item in value {
+            let item = item.as_str().unwrap();
+            assert!(item.chars().count() <= 4);
+            unicode |= !item.is_ascii();
+            whitespace |= item.contains(char::is_whitespace);
+        }
+    }
+    assert_eq!(lengths, HashSet::from([0, 3]));
+    assert!(unicode && whitespace);
+}
+
+#[test]
+fn mixed_records_stay_valid() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let template = generate_template_from_value(&with_edge_cases(&schema, 0.5)).unwrap();
+    let mut validator = Validator::new(&schema);
+    let mut random_ages = 0;
+    for _ in 0..500 {
+        let record = create_json_from_schema(&template);
+        assert!(validator.validate(&record).is_empty(), "{}", record);
+        if !matches!(record["age"].as_i64().unwrap(), 0 | 1 | 99 | 100) {
+            random_ages += 1;
+        }
+    }
+    assert!((100..400).contains(&random_ages));
+}
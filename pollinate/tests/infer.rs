@@ -0,0 +1,72 @@
+use pollinate::{
+    infer::{infer_schema, read_samples},
+    json_utils::create_json_vec_from_schema,
+    schema::populate_schema,
+};
+use serde_json::json;
+
+#[test]
+fn infer_from_ndjson() {
+    let samples = read_samples("test_data/users.ndjson").unwrap();
+    assert_eq!(samples.len(), 4);
+    let schema = infer_schema(&samples).unwrap();
+    let properties = &schema["properties"];
+    assert_eq!(
+        properties["id"],
+        json!({"type": "integer", "minimum": 1, "maximum": 4})
+    );
+    assert_eq!(properties["email"]["format"], json!("email"));
+    assert_eq!(properties["createdAt"]["format"], json!("date-time"));
+    assert_eq!(properties["role"]["enum"], json!(["admin", "user"]));
+    assert_eq!(properties["score"]["type"], json!("number"));
+    assert_eq!(properties["tags"]["minimum"], json!(0));
+    assert_eq!(properties["tags"]["maximum"], json!(3));
+    assert_eq!(properties["manager"]["type"], json!(["integer", "null"]));
+    assert!(!schema["required"]
+        .as_array()
+        .unwrap()
+        .contains(&json!("manager")));
+}
+
+#[test]
+fn infer_from_json_array() {
+    let samples = read_samples("test_data/person_schema_output.json").unwrap();
+    let schema = infer_schema(&samples).unwrap();
+    assert_eq!(schema["type"], json!("object"));
+    assert_eq!(schema["properties"]["partner"]["type"], json!("object"));
+    assert_eq!(
+        schema["properties"]["skills"]["items"]["type"],
+        json!("string")
+    );
+}
+
+#[test]
+fn inferred_schema_generates_records() {
+    let schema = infer_schema(&read_samples("test_data/users.ndjson").unwrap()).unwrap();
+    let template = populate_schema(schema["properties"].as_object().unwrap()).unwrap();
+    let records = create_json_vec_from_schema(&template, 20).unwrap();
+    for record in records {
+        assert!(record["email"].as_str().unwrap().contains('@'));
+        let role = record["role"].as_str().unwrap();
+        assert!(role == "admin" || role == "user");
+    }
+}
+
+#[test]
+fn null_fields_stay_generatable() {
+    let samples = vec![
+        json!({"nickname": null, "tags": []}),
+        json!({"nickname": null, "tags": []}),
+    ];
+    let schema = infer_schema(&samples).unwrap();
+    assert_eq!(
+        schema["properties"]["nickname"],
+        json!({"type": ["string", "null"]})
+    );
+    let template = populate_schema(schema["properties"].as_object().unwrap()).unwrap();
+    let records = create_json_vec_from_schema(&template, 50).unwrap();
+    assert!(records.iter().any(|x| x["nickname"].is_string()));
+
+    assert!(infer_schema(&[]).is_err());
+    assert!(infer_schema(&[json!(1), json!(2)]).is_err());
+}
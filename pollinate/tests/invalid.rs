@@ -0,0 +1,117 @@
+use pollinate::{
+    invalid::{break_record, InvalidWriter},
+    json_utils::{stream_json_from_schema, NdjsonWriter},
+    schema::generate_template_from_value,
+    validate::validate,
+};
+use serde_json::{json, Value};
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn each_record_breaks_one_rule() {
+    let schema = json!({
+        "type": "object",
+        "required": ["id", "tags"],
+        "additionalProperties": false,
+        "properties": {
+            "id": {"type": "integer", "x-sequence": {"start": 1}},
+            "email": {"type": "string", "format": "email"},
+            "name": {"type": ["string", "null"], "minLength": 2, "maxLength": 6},
+            "plan": {"type": "string", "enum": ["free", "pro"]},
+            "tags": {"type": "array", "minimum": 1, "maximum": 3, "items": {"type": "integer", "minimum": 1, "maximum": 4}},
+            "meta": {"type": "object", "minProperties": 1, "maxProperties": 3, "additionalProperties": {"type": "boolean"}}
+        }
+    });
+    let template = generate_template_from_value(&schema).unwrap();
+    let mut output = Vec::new();
+    let mut writer = InvalidWriter::new(Box::new(NdjsonWriter::new(&mut output)), &schema);
+    stream_json_from_schema(&template, 500, &mut writer).unwrap();
+    drop(writer);
+
+    let mut rules = std::collections::HashSet::new();
+    for line in String::from_utf8(output).unwrap().lines() {
+        let annotated: Value = serde_json::from_str(line).unwrap();
+        let errors = validate(&annotated["record"], &schema);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(annotated["violation"]["path"], errors[0].path);
+        assert_eq!(annotated["violation"]["message"], errors[0].message);
+        rules.insert(annotated["violation"]["rule"].as_str().unwrap().to_owned());
+    }
+    for rule in [
+        "type",
+        "format",
+        "enum",
+        "required",
+        "additionalProperties",
+        "maxProperties",
+    ] {
+        assert!(rules.contains(rule), "{} was never broken", rule);
+    }
+}
+
+#[test]
+fn breaks_required_keys_and_array_lengths() {
+    let schema = json!({"type": "object", "required": ["id"], "properties": {"id": {}}});
+    let (broken, violation) = break_record(&json!({"id": 1}), &schema).unwrap();
+    assert_eq!(broken, json!({}));
+    assert_eq!(violation.rule, "required");
+    assert_eq!(violation.message, "missing required property \"id\"");
+
+    let schema = json!({"type": "object", "properties": {"tags": {"maximum": 2, "items": {"type": "boolean"}}}});
+    let (broken, violation) = break_record(&json!({"tags": []}), &schema).unwrap();
+    assert_eq!(broken["tags"].as_array().unwrap().len(), 3);
+    assert_eq!(violation.path, "$.tags");
+    assert_eq!(violation.rule, "maximum");
+
+    let schema = json!({"type": "object", "properties": {"id": {}}});
+    assert!(break_record(&json!({"id": 1}), &schema).is_none());
+}
+
+#[test]
+fn lengths_are_kept_when_items_have_no_type() {
+    let schema = json!({"type": "object", "properties": {
+        "tags": {"maximum": 2, "items": {"enum": [1]}},
+        "meta": {"maxProperties": 1, "additionalProperties": {"const": true}}
+    }});
+    let record = json!({"tags": [1], "meta": {"a": true}});
+    for _ in 0..50 {
+        if let Some((_, violation)) = break_record(&record, &schema) {
+            assert!(!["maximum", "maxProperties"].contains(&violation.rule.as_str()));
+        }
+    }
+}
+
+#[test]
+fn violations_written_to_a_separate_file() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pollinate"))
+        .args([
+            "-i",
+            "test_data/person_schema.json",
+            "-c",
+            "20",
+            "-o",
+            "invalid_people.ndjson",
+        ])
+        .args([
+            "--invalid",
+            "--violations",
+            "invalid_people.violations.ndjson",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let records = fs::read_to_string("invalid_people.ndjson").unwrap();
+    let violations = fs::read_to_string("invalid_people.violations.ndjson").unwrap();
+    assert_eq!(violations.lines().count(), 20);
+    let schema = pollinate::schema::read_schema("test_data/person_schema.json").unwrap();
+    for (i, (record, violation)) in records.lines().zip(violations.lines()).enumerate() {
+        let violation: Value = serde_json::from_str(violation).unwrap();
+        let errors = validate(&serde_json::from_str(record).unwrap(), &schema);
+        assert_eq!(violation["record"], i + 1);
+        assert_eq!(violation["path"], errors[0].path);
+    }
+    fs::remove_file("invalid_people.ndjson").unwrap();
+    fs::remove_file("invalid_people.violations.ndjson").unwrap();
+}
@@ -0,0 +1,102 @@
+use pollinate::{schema::Template, validate::Validator};
+use serde_json::json;
+use std::sync::mpsc;
+
+fn schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "string", "format": "uuid"},
+            "age": {"type": "integer", "minimum": 0, "maximum": 100},
+            "tags": {"type": "array", "items": {"type": "string", "maxLength": 6}, "maximum": 3},
+            "owner": {"type": "object", "properties": {
+                "name": {"type": "string", "maxLength": 8},
+                "score": {"type": "number", "minimum": 0, "maximum": 1}
+            }}
+        }
+    })
+}
+
+#[test]
+fn seeds_repeat_records() {
+    // separately built templates, so nothing depends on the order of their fields
+    let first: Vec<_> = Template::new(&schema())
+        .unwrap()
+        .iter_with_seed(7)
+        .take(20)
+        .collect();
+    let second: Vec<_> = Template::new(&schema())
+        .unwrap()
+        .iter_with_seed(7)
+        .take(20)
+        .collect();
+    assert_eq!(first, second);
+    let other: Vec<_> = Template::new(&schema())
+        .unwrap()
+        .iter_with_seed(8)
+        .take(20)
+        .collect();
+    assert_ne!(first, other);
+
+    let mut validator = Validator::new(&schema());
+    assert!(first.iter().all(|x| validator.validate(x).is_empty()));
+}
+
+#[test]
+fn iterators_interleave() {
+    let template = Template::new(&schema()).unwrap();
+    let alone: Vec<_> = template.iter_with_seed(1).take(10).collect();
+    let pairs: Vec<_> = template
+        .iter_with_seed(1)
+        .zip(template.iter_with_seed(2))
+        .take(10)
+        .collect();
+    assert_eq!(pairs.iter().map(|x| x.0.clone()).collect::<Vec<_>>(), alone);
+    assert!(pairs.iter().all(|(a, b)| a != b));
+
+    let ids = Template::new(&json!({"type": "object", "properties": {
+        "id": {"type": "integer", "x-sequence": {"start": 10, "step": 5}}
+    }}))
+    .unwrap();
+    let (sender, receiver) = mpsc::channel();
+    for record in ids.iter().take(3) {
+        sender.send(record).unwrap();
+    }
+    drop(sender);
+    let sent: Vec<_> = receiver.iter().map(|x| x["id"].clone()).collect();
+    assert_eq!(sent, vec![json!(10), json!(15), json!(20)]);
+}
+
+#[test]
+fn iterators_start_over_after_generate() {
+    let template = Template::new(&json!({"type": "object", "properties": {
+        "id": {"type": "integer", "x-sequence": {"start": 1, "scope": "global"}},
+        "code": {"type": "integer", "minimum": 0, "maximum": 4, "x-unique": true}
+    }}))
+    .unwrap();
+    let generated: Vec<_> = (0..3).map(|_| template.generate()["id"].clone()).collect();
+    assert_eq!(generated, vec![json!(1), json!(2), json!(3)]);
+
+    let records: Vec<_> = template.iter().take(5).collect();
+    let ids: Vec<_> = records.iter().map(|x| x["id"].clone()).collect();
+    assert_eq!(ids, (1..=5).map(|x| json!(x)).collect::<Vec<_>>());
+    let mut codes: Vec<_> = records
+        .iter()
+        .map(|x| x["code"].as_i64().unwrap())
+        .collect();
+    codes.sort();
+    assert_eq!(codes, vec![0, 1, 2, 3, 4]);
+
+    assert_eq!(template.generate()["id"], json!(4));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn records_stream() {
+    use futures::{executor::block_on, StreamExt};
+
+    let template = Template::new(&schema()).unwrap();
+    let streamed: Vec<_> = block_on(template.stream_with_seed(3).take(5).collect());
+    let iterated: Vec<_> = template.iter_with_seed(3).take(5).collect();
+    assert_eq!(streamed, iterated);
+}
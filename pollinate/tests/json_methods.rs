@@ -56,7 +56,7 @@ fn return_json_object_array() {
     schema.insert("last_name".to_owned(), last_names);
     schema.insert("zip_code".to_owned(), zips);
 
-    let val = create_json_vec_from_schema(&schema, 100);
+    let val = create_json_vec_from_schema(&schema, 100).unwrap();
     println!("{:?}", val);
 }
 
@@ -91,7 +91,7 @@ fn dump_json_vec_from_schema() {
     schema.insert("last_name".to_owned(), last_names);
     schema.insert("zip_code".to_owned(), zips);
 
-    let val = create_json_vec_from_schema(&schema, 5000);
+    let val = create_json_vec_from_schema(&schema, 5000).unwrap();
     let _ = dump_json_array(&val, "temp.json");
     println!("{:?}", val);
 }
@@ -111,7 +111,7 @@ fn test_nested_object() {
     outer_schema.insert("first_name".to_owned(), first_names);
     outer_schema.insert("last_name".to_owned(), last_names);
     outer_schema.insert("address".to_owned(), nested_schema);
-    let val = create_json_vec_from_schema(&outer_schema, 10);
+    let val = create_json_vec_from_schema(&outer_schema, 10).unwrap();
     _ = dump_json_array(&val, "nested.json");
 }
 
@@ -132,6 +132,148 @@ fn test_all_three_types() {
     outer_schema.insert("last_name".to_owned(), last_names);
     outer_schema.insert("age".to_owned(), ages);
     outer_schema.insert("address".to_owned(), nested_schema);
-    let val = create_json_vec_from_schema(&outer_schema, 10);
+    let val = create_json_vec_from_schema(&outer_schema, 10).unwrap();
     _ = dump_json_array(&val, "nested.json");
 }
+
+#[test]
+fn stream_ndjson_to_file() {
+    let ages = Box::new(RangedValues::new(12, 37)) as Box<dyn Values>;
+    let mut schema = HashMap::new();
+    schema.insert("age".to_owned(), ages);
+    let file = std::env::temp_dir().join("pollinate_stream.ndjson");
+    let file = file.to_str().unwrap();
+    let options = WriterOptions {
+        format: OutputFormat::Ndjson,
+        ..Default::default()
+    };
+    let mut writer = create_record_writer(file, &json!({}), 1000, &options).unwrap();
+    stream_json_from_schema(&schema, 1000, writer.as_mut()).unwrap();
+    let contents = std::fs::read_to_string(file).unwrap();
+    assert_eq!(contents.lines().count(), 1000);
+    for line in contents.lines() {
+        let record: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(record["age"].is_i64());
+    }
+}
+
+#[test]
+fn stream_json_array_matches_dump() {
+    let mut schema = HashMap::new();
+    schema.insert(
+        "zip".to_owned(),
+        Box::new(DiscreteValues::new(&[json!(97012)])) as Box<dyn Values>,
+    );
+    let mut output = Vec::new();
+    stream_json_from_schema(&schema, 3, &mut JsonWriter::new(&mut output)).unwrap();
+    let expected =
+        serde_json::to_string(&create_json_vec_from_schema(&schema, 3).unwrap()).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+    let mut output = Vec::new();
+    stream_json_from_schema(&schema, 0, &mut JsonWriter::new(&mut output)).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "[]");
+}
+
+#[test]
+fn yaml_stream_round_trip() {
+    let records = vec![
+        json!({"name": "Adam", "address": {"zip": 97012}, "tags": ["a", "b"]}),
+        json!({"name": "Ted", "address": null, "tags": []}),
+    ];
+    let mut output = Vec::new();
+    let mut writer = YamlWriter::new(&mut output);
+    for record in records.iter() {
+        writer.write_record(record).unwrap();
+    }
+    writer.finish().unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let parsed: Vec<serde_json::Value> = serde_yaml::Deserializer::from_str(&output)
+        .map(|document| serde::Deserialize::deserialize(document).unwrap())
+        .collect();
+    assert_eq!(parsed, records);
+}
+
+#[test]
+fn toml_array_of_tables_round_trip() {
+    let records = [
+        json!({"name": "Adam", "pets": [{"name": "Rex"}, {"name": "Tom"}], "partner": {"age": 30}}),
+        json!({"name": "Ted", "pets": [], "partner": {"age": 41, "nickname": null}}),
+    ];
+    let mut output = Vec::new();
+    let mut writer = TomlWriter::new(&mut output);
+    for record in records.iter() {
+        writer.write_record(record).unwrap();
+    }
+    writer.finish().unwrap();
+    let parsed: serde_json::Value = toml::from_str(&String::from_utf8(output).unwrap()).unwrap();
+    assert_eq!(
+        parsed["records"],
+        json!([
+            {"name": "Adam", "pets": [{"name": "Rex"}, {"name": "Tom"}], "partner": {"age": 30}},
+            {"name": "Ted", "pets": [], "partner": {"age": 41}}
+        ])
+    );
+}
+
+#[test]
+fn canonical_json_matches_rfc_8785() {
+    let value: serde_json::Value = serde_json::from_str(
+        r#"{
+            "numbers": [333333333.3333333, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "€$\u000F\u000aA'B\"\\\\\"\/",
+            "literals": [null, true, false]
+        }"#,
+    )
+    .unwrap();
+    let mut output = Vec::new();
+    write_json(&mut output, &value, &JsonStyle::canonical()).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+    );
+}
+
+#[test]
+fn canonical_json_keeps_large_integers_exact() {
+    let value = json!([9007199254740993_i64, u64::MAX, i64::MIN, 1.0]);
+    let mut output = Vec::new();
+    write_json(&mut output, &value, &JsonStyle::canonical()).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "[9007199254740993,18446744073709551615,-9223372036854775808,1]"
+    );
+}
+
+#[test]
+fn canonical_json_sorts_keys_by_utf16() {
+    let value: serde_json::Value = serde_json::from_str(
+        r#"{"\u20ac": 1, "\r": 2, "\ufb33": 3, "1": 4, "\ud83d\ude00": 5, "\u0080": 6, "\u00f6": 7}"#,
+    )
+    .unwrap();
+    let mut output = Vec::new();
+    write_json(&mut output, &value, &JsonStyle::canonical()).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "{\"\\r\":2,\"1\":4,\"\u{80}\":6,\"\u{f6}\":7,\"\u{20ac}\":1,\"\u{1f600}\":5,\"\u{fb33}\":3}"
+    );
+}
+
+#[test]
+fn pretty_json_array_writer() {
+    let mut output = Vec::new();
+    let mut writer = JsonWriter::new(&mut output).with_style(JsonStyle::pretty(2));
+    writer.write_record(&json!({"b": 1, "a": []})).unwrap();
+    writer.write_record(&json!({"b": 2, "a": [true]})).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "[\n  {\n    \"a\": [],\n    \"b\": 1\n  },\n  {\n    \"a\": [\n      true\n    ],\n    \"b\": 2\n  }\n]\n"
+    );
+
+    let mut output = Vec::new();
+    let mut writer = NdjsonWriter::new(&mut output).canonical();
+    writer.write_record(&json!({"b": 1.0, "a": 1e-7})).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "{\"a\":1e-7,\"b\":1}\n");
+}
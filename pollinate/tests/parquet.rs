@@ -0,0 +1,138 @@
+use arrow_array::{Array, Date32Array, Int64Array, StructArray};
+use arrow_schema::DataType;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use pollinate::{
+    json_utils::{create_record_writer, stream_json_from_schema, OutputFormat, WriterOptions},
+    parquet_utils::{arrow_schema_from_schema, ParquetWriter},
+    schema::{generate_template_from_value, read_schema},
+};
+use serde_json::{json, Value};
+use std::fs::File;
+
+fn write_parquet(schema: &Value, file_name: &str, count: u32, row_group_size: usize) -> File {
+    let template = generate_template_from_value(schema).unwrap();
+    let path = std::env::temp_dir().join(file_name);
+    let path = path.to_str().unwrap();
+    assert_eq!(OutputFormat::from_path(path), Some(OutputFormat::Parquet));
+    let options = WriterOptions {
+        format: OutputFormat::Parquet,
+        row_group_size,
+        ..Default::default()
+    };
+    let mut writer = create_record_writer(path, schema, count, &options).unwrap();
+    stream_json_from_schema(&template, count, writer.as_mut()).unwrap();
+    let output = File::open(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    output
+}
+
+#[test]
+fn parquet_row_groups_from_person_schema() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let output = write_parquet(&schema, "pollinate_people.parquet", 1050, 100);
+    let builder = ParquetRecordBatchReaderBuilder::try_new(output).unwrap();
+    assert_eq!(builder.metadata().num_row_groups(), 11);
+    assert_eq!(builder.metadata().file_metadata().num_rows(), 1050);
+    let arrow_schema = builder.schema().clone();
+    assert!(matches!(
+        arrow_schema.field_with_name("partner").unwrap().data_type(),
+        DataType::Struct(_)
+    ));
+    assert!(matches!(
+        arrow_schema.field_with_name("skills").unwrap().data_type(),
+        DataType::List(_)
+    ));
+    let batches: Vec<_> = builder.build().unwrap().map(|x| x.unwrap()).collect();
+    let partner = batches[0]
+        .column_by_name("partner")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StructArray>()
+        .unwrap();
+    let ages = partner
+        .column_by_name("age")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .unwrap();
+    assert!(ages.iter().all(|x| (0..=100).contains(&x.unwrap())));
+}
+
+#[test]
+fn parquet_nullable_and_typed_columns() {
+    let schema = json!({
+        "type": "object",
+        "required": ["joined"],
+        "properties": {
+            "joined": {"type": "string", "format": "date"},
+            "seen": {"type": "string", "format": "date-time"},
+            "nickname": {"type": ["string", "null"]},
+            "scores": {"type": "object", "maxProperties": 3, "additionalProperties": {"type": "integer", "minimum": 0, "maximum": 9}}
+        }
+    });
+    let output = write_parquet(&schema, "pollinate_typed.parquet", 50, 1000);
+    let builder = ParquetRecordBatchReaderBuilder::try_new(output).unwrap();
+    let arrow_schema = builder.schema().clone();
+    let joined = arrow_schema.field_with_name("joined").unwrap();
+    assert_eq!(joined.data_type(), &DataType::Date32);
+    assert!(!joined.is_nullable());
+    assert!(matches!(
+        arrow_schema.field_with_name("seen").unwrap().data_type(),
+        DataType::Timestamp(_, _)
+    ));
+    assert!(arrow_schema
+        .field_with_name("nickname")
+        .unwrap()
+        .is_nullable());
+    assert!(matches!(
+        arrow_schema.field_with_name("scores").unwrap().data_type(),
+        DataType::Map(_, _)
+    ));
+    let batches: Vec<_> = builder.build().unwrap().map(|x| x.unwrap()).collect();
+    let dates = batches[0]
+        .column_by_name("joined")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Date32Array>()
+        .unwrap();
+    assert_eq!(dates.null_count(), 0);
+    assert_eq!(batches.iter().map(|x| x.num_rows()).sum::<usize>(), 50);
+}
+
+#[test]
+fn empty_row_groups_are_rejected() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let result = ParquetWriter::new(Vec::new(), &schema, 0);
+    assert_eq!(
+        result.err().unwrap().kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn one_of_columns_take_the_shared_type() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "id": {"oneOf": [
+                {"type": "integer", "minimum": 0, "maximum": 9},
+                {"type": "integer", "minimum": 100, "maximum": 109}
+            ]}
+        }
+    });
+    let arrow = arrow_schema_from_schema(&schema).unwrap();
+    assert_eq!(
+        arrow.field_with_name("id").unwrap().data_type(),
+        &DataType::Int64
+    );
+
+    let schema = json!({
+        "type": "object",
+        "properties": {"id": {"oneOf": [{"type": "integer"}, {"type": "string"}]}}
+    });
+    let result = ParquetWriter::new(Vec::new(), &schema, 10);
+    assert_eq!(
+        result.err().unwrap().kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+}
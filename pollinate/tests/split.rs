@@ -0,0 +1,104 @@
+use pollinate::{
+    default_values::seed_generator_rng,
+    json_utils::{
+        create_json_from_schema, stream_json_from_schema, OutputFormat, RecordWriter, WriterOptions,
+    },
+    schema::{generate_template_from_value, read_schema},
+    split_utils::{OutputTemplate, SplitWriter},
+};
+use serde_json::{json, Value};
+use std::fs;
+use std::num::NonZeroU32;
+
+#[test]
+fn split_into_files_with_manifest() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let options = WriterOptions {
+        format: OutputFormat::Ndjson,
+        ..WriterOptions::default()
+    };
+    let output = OutputTemplate::parse("split_test/{index:03}/people.ndjson").unwrap();
+    let mut writer = SplitWriter::new(output, &schema, 7, NonZeroU32::new(3).unwrap(), options)
+        .with_manifest("split_test/manifest.json");
+    stream_json_from_schema(&template, 7, &mut writer).unwrap();
+
+    let manifest: Value =
+        serde_json::from_str(&fs::read_to_string("split_test/manifest.json").unwrap()).unwrap();
+    assert_eq!(
+        manifest,
+        json!({"files": [
+            {"path": "split_test/000/people.ndjson", "records": 3},
+            {"path": "split_test/001/people.ndjson", "records": 3},
+            {"path": "split_test/002/people.ndjson", "records": 1},
+        ]})
+    );
+    let last = fs::read_to_string("split_test/002/people.ndjson").unwrap();
+    assert_eq!(last.lines().count(), 1);
+    fs::remove_dir_all("split_test").unwrap();
+}
+
+#[test]
+fn name_files_after_fields() {
+    let schema = json!({"type": "object", "properties": {"id": {"type": "integer", "minimum": 0, "maximum": 9}}});
+    let output = OutputTemplate::parse("split_fields/{field:id}-{uuid}.json").unwrap();
+    let mut writer = SplitWriter::new(
+        output,
+        &schema,
+        2,
+        NonZeroU32::MIN,
+        WriterOptions::default(),
+    );
+    writer.write_record(&json!({"id": 4})).unwrap();
+    writer.write_record(&json!({"id": 7})).unwrap();
+    writer.finish().unwrap();
+
+    let files = writer.files();
+    assert_eq!(files.len(), 2);
+    assert!(files[1].0.starts_with("split_fields/7-"));
+    assert_eq!(files[1].0.len(), "split_fields/7-.json".len() + 36);
+    assert_eq!(fs::read_to_string(&files[0].0).unwrap(), r#"{"id":4}"#);
+    fs::remove_dir_all("split_fields").unwrap();
+}
+
+#[test]
+fn field_values_stay_in_their_directory() {
+    let template = OutputTemplate::parse("out/{field:a}/{field:b}/{field:c}.json").unwrap();
+    let record = json!({"a": "..", "b": ".", "c": "../x"});
+    assert_eq!(template.render(0, &record).unwrap(), "out/__/_/.._x.json");
+}
+
+#[test]
+fn uuids_leave_seeded_records_unchanged() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let output = OutputTemplate::parse("out/{uuid}.json").unwrap();
+    seed_generator_rng(7);
+    let first: Vec<Value> = (0..3)
+        .map(|_| {
+            output.render(0, &json!({})).unwrap();
+            create_json_from_schema(&template)
+        })
+        .collect();
+    seed_generator_rng(7);
+    let second: Vec<Value> = (0..3).map(|_| create_json_from_schema(&template)).collect();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn reject_invalid_templates() {
+    assert!(OutputTemplate::parse("out/{index.json").is_err());
+    assert!(OutputTemplate::parse("out/{name}.json").is_err());
+    assert!(OutputTemplate::parse("out/{index:five}.json").is_err());
+
+    let schema = json!({"type": "object", "properties": {"id": {"type": "integer", "minimum": 0, "maximum": 9}}});
+    let output = OutputTemplate::parse("split_missing/{field:name}.json").unwrap();
+    let mut writer = SplitWriter::new(
+        output,
+        &schema,
+        1,
+        NonZeroU32::MIN,
+        WriterOptions::default(),
+    );
+    assert!(writer.write_record(&json!({"id": 1})).is_err());
+}
@@ -0,0 +1,121 @@
+use pollinate::{
+    json_utils::{stream_json_from_schema, RecordWriter},
+    schema::{generate_template_from_value, read_schema},
+    sql_utils::*,
+};
+use serde_json::json;
+
+#[test]
+fn create_table_maps_types() {
+    let schema = json!({
+        "title": "User",
+        "properties": {
+            "id": {"type": "integer", "x-sequence": {}},
+            "code": {"type": "string", "x-sequence": {"format": "U-{}"}},
+            "score": {"type": "number"},
+            "active": {"type": "boolean"},
+            "born": {"type": "string", "format": "date"},
+            "seen": {"type": ["string", "null"], "format": "date-time"},
+            "key": {"type": "string", "format": "uuid"}
+        }
+    });
+    let tables = tables_from_schema(&schema, "user", SqlNesting::Json);
+    assert_eq!(
+        create_table_sql(&tables, 0, SqlDialect::Postgres),
+        "CREATE TABLE \"user\" (\n  \"active\" BOOLEAN NOT NULL,\n  \"born\" DATE NOT NULL,\n  \
+         \"code\" TEXT NOT NULL,\n  \"id\" BIGINT NOT NULL,\n  \"key\" UUID NOT NULL,\n  \
+         \"score\" DOUBLE PRECISION NOT NULL,\n  \"seen\" TIMESTAMPTZ\n);\n"
+    );
+}
+
+#[test]
+fn inserts_are_batched() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let tables = tables_from_schema(&schema, "person", SqlNesting::Tables);
+    let mut output = Vec::new();
+    let mut writer = SqlWriter::new(&mut output, tables, SqlDialect::Sqlite);
+    stream_json_from_schema(&template, 250, &mut writer).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.matches("CREATE TABLE").count(), 2);
+    assert_eq!(output.matches("INSERT INTO \"person\" ").count(), 3);
+    assert_eq!(output.matches("INSERT INTO \"person_partner\" ").count(), 3);
+    assert!(output.ends_with("COMMIT;\n"));
+}
+
+#[test]
+fn values_are_escaped() {
+    let schema = json!({"properties": {"note": {"type": "string"}, "tags": {"type": "array"}}});
+    let tables = tables_from_schema(&schema, "notes", SqlNesting::Json);
+    let mut output = Vec::new();
+    let mut writer = SqlWriter::new(&mut output, tables, SqlDialect::Mysql);
+    writer
+        .write_record(&json!({"note": "it's a \\ test", "tags": ["o'k"]}))
+        .unwrap();
+    writer.finish().unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("('it''s a \\\\ test', '[\"o''k\"]')"));
+}
+
+#[test]
+fn sqlite_child_tables_for_arrays_of_objects() {
+    let schema = json!({
+        "title": "Order",
+        "properties": {
+            "id": {"type": "integer", "x-sequence": {}},
+            "lines": {
+                "type": "array",
+                "minimum": 0,
+                "maximum": 3,
+                "items": {
+                    "type": "object",
+                    "properties": {"sku": {"type": "string", "enum": ["A", "B"]}}
+                }
+            }
+        }
+    });
+    let file = std::env::temp_dir().join("pollinate_orders.db");
+    let file = file.to_str().unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let tables = tables_from_schema(&schema, "order", SqlNesting::Tables);
+    let mut writer = SqliteWriter::open(file, tables).unwrap();
+    let mut lines = 0;
+    for _ in 0..20 {
+        let record = pollinate::json_utils::create_json_from_schema(&template);
+        lines += record["lines"].as_array().unwrap().len() as i64;
+        writer.write_record(&record).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let connection = rusqlite::Connection::open(file).unwrap();
+    let count = |sql: &str| -> i64 { connection.query_row(sql, [], |row| row.get(0)).unwrap() };
+    assert_eq!(count("SELECT COUNT(*) FROM \"order\""), 20);
+    assert_eq!(count("SELECT COUNT(*) FROM order_lines"), lines);
+    assert_eq!(
+        count(
+            "SELECT COUNT(*) FROM order_lines l LEFT JOIN \"order\" o \
+             ON l._parent_id = o._id WHERE o._id IS NULL"
+        ),
+        0
+    );
+}
+
+#[test]
+fn tables_without_columns_get_a_key() {
+    let schema = json!({"title": "Empty", "properties": {}});
+    let tables = tables_from_schema(&schema, "empty", SqlNesting::Json);
+    assert_eq!(
+        create_table_sql(&tables, 0, SqlDialect::Postgres),
+        "CREATE TABLE \"empty\" (\n  \"_id\" BIGINT PRIMARY KEY\n);\n"
+    );
+    let mut output = Vec::new();
+    let mut writer = SqlWriter::new(&mut output, tables.clone(), SqlDialect::Postgres);
+    writer.write_record(&json!({})).unwrap();
+    writer.finish().unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("INSERT INTO \"empty\" (\"_id\") VALUES\n  (1);"));
+
+    let mut writer = SqliteWriter::open(":memory:", tables).unwrap();
+    writer.write_record(&json!({})).unwrap();
+    writer.finish().unwrap();
+}
@@ -0,0 +1,65 @@
+use pollinate::{
+    json_utils::{create_record_writer, stream_json_from_schema, RecordWriter, WriterOptions},
+    schema::{generate_template_from_value, read_schema},
+    template_utils::{TemplateWriter, TextTemplate},
+};
+use serde_json::json;
+use std::fs;
+
+#[test]
+fn fixed_width_records_from_schema() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let options = WriterOptions {
+        template: Some(
+            TextTemplate::parse("{{pad firstName 8}}{{pad_left age 3 fill=\"0\"}}\n").unwrap(),
+        ),
+        ..WriterOptions::default()
+    };
+    let mut writer = create_record_writer("fixed_width.txt", &schema, 20, &options).unwrap();
+    stream_json_from_schema(&template, 20, writer.as_mut()).unwrap();
+
+    let text = fs::read_to_string("fixed_width.txt").unwrap();
+    assert_eq!(text.lines().count(), 20);
+    for line in text.lines() {
+        assert_eq!(line.len(), 11);
+        assert!(line[8..].chars().all(|x| x.is_ascii_digit()));
+    }
+    fs::remove_file("fixed_width.txt").unwrap();
+}
+
+#[test]
+fn header_and_footer_sections() {
+    let source = "{{!-- header --}}\nBEGIN {{count}}\n{{!-- footer --}}\nEND\n{{!-- record --}}\n{{#each tags}}[{{lower this}}]{{/each}}\n";
+    let template = TextTemplate::parse(source).unwrap();
+    let mut output = Vec::new();
+    let mut writer = TemplateWriter::new(&mut output, template.clone(), 2);
+    writer.write_record(&json!({"tags": ["A", "B"]})).unwrap();
+    writer.write_record(&json!({"tags": []})).unwrap();
+    writer.finish().unwrap();
+    drop(writer);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "BEGIN 2\n[a][b]\n\nEND\n"
+    );
+
+    let mut output = Vec::new();
+    TemplateWriter::new(&mut output, template, 0)
+        .finish()
+        .unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "BEGIN 0\nEND\n");
+}
+
+#[test]
+fn formatting_helpers() {
+    let template = TextTemplate::parse(
+        "{{upper name}}|{{pad name 3}}|{{fixed price 2}}|{{xml note}}|{{json tags}}|{{missing}}",
+    )
+    .unwrap();
+    let record = json!({"name": "apples", "price": 1.5, "note": "<a & b>", "tags": ["x"]});
+    assert_eq!(
+        template.render_record(&record).unwrap(),
+        "APPLES|app|1.50|&lt;a &amp; b&gt;|[\"x\"]|"
+    );
+    assert!(TextTemplate::parse("{{#each items}}").is_err());
+}
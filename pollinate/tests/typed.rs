@@ -0,0 +1,156 @@
+use pollinate::{schema::Template, typed::schema_from_json_schema, validate::Validator};
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Partner {
+    first_name: String,
+    age: u8,
+    skills: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Person {
+    first_name: String,
+    age: u8,
+    skills: Vec<String>,
+    partner: Partner,
+}
+
+#[test]
+fn records_fill_structs() {
+    let template = Template::from_file("test_data/person_schema.json").unwrap();
+    for _ in 0..100 {
+        let person: Person = pollinate::generate(&template).unwrap();
+        assert!(["Adam", "John", "Jacob"].contains(&person.first_name.as_str()));
+        assert!(person.age <= 100 && person.partner.age <= 100);
+        assert!(person.skills.len() <= 5 && person.partner.skills.len() <= 5);
+        assert!(!person.partner.first_name.is_empty());
+    }
+    let wrong: Result<Vec<u8>, _> = pollinate::generate(&template);
+    assert!(wrong.is_err());
+}
+
+#[test]
+fn json_schemas_are_converted() {
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Tree",
+        "type": "object",
+        "properties": {
+            "root": {"$ref": "#/$defs/Node"},
+            "kind": {"const": "tree"},
+            "size": {"type": "integer", "format": "int8"},
+            "labels": {"type": "array", "items": {"type": "string"}, "minItems": 1, "maxItems": 2},
+            "shape": {"oneOf": [
+                {"type": "object", "properties": {"circle": {"type": "number"}}, "additionalProperties": false},
+                {"type": "string", "enum": ["point"]}
+            ]}
+        },
+        "$defs": {
+            "Node": {
+                "type": "object",
+                "properties": {
+                    "value": {"type": "integer", "format": "uint16", "minimum": 0},
+                    "next": {"anyOf": [{"$ref": "#/$defs/Node"}, {"type": "null"}]}
+                }
+            }
+        }
+    });
+    let converted = schema_from_json_schema(&schema).unwrap();
+    assert_eq!(
+        converted["properties"]["kind"],
+        json!({"type": "string", "enum": ["tree"]})
+    );
+    assert_eq!(converted["properties"]["size"]["minimum"], json!(-128));
+    assert_eq!(
+        converted["properties"]["root"]["properties"]["next"],
+        json!({"type": "null"})
+    );
+    assert!(converted.get("$defs").is_none());
+
+    let mut validator = Validator::new(&converted);
+    for record in Template::new(&converted).unwrap().iter().take(100) {
+        assert_eq!(validator.validate(&record), vec![]);
+        assert!(record["root"]["value"].as_u64().unwrap() <= u16::MAX.into());
+        assert!((1..=2).contains(&record["labels"].as_array().unwrap().len()));
+    }
+}
+
+#[test]
+fn unsupported_json_schemas_are_errors() {
+    let unsupported = [
+        json!({"$ref": "https://example.com/user.json"}),
+        json!({"allOf": [{"type": "string"}, {"maxLength": 3}]}),
+        json!({"type": "array", "prefixItems": [{"type": "number"}]}),
+        json!({"description": "anything"}),
+        json!({"$ref": "#/$defs/Missing"}),
+        json!(true),
+    ];
+    for property in unsupported {
+        let schema = json!({"type": "object", "properties": {"value": property}});
+        let error = schema_from_json_schema(&schema).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+    assert!(schema_from_json_schema(&json!({"type": "string"})).is_err());
+}
+
+#[cfg(feature = "schemars")]
+#[test]
+// most fields are only checked by deserializing them
+#[allow(dead_code)]
+fn schemars_types_are_generated() {
+    use schemars::JsonSchema;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, JsonSchema)]
+    enum Plan {
+        Free,
+        Pro,
+    }
+
+    #[derive(Debug, Deserialize, JsonSchema)]
+    #[serde(tag = "kind")]
+    enum Shape {
+        Circle { radius: f64 },
+        Square { side: f32 },
+    }
+
+    #[derive(Debug, Deserialize, JsonSchema)]
+    struct Comment {
+        text: String,
+        reply: Option<Box<Comment>>,
+    }
+
+    #[derive(Debug, Deserialize, JsonSchema)]
+    struct Account {
+        id: u64,
+        level: i8,
+        initial: char,
+        active: bool,
+        plan: Plan,
+        trial: Option<Plan>,
+        shapes: Vec<Shape>,
+        limits: HashMap<String, u16>,
+        comment: Option<Comment>,
+        nickname: Option<String>,
+    }
+
+    let schema = pollinate::typed::schema_for::<Account>().unwrap();
+    let mut validator = Validator::new(&schema);
+    let template = pollinate::typed::template_for::<Account>().unwrap();
+    for record in template.iter_with_seed(5).take(200) {
+        assert_eq!(validator.validate(&record), vec![]);
+        let account: Account = serde_json::from_value(record).unwrap();
+        assert_eq!(
+            account.initial.len_utf8(),
+            account.initial.to_string().len()
+        );
+        assert!(account.shapes.len() <= 5);
+        if let Some(comment) = account.comment {
+            assert!(comment.reply.is_none());
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use pollinate::{
+    json_utils::{stream_json_from_schema, NdjsonWriter},
+    schema::generate_template_from_value,
+    validate::{validate, ValidatingWriter, Validator},
+};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn keyword_schema() -> Value {
+    json!({
+        "title": "Order",
+        "type": "object",
+        "properties": {
+            "id": {"type": "integer", "x-sequence": {"start": 1}},
+            "code": {"type": "string", "x-sequence": {"format": "ORD-{:04}"}},
+            "email": {"type": "string", "format": "email", "x-unique": true},
+            "placed": {"type": "string", "format": "date-time"},
+            "due": {"type": "string", "format": "date"},
+            "uid": {"type": "string", "format": "uuid"},
+            "note": {"type": ["string", "null"], "minLength": 2, "maxLength": 6},
+            "price": {"type": "number", "minimum": 0.5, "maximum": 2.5},
+            "lines": {"type": "array", "minimum": 1, "maximum": 3, "items": {"type": "integer", "minimum": 1, "maximum": 4}},
+            "pet": {"oneOf": [{"type": "string", "enum": ["cat"]}, {"type": "integer", "minimum": 0, "maximum": 3}]},
+            "meta": {"type": "object", "additionalProperties": {"type": "integer", "minimum": 0, "maximum": 5}}
+        }
+    })
+}
+
+#[test]
+fn generated_records_are_valid() {
+    let schema = keyword_schema();
+    let template = generate_template_from_value(&schema).unwrap();
+    let mut writer = ValidatingWriter::new(Box::new(NdjsonWriter::new(std::io::sink())), &schema);
+    stream_json_from_schema(&template, 2000, &mut writer).unwrap();
+}
+
+#[test]
+fn errors_name_the_path() {
+    let schema = json!({
+        "type": "object",
+        "required": ["id"],
+        "additionalProperties": false,
+        "properties": {
+            "partner": {
+                "type": "object",
+                "properties": {
+                    "born": {"type": "string", "format": "date-time"},
+                    "skills": {"type": "array", "maximum": 5, "items": {"type": "string", "enum": ["Java", "Sales"]}}
+                }
+            },
+            "pet": {"oneOf": [{"type": "number"}, {"type": "integer"}]}
+        }
+    });
+    let record = json!({
+        "partner": {"born": "2021-13-01T00:00:00Z", "skills": ["Java", "Cooking"]},
+        "pet": 3,
+        "extra": true
+    });
+    let errors: Vec<String> = validate(&record, &schema)
+        .iter()
+        .map(|x| x.to_string())
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            "$: missing required property \"id\"",
+            "$: unexpected property \"extra\"",
+            "$.partner.born: \"2021-13-01T00:00:00Z\" is not a valid date-time",
+            "$.partner.skills[1]: \"Cooking\" is not one of [\"Java\",\"Sales\"]",
+            "$.pet: matches 2 of the oneOf schemas, expected 1",
+        ]
+    );
+
+    let mut validator = Validator::new(&keyword_schema());
+    let record = json!({"email": "a@example.com", "uid": "e3820c82-c8c4-44bd-9db4-d48651ecb24", "placed": "2021-03-04T05:06:07.5+01:00"});
+    let errors = validator.validate(&record);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, "$.uid");
+    let errors = validator.validate(&record);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(
+        errors[0].to_string(),
+        "$.email: \"a@example.com\" is repeated"
+    );
+}
+
+#[test]
+fn validate_command_reports_invalid_records() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pollinate"))
+        .args(["validate", "--schema", "test_data/person_schema.json", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let data =
+        "{\"age\": 30, \"skills\": [\"Java\"]}\n{\"age\": 130, \"partner\": {\"skills\": [1]}}\n";
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(data.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "record 2: $.age: 130 is greater than the maximum 100\nrecord 2: $.partner.skills[0]: expected string, found integer\n1 of 2 records are valid\n"
+    );
+}
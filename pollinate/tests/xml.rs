@@ -0,0 +1,122 @@
+use pollinate::{
+    json_utils::{create_record_writer, stream_json_from_schema, OutputFormat, WriterOptions},
+    schema::{generate_template_from_value, read_schema},
+    xml_utils::encode_xml,
+};
+use serde_json::json;
+use std::fs;
+
+#[test]
+fn xml_hints_shape_elements() {
+    let schema = json!({
+        "title": "order",
+        "type": "object",
+        "x-xml": {"namespace": "urn:example:orders", "prefix": "o"},
+        "properties": {
+            "id": {"type": "integer", "x-xml": {"attribute": true}},
+            "status": {"type": "string", "x-xml": {"attribute": true, "name": "state"}},
+            "note": {"type": ["string", "null"]},
+            "lines": {
+                "type": "array",
+                "x-xml": {"wrapped": true},
+                "items": {
+                    "type": "object",
+                    "x-xml": {"name": "line"},
+                    "properties": {"sku": {"type": "string", "x-xml": {"attribute": true}}, "qty": {"type": "integer"}}
+                }
+            },
+            "tags": {"type": "array", "items": {"type": "string"}, "x-xml": {"name": "tag"}},
+            "meta": {"type": "object", "additionalProperties": {"type": "integer"}}
+        }
+    });
+    let record = json!({
+        "id": 7,
+        "status": "new",
+        "note": null,
+        "lines": [{"sku": "A1", "qty": 2}],
+        "tags": ["x", "y"],
+        "meta": {"k": 1}
+    });
+    assert_eq!(
+        encode_xml(&record, &schema),
+        concat!(
+            r#"<o:order xmlns:o="urn:example:orders" id="7" state="new">"#,
+            r#"<lines><line sku="A1"><qty>2</qty></line></lines>"#,
+            r#"<meta><entry key="k">1</entry></meta>"#,
+            r#"<tag>x</tag><tag>y</tag>"#,
+            r#"</o:order>"#
+        )
+    );
+}
+
+#[test]
+fn xml_escapes_text_and_attributes() {
+    let schema = json!({
+        "type": "object",
+        "properties": {"title": {"type": "string", "x-xml": {"attribute": true}}, "body": {"type": "string"}}
+    });
+    let record = json!({"title": "\"A\" & <B>\n", "body": "1 < 2 & 'q'\u{1}"});
+    assert_eq!(
+        encode_xml(&record, &schema),
+        r#"<record title="&quot;A&quot; &amp; &lt;B&gt;&#10;"><body>1 &lt; 2 &amp; 'q'</body></record>"#
+    );
+}
+
+#[test]
+fn xml_names_are_sanitized() {
+    let schema = json!({
+        "title": "2 people",
+        "type": "object",
+        "properties": {
+            "first name": {"type": "string"},
+            "1st": {"type": "integer", "x-xml": {"attribute": true}},
+            "tags": {"type": "array", "items": {"type": "string"}, "x-xml": {"name": "a tag", "wrapped": true}}
+        }
+    });
+    let record = json!({"first name": "Ann", "1st": 1, "tags": ["x"]});
+    assert_eq!(
+        encode_xml(&record, &schema),
+        r#"<_2_people _1st="1"><first_name>Ann</first_name><a_tag><a_tag>x</a_tag></a_tag></_2_people>"#
+    );
+}
+
+#[test]
+fn xml_attribute_namespaces_are_declared() {
+    let schema = json!({
+        "title": "item",
+        "type": "object",
+        "x-xml": {"namespace": "urn:a", "prefix": "a"},
+        "properties": {
+            "id": {"type": "integer", "x-xml": {"attribute": true, "namespace": "urn:a", "prefix": "a"}},
+            "lang": {"type": "string", "x-xml": {"attribute": true, "namespace": "urn:b", "prefix": "b c"}},
+            "kind": {"type": "string", "x-xml": {"attribute": true, "namespace": "urn:b", "prefix": "b c"}}
+        }
+    });
+    let record = json!({"id": 1, "lang": "en", "kind": "x"});
+    assert_eq!(
+        encode_xml(&record, &schema),
+        r#"<a:item xmlns:a="urn:a" a:id="1" b_c:kind="x" xmlns:b_c="urn:b" b_c:lang="en"/>"#
+    );
+}
+
+#[test]
+fn xml_records_from_schema() {
+    let schema = read_schema("test_data/person_schema.json").unwrap();
+    let template = generate_template_from_value(&schema).unwrap();
+    let options = WriterOptions {
+        format: OutputFormat::Xml,
+        ..WriterOptions::default()
+    };
+    let mut writer = create_record_writer("people.xml", &schema, 4, &options).unwrap();
+    stream_json_from_schema(&template, 4, writer.as_mut()).unwrap();
+
+    let text = fs::read_to_string("people.xml").unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 7);
+    assert_eq!(lines[1], "<records>");
+    assert!(lines[2..6]
+        .iter()
+        .all(|x| x.starts_with("  <Person><age>") && x.ends_with("</Person>")));
+    assert_eq!(lines[6], "</records>");
+    fs::remove_file("people.xml").unwrap();
+}